@@ -0,0 +1,71 @@
+extern crate suzu;
+
+use suzu::core::embedded::embedded_resource;
+use suzu::core::error::{parse_toml_str, TomlNode};
+use suzu::core::texture_registry::register_textures_from_game_data;
+use suzu::core::SuzuError;
+use suzu::object::scenario::ScenarioElementPool;
+use suzu::object::scenario_graph::{ScenarioGraph, ScenarioGraphFormat};
+use suzu::object::scenario_script::parse_scenario_source;
+
+fn export_graph(path: &str, format: ScenarioGraphFormat) -> Result<String, SuzuError> {
+    let content = match embedded_resource(path) {
+        Some(source) => source.to_string(),
+        None => std::fs::read_to_string(path).map_err(|e| SuzuError::io(path, e))?,
+    };
+
+    let root = parse_scenario_source(path, &content)?;
+    let root = TomlNode::root(path, &root);
+
+    let first_id = root.get("first-scenario-id")?.as_integer()? as i32;
+    let pool = ScenarioElementPool::from_toml(&root, ggez::graphics::Font::default())?;
+
+    Ok(ScenarioGraph::from_pool(&pool, Some(first_id)).export(format))
+}
+
+///
+/// シナリオの分岐構造をグラフとして出力するツール
+///
+/// 使い方: suzu-graph [--mermaid] シナリオファイル...
+/// 標準ではGraphvizのDOT形式で出力する
+///
+pub fn main() {
+    let mut format = ScenarioGraphFormat::Dot;
+    let mut paths = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--mermaid" => format = ScenarioGraphFormat::Mermaid,
+            "--dot" => format = ScenarioGraphFormat::Dot,
+            _ => paths.push(arg),
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("usage: suzu-graph [--dot | --mermaid] <scenario.toml | scenario.suzu>...");
+        std::process::exit(2);
+    }
+
+    // game_data.tomlだけで宣言されているテクスチャの名前を登録しておく
+    let game_data_path = "./resources/game_data.toml";
+    if let Ok(content) = std::fs::read_to_string(game_data_path) {
+        if let Ok(root) = parse_toml_str(game_data_path, &content) {
+            register_textures_from_game_data(&root);
+        }
+    }
+
+    let mut failed = false;
+    for path in paths.iter() {
+        match export_graph(path, format) {
+            Ok(graph) => print!("{}", graph),
+            Err(e) => {
+                eprintln!("{}", e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
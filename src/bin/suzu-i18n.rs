@@ -0,0 +1,125 @@
+extern crate suzu;
+
+use std::path::PathBuf;
+
+use suzu::core::translation::TranslationCatalog;
+use suzu::core::util::{set_resource_directory, try_read_resource_text};
+use suzu::core::SuzuError;
+use suzu::object::scenario_script::parse_scenario_source;
+
+///
+/// ゲームと同じパスでファイルを読む
+/// "/"で始まるパスはリソースディレクトリからの相対パスとして扱う
+///
+fn read_source(path: &str) -> Result<toml::Value, SuzuError> {
+    let content = if path.starts_with('/') {
+        try_read_resource_text(path)?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| SuzuError::io(path, e))?
+    };
+
+    parse_scenario_source(path, &content)
+}
+
+fn extract(
+    locale: &str,
+    catalog_path: Option<&str>,
+    paths: &[String],
+) -> Result<TranslationCatalog, SuzuError> {
+    let mut catalog = match catalog_path {
+        Some(catalog_path) => {
+            let content = std::fs::read_to_string(catalog_path)
+                .map_err(|e| SuzuError::io(catalog_path, e))?;
+            TranslationCatalog::from_json(catalog_path, &content)?
+        }
+        None => TranslationCatalog::new(locale),
+    };
+
+    for path in paths.iter() {
+        let root = read_source(path)?;
+        let count = catalog.extract(path, &root)?;
+        eprintln!("{}: {} strings", path, count);
+    }
+
+    Ok(catalog)
+}
+
+fn inject(catalog_path: &str, path: &str) -> Result<String, SuzuError> {
+    let content =
+        std::fs::read_to_string(catalog_path).map_err(|e| SuzuError::io(catalog_path, e))?;
+    let catalog = TranslationCatalog::from_json(catalog_path, &content)?;
+
+    let mut root = read_source(path)?;
+    catalog.inject(path, &mut root);
+
+    toml::to_string(&root).map_err(|e| SuzuError::missing_asset(path, e))
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: suzu-i18n [--resources <dir>] extract [--locale <locale>] [--catalog <catalog.json>] <file>..."
+    );
+    eprintln!("       suzu-i18n [--resources <dir>] inject <catalog.json> <file>");
+    std::process::exit(2);
+}
+
+///
+/// 翻訳カタログを作るツール
+///
+/// extractはシナリオ, マップのイベント, タイトルメニューの文字列をJSONのカタログとして出力する
+/// --catalogで既存のカタログを渡すと, 原文が変わっていない訳文を残したまま更新する
+/// injectはカタログの訳文を埋め込んだTOMLを出力する
+///
+/// ファイルのパスはゲームが読み込むときと同じ書き方で渡す
+/// --resourcesでリソースディレクトリを指定する. 省略した場合は ./resources を使う
+///
+pub fn main() {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let resource_dir = if args.first().map(|arg| arg.as_str()) == Some("--resources") {
+        if args.len() < 2 {
+            usage();
+        }
+        let dir = args.remove(1);
+        args.remove(0);
+        PathBuf::from(dir)
+    } else {
+        PathBuf::from("./resources")
+    };
+    set_resource_directory(resource_dir);
+
+    let result = match args.first().map(|arg| arg.as_str()) {
+        Some("extract") => {
+            let mut locale = "en".to_string();
+            let mut catalog_path = None;
+            let mut paths = Vec::new();
+
+            let mut iter = args.iter().skip(1);
+            while let Some(arg) = iter.next() {
+                match arg.as_str() {
+                    "--locale" => locale = iter.next().cloned().unwrap_or_else(|| usage()),
+                    "--catalog" => {
+                        catalog_path = Some(iter.next().cloned().unwrap_or_else(|| usage()))
+                    }
+                    _ => paths.push(arg.clone()),
+                }
+            }
+
+            if paths.is_empty() {
+                usage();
+            }
+
+            extract(&locale, catalog_path.as_deref(), &paths).map(|catalog| catalog.to_json())
+        }
+        Some("inject") if args.len() == 3 => inject(&args[1], &args[2]),
+        _ => usage(),
+    };
+
+    match result {
+        Ok(output) => println!("{}", output),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
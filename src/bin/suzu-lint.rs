@@ -0,0 +1,36 @@
+extern crate suzu;
+
+use std::path::PathBuf;
+
+use suzu::object::scenario_lint::{lint_scenario_corpus, LintSeverity};
+
+///
+/// シナリオの検査ツール
+///
+/// 使い方: suzu-lint [リソースディレクトリ]
+/// リソースディレクトリを省略した場合は ./resources を検査する
+///
+pub fn main() {
+    let resource_dir = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("./resources"));
+
+    let diagnostics = lint_scenario_corpus(&resource_dir);
+
+    for diagnostic in diagnostics.iter() {
+        println!("{}", diagnostic);
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == LintSeverity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+
+    println!("{} error(s), {} warning(s)", errors, warnings);
+
+    if errors > 0 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,94 @@
+extern crate suzu;
+
+use std::path::Path;
+
+use suzu::core::resource_pack::ResourcePack;
+use suzu::core::SuzuError;
+
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<(String, Vec<u8>)>,
+) -> Result<(), SuzuError> {
+    let dir_str = dir.to_string_lossy().to_string();
+    let mut entries = std::fs::read_dir(dir)
+        .map_err(|e| SuzuError::io(&dir_str, e))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+            continue;
+        }
+
+        let path_str = path.to_string_lossy().to_string();
+        let bytes = std::fs::read(&path).map_err(|e| SuzuError::io(&path_str, e))?;
+
+        // パック内のパスは, resourcesと同じく"/"から始める
+        let relative = path.strip_prefix(root).unwrap();
+        let pack_path = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .fold(String::new(), |acc, name| format!("{}/{}", acc, name));
+        files.push((pack_path, bytes));
+    }
+
+    Ok(())
+}
+
+fn build(output: &str, dir: &str, encrypted: bool) -> Result<usize, SuzuError> {
+    let mut files = Vec::new();
+    collect_files(Path::new(dir), Path::new(dir), &mut files)?;
+    ResourcePack::write(Path::new(output), &files, encrypted)?;
+    Ok(files.len())
+}
+
+fn list(pack: &str) -> Result<Vec<String>, SuzuError> {
+    let pack = ResourcePack::open(Path::new(pack))?;
+    let mut paths = pack
+        .paths()
+        .map(|path| path.to_string())
+        .collect::<Vec<_>>();
+    paths.sort();
+    Ok(paths)
+}
+
+fn usage() -> ! {
+    eprintln!("usage: suzu-pack [--encrypt] <output.suzupack> <directory>");
+    eprintln!("       suzu-pack --list <pack.suzupack>");
+    std::process::exit(2);
+}
+
+///
+/// リソースパックを作るツール
+///
+/// ディレクトリの中身を, そのディレクトリを"/"としたパスでパックにまとめる
+/// 上書き用のパックでは, "/game_data.patch.toml"のようなパッチも一緒に入れられる
+///
+pub fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(|arg| arg.as_str()).collect::<Vec<_>>();
+
+    let result = match args.as_slice() {
+        ["--list", pack] => list(pack).map(|paths| {
+            for path in paths {
+                println!("{}", path);
+            }
+        }),
+        ["--encrypt", output, dir] => build(output, dir, true).map(|count| {
+            eprintln!("{}: {} files (encrypted)", output, count);
+        }),
+        [output, dir] => build(output, dir, false).map(|count| {
+            eprintln!("{}: {} files", output, count);
+        }),
+        _ => usage(),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -1,12 +1,31 @@
 pub mod book_management;
 pub mod crypt;
+pub mod embedded;
+pub mod error;
 pub mod game_system;
 pub mod map_parser;
+pub mod mod_loader;
+pub mod resource_pack;
+pub mod schema;
+pub mod save_format;
+pub mod simulation;
+pub mod story;
+pub mod texture_loader;
+pub mod texture_registry;
+pub mod translation;
 pub mod util;
 
+pub use error::SuzuError;
+pub use texture_registry::TextureID;
+
 use game_system::WeekWorkSchedule;
 use ggez::*;
 use ggez::{audio::SoundSource, graphics as ggraphics};
+use mod_loader::ModRegistry;
+use simulation::{EconomyData, SuzunaSimulator};
+use story::{StoryValue, StoryVariables};
+use texture_loader::{DecodedTexture, TexturePreloader};
+use translation::TranslationCatalog;
 
 use tdev::ProgramableKey;
 use torifune::core::Clock;
@@ -26,6 +45,7 @@ use ginput::mouse::MouseButton;
 
 use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet};
 use std::str::FromStr;
 
 use crate::{object::scenario_object::SuzunaStatusPageID, scene};
@@ -33,7 +53,7 @@ use crate::{
     object::{
         scenario_object::SuzunaAdAgencyType, task_object::tt_sub_component::BorrowingRecordBookData,
     },
-    parse_toml_file,
+    parse_toml_file, try_parse_toml_file,
 };
 
 use std::fs::File;
@@ -43,14 +63,17 @@ use serde::{Deserialize, Serialize};
 extern crate chrono;
 extern crate serde_json;
 
+use crate::object::map_object::MapEventList;
 use crate::object::scenario_object::SuzunaAdType;
+use crate::object::shop_object::{ShopClockConfig, SHOP_CLOCK_CONFIG_PATH};
+use book_management::{NewBookScheduleFile, NEW_BOOK_SCHEDULE_PATH};
 use number_to_jk::number_to_jk;
 
 extern crate num;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Debug)]
 pub struct Version {
     major: u8,
     minor: u8,
@@ -63,12 +86,23 @@ impl Version {
     }
     
     pub fn from_str(s: &str) -> Self {
-	let ver = s.rsplit(".").collect::<Vec<&str>>();
-	Version {
-	    major: ver[0].parse().unwrap(),
-	    minor: ver[1].parse().unwrap(),
-	    patch: ver[2].parse().unwrap(),
+	Self::try_from_str(s).unwrap()
+    }
+
+    ///
+    /// "major.minor.patch"の順に読む. 以前はrsplitで逆順に読んでいたため, 比較の結果が逆になっていた
+    ///
+    pub fn try_from_str(s: &str) -> Option<Self> {
+	let ver = s.trim().split(".").collect::<Vec<&str>>();
+	if ver.len() != 3 {
+	    return None;
 	}
+
+	Some(Version {
+	    major: ver[0].parse().ok()?,
+	    minor: ver[1].parse().ok()?,
+	    patch: ver[2].parse().ok()?,
+	})
     }
 
     pub fn to_string(&self) -> String {
@@ -116,120 +150,43 @@ impl InitialDisplay {
         }
     }
 
-    pub fn draw(&self, ctx: &mut ggez::Context) {
+    ///
+    /// ロゴと, 読み込みの進み具合を表すバーを描画する
+    /// progressは0.0から1.0
+    ///
+    pub fn draw(&self, ctx: &mut ggez::Context, progress: f32) {
         ggraphics::clear(ctx, [0.0, 0.0, 0.0, 0.0].into());
         let texture = self.texture.get(self.index);
 
         ggraphics::draw(ctx, texture.unwrap(), ggraphics::DrawParam::default()).unwrap();
 
+        let frame = ggraphics::Rect::new(383.0, 700.0, 600.0, 8.0);
+        let bar = ggraphics::Rect::new(
+            frame.x,
+            frame.y,
+            frame.w * progress.max(0.0).min(1.0),
+            frame.h,
+        );
+
+        for (rect, color) in &[(frame, 0x404040ffu32), (bar, 0xddddddff)] {
+            if rect.w <= 0.0 {
+                continue;
+            }
+
+            let mesh = ggraphics::Mesh::new_rectangle(
+                ctx,
+                ggraphics::DrawMode::fill(),
+                *rect,
+                ggraphics::Color::from_rgba_u32(*color),
+            )
+            .unwrap();
+            ggraphics::draw(ctx, &mesh, ggraphics::DrawParam::default()).unwrap();
+        }
+
         ggraphics::present(ctx).unwrap();
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub enum TextureID {
-    Ghost1 = 0,
-    LotusPink,
-    LotusBlue,
-    LotusYellow,
-    TextBackground,
-    Paper1,
-    Paper2,
-    LargeBook1,
-    LargeBook2,
-    LargeBook3,
-    MiddleBook1,
-    MiddleBook2,
-    MiddleBook3,
-    Wood1,
-    WafuTexture1,
-    WafuTexture2,
-    Chobo1,
-    ChoicePanel1,
-    ChoicePanel2,
-    ChoicePanel3,
-    ChoicePanel4,
-    ChoicePanel5,
-    JunkoTachieDefault,
-    SightBackground1,
-    ArrowRight,
-    ArrowLeft,
-    KosuzuDotFront1,
-    KosuzuDotFront2,
-    KosuzuDotFront3,
-    KosuzuDotBack1,
-    KosuzuDotBack2,
-    KosuzuDotBack3,
-    KosuzuDotRight1,
-    KosuzuDotRight2,
-    KosuzuDotRight3,
-    KosuzuDotLeft1,
-    KosuzuDotLeft2,
-    KosuzuDotLeft3,
-    StoreButton,
-    ResetButton,
-    MenuArt1,
-    MenuArt2,
-    JpHouseTexture,
-    BookBoxFront,
-    BookBoxBack,
-    Paper3,
-    Paper4,
-    Paper5,
-    Paper6,
-    Paper7,
-    Clock1,
-    ClockNeedle1,
-    ShortClockNeedle1,
-    Hanko,
-    NextLineIcon,
-    LargeBookScratchFair1,
-    LargeBookScratchFair2,
-    LargeBookScratchFair3,
-    LargeBookScratchFair4,
-    LargeBookScratchBad1,
-    LargeBookScratchBad2,
-    LargeBookScratchBad3,
-    LargeBookScratchBad4,
-    ManualPageBookTitles,
-    ManualPageBorrowingFlow,
-    ManualPageReturnFlow,
-    GoNextPageLeft,
-    GoNextPageRight,
-    Library,
-    KosuzuTachie1,
-    CheckCircle,
-    MoneyBox,
-    KosuzuSmile1,
-    Coin100Yen,
-    Coin50Yen,
-    Coin500Yen,
-    BaraBG,
-    SuzunaanMap,
-    Mob1TachieDefault,
-    SuzuLogo,
-    AkyuTachieDefault,
-    Mob1DotFront1,
-    Mob1DotFront2,
-    Mob1DotFront3,
-    Mob1DotBack1,
-    Mob1DotBack2,
-    Mob1DotBack3,
-    Mob1DotRight1,
-    Mob1DotRight2,
-    Mob1DotRight3,
-    Mob1DotLeft1,
-    Mob1DotLeft2,
-    Mob1DotLeft3,
-    GoNextPageLeftNotAlpha,
-    GoNextPageRightNotAlpha,
-    Knob,
-    NitoriTachieDefault,
-    NitoriTachieSunGlass,
-    TownBackground,
-    Unknown,
-}
-
 #[derive(Debug, Clone, Copy)]
 pub enum FontID {
     JpFude1,
@@ -251,226 +208,7 @@ impl FromStr for FontID {
     }
 }
 
-impl FromStr for TextureID {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, ()> {
-        match s {
-            "Ghost1" => Ok(Self::Ghost1),
-            "LotusPink" => Ok(Self::LotusPink),
-            "LotusBlue" => Ok(Self::LotusBlue),
-            "LotusYellow" => Ok(Self::LotusYellow),
-            "TextBackground" => Ok(Self::TextBackground),
-            "Paper1" => Ok(Self::Paper1),
-            "Paper2" => Ok(Self::Paper2),
-            "LargeBook1" => Ok(Self::LargeBook1),
-            "LargeBook2" => Ok(Self::LargeBook2),
-            "LargeBook3" => Ok(Self::LargeBook3),
-            "MiddleBook1" => Ok(Self::MiddleBook1),
-            "MiddleBook2" => Ok(Self::MiddleBook2),
-            "MiddleBook3" => Ok(Self::MiddleBook3),
-            "Wood1" => Ok(Self::Wood1),
-            "WafuTexture1" => Ok(Self::WafuTexture1),
-            "WafuTexture2" => Ok(Self::WafuTexture2),
-            "Chobo1" => Ok(Self::Chobo1),
-            "ChoicePanel1" => Ok(Self::ChoicePanel1),
-            "ChoicePanel2" => Ok(Self::ChoicePanel2),
-            "ChoicePanel3" => Ok(Self::ChoicePanel3),
-            "ChoicePanel4" => Ok(Self::ChoicePanel4),
-            "ChoicePanel5" => Ok(Self::ChoicePanel5),
-            "JunkoTachieDefault" => Ok(Self::JunkoTachieDefault),
-            "SightBackground1" => Ok(Self::SightBackground1),
-            "ArrowRight" => Ok(Self::ArrowRight),
-            "ArrowLeft" => Ok(Self::ArrowLeft),
-            "KosuzuDotFront1" => Ok(Self::KosuzuDotFront1),
-            "KosuzuDotFront2" => Ok(Self::KosuzuDotFront2),
-            "KosuzuDotFront3" => Ok(Self::KosuzuDotFront3),
-            "KosuzuDotBack1" => Ok(Self::KosuzuDotBack1),
-            "KosuzuDotBack2" => Ok(Self::KosuzuDotBack2),
-            "KosuzuDotBack3" => Ok(Self::KosuzuDotBack3),
-            "KosuzuDotRight1" => Ok(Self::KosuzuDotRight1),
-            "KosuzuDotRight2" => Ok(Self::KosuzuDotRight2),
-            "KosuzuDotRight3" => Ok(Self::KosuzuDotRight3),
-            "KosuzuDotLeft1" => Ok(Self::KosuzuDotLeft1),
-            "KosuzuDotLeft2" => Ok(Self::KosuzuDotLeft2),
-            "KosuzuDotLeft3" => Ok(Self::KosuzuDotLeft3),
-            "StoreButton" => Ok(Self::StoreButton),
-            "ResetButton" => Ok(Self::ResetButton),
-            "MenuArt1" => Ok(Self::MenuArt1),
-            "MenuArt2" => Ok(Self::MenuArt2),
-            "JpHouseTexture" => Ok(Self::JpHouseTexture),
-            "BookBoxFront" => Ok(Self::BookBoxFront),
-            "BookBoxBack" => Ok(Self::BookBoxBack),
-            "Paper3" => Ok(Self::Paper3),
-            "Paper4" => Ok(Self::Paper4),
-            "Paper5" => Ok(Self::Paper5),
-            "Paper6" => Ok(Self::Paper6),
-            "Paper7" => Ok(Self::Paper7),
-            "Clock1" => Ok(Self::Clock1),
-            "ClockNeedle1" => Ok(Self::ClockNeedle1),
-            "ShortClockNeedle1" => Ok(Self::ShortClockNeedle1),
-            "Hanko" => Ok(Self::Hanko),
-            "NextLineIcon" => Ok(Self::NextLineIcon),
-            "LargeBookScratchFair1" => Ok(Self::LargeBookScratchFair1),
-            "LargeBookScratchFair2" => Ok(Self::LargeBookScratchFair2),
-            "LargeBookScratchFair3" => Ok(Self::LargeBookScratchFair3),
-            "LargeBookScratchFair4" => Ok(Self::LargeBookScratchFair4),
-            "LargeBookScratchBad1" => Ok(Self::LargeBookScratchBad1),
-            "LargeBookScratchBad2" => Ok(Self::LargeBookScratchBad2),
-            "LargeBookScratchBad3" => Ok(Self::LargeBookScratchBad3),
-            "LargeBookScratchBad4" => Ok(Self::LargeBookScratchBad4),
-            "ManualPageBookTitles" => Ok(Self::ManualPageBookTitles),
-            "ManualPageBorrowingFlow" => Ok(Self::ManualPageBorrowingFlow),
-            "ManualPageReturnFlow" => Ok(Self::ManualPageReturnFlow),
-            "GoNextPageLeft" => Ok(Self::GoNextPageLeft),
-            "GoNextPageRight" => Ok(Self::GoNextPageRight),
-            "Library" => Ok(Self::Library),
-            "KosuzuTachie1" => Ok(Self::KosuzuTachie1),
-            "CheckCircle" => Ok(Self::CheckCircle),
-            "MoneyBox" => Ok(Self::MoneyBox),
-            "KosuzuSmile1" => Ok(Self::KosuzuSmile1),
-            "Coin100Yen" => Ok(Self::Coin100Yen),
-            "Coin50Yen" => Ok(Self::Coin50Yen),
-            "Coin500Yen" => Ok(Self::Coin500Yen),
-            "BaraBG" => Ok(Self::BaraBG),
-            "SuzunaanMap" => Ok(Self::SuzunaanMap),
-            "Mob1TachieDefault" => Ok(Self::Mob1TachieDefault),
-            "SuzuLogo" => Ok(Self::SuzuLogo),
-            "AkyuTachieDefault" => Ok(Self::AkyuTachieDefault),
-            "Mob1DotFront1" => Ok(Self::Mob1DotFront1),
-            "Mob1DotFront2" => Ok(Self::Mob1DotFront2),
-            "Mob1DotFront3" => Ok(Self::Mob1DotFront3),
-            "Mob1DotBack1" => Ok(Self::Mob1DotBack1),
-            "Mob1DotBack2" => Ok(Self::Mob1DotBack2),
-            "Mob1DotBack3" => Ok(Self::Mob1DotBack3),
-            "Mob1DotRight1" => Ok(Self::Mob1DotRight1),
-            "Mob1DotRight2" => Ok(Self::Mob1DotRight2),
-            "Mob1DotRight3" => Ok(Self::Mob1DotRight3),
-            "Mob1DotLeft1" => Ok(Self::Mob1DotLeft1),
-            "Mob1DotLeft2" => Ok(Self::Mob1DotLeft2),
-            "Mob1DotLeft3" => Ok(Self::Mob1DotLeft3),
-            "GoNextPageLeftNotAlpha" => Ok(Self::GoNextPageLeftNotAlpha),
-            "GoNextPageRightNotAlpha" => Ok(Self::GoNextPageRightNotAlpha),
-            "Knob" => Ok(Self::Knob),
-	    "NitoriTachieDefault" => Ok(Self::NitoriTachieDefault),
-	    "NitoriTachieSunGlass" => Ok(Self::NitoriTachieSunGlass),
-	    "TownBackground" => Ok(Self::TownBackground),
-            _ => Err(()),
-        }
-    }
-}
-
-impl TextureID {
-    pub fn from_u32(n: u32) -> Option<Self> {
-        match n {
-            0 => Some(Self::Ghost1),
-            1 => Some(Self::LotusPink),
-            2 => Some(Self::LotusBlue),
-            3 => Some(Self::LotusYellow),
-            4 => Some(Self::TextBackground),
-            5 => Some(Self::Paper1),
-            6 => Some(Self::Paper2),
-            7 => Some(Self::LargeBook1),
-            8 => Some(Self::LargeBook2),
-            9 => Some(Self::LargeBook3),
-            10 => Some(Self::MiddleBook1),
-            11 => Some(Self::MiddleBook2),
-            12 => Some(Self::MiddleBook3),
-            13 => Some(Self::Wood1),
-            14 => Some(Self::WafuTexture1),
-            15 => Some(Self::WafuTexture2),
-            16 => Some(Self::Chobo1),
-            17 => Some(Self::ChoicePanel1),
-            18 => Some(Self::ChoicePanel2),
-            19 => Some(Self::ChoicePanel3),
-            20 => Some(Self::ChoicePanel4),
-            21 => Some(Self::ChoicePanel5),
-            22 => Some(Self::JunkoTachieDefault),
-            23 => Some(Self::SightBackground1),
-            24 => Some(Self::ArrowRight),
-            25 => Some(Self::ArrowLeft),
-            26 => Some(Self::KosuzuDotFront1),
-            27 => Some(Self::KosuzuDotFront2),
-            28 => Some(Self::KosuzuDotFront3),
-            29 => Some(Self::KosuzuDotBack1),
-            30 => Some(Self::KosuzuDotBack2),
-            31 => Some(Self::KosuzuDotBack3),
-            32 => Some(Self::KosuzuDotRight1),
-            33 => Some(Self::KosuzuDotRight2),
-            34 => Some(Self::KosuzuDotRight3),
-            35 => Some(Self::KosuzuDotLeft1),
-            36 => Some(Self::KosuzuDotLeft2),
-            37 => Some(Self::KosuzuDotLeft3),
-            38 => Some(Self::StoreButton),
-            39 => Some(Self::ResetButton),
-            40 => Some(Self::MenuArt1),
-            41 => Some(Self::MenuArt2),
-            42 => Some(Self::JpHouseTexture),
-            43 => Some(Self::BookBoxFront),
-            44 => Some(Self::BookBoxBack),
-            45 => Some(Self::Paper3),
-            46 => Some(Self::Paper4),
-            47 => Some(Self::Paper5),
-            48 => Some(Self::Paper6),
-            49 => Some(Self::Paper7),
-            50 => Some(Self::Clock1),
-            51 => Some(Self::ClockNeedle1),
-            52 => Some(Self::ShortClockNeedle1),
-            53 => Some(Self::Hanko),
-            54 => Some(Self::NextLineIcon),
-            55 => Some(Self::LargeBookScratchFair1),
-            56 => Some(Self::LargeBookScratchFair2),
-            57 => Some(Self::LargeBookScratchFair3),
-            58 => Some(Self::LargeBookScratchFair4),
-            59 => Some(Self::LargeBookScratchBad1),
-            60 => Some(Self::LargeBookScratchBad2),
-            61 => Some(Self::LargeBookScratchBad3),
-            62 => Some(Self::LargeBookScratchBad4),
-            63 => Some(Self::ManualPageBookTitles),
-            64 => Some(Self::ManualPageBorrowingFlow),
-            65 => Some(Self::ManualPageReturnFlow),
-            66 => Some(Self::GoNextPageLeft),
-            67 => Some(Self::GoNextPageRight),
-            68 => Some(Self::Library),
-            69 => Some(Self::KosuzuTachie1),
-            70 => Some(Self::CheckCircle),
-            71 => Some(Self::MoneyBox),
-            72 => Some(Self::KosuzuSmile1),
-            73 => Some(Self::Coin100Yen),
-            74 => Some(Self::Coin50Yen),
-            75 => Some(Self::Coin500Yen),
-            76 => Some(Self::BaraBG),
-            77 => Some(Self::SuzunaanMap),
-            78 => Some(Self::Mob1TachieDefault),
-            79 => Some(Self::SuzuLogo),
-            80 => Some(Self::AkyuTachieDefault),
-            81 => Some(Self::Mob1DotFront1),
-            82 => Some(Self::Mob1DotFront2),
-            83 => Some(Self::Mob1DotFront3),
-            84 => Some(Self::Mob1DotBack1),
-            85 => Some(Self::Mob1DotBack2),
-            86 => Some(Self::Mob1DotBack3),
-            87 => Some(Self::Mob1DotRight1),
-            88 => Some(Self::Mob1DotRight2),
-            89 => Some(Self::Mob1DotRight3),
-            90 => Some(Self::Mob1DotLeft1),
-            91 => Some(Self::Mob1DotLeft2),
-            92 => Some(Self::Mob1DotLeft3),
-            93 => Some(Self::GoNextPageLeftNotAlpha),
-            94 => Some(Self::GoNextPageRightNotAlpha),
-            95 => Some(Self::Knob),
-	    96 => Some(Self::NitoriTachieDefault),
-	    97 => Some(Self::NitoriTachieSunGlass),
-	    98 => Some(Self::TownBackground),
-            _ => None,
-        }
-    }
-}
-
 impl TextureID {
-    pub fn select_random() -> Self {
-        TextureID::from_u32(rand::random::<u32>() % (Self::Unknown as u32)).unwrap()
-    }
-
     pub fn random_large_book_scratch_fair() -> TextureID {
         let candidate = [
             TextureID::LargeBookScratchFair1,
@@ -505,6 +243,22 @@ pub enum TileBatchTextureID {
     BlackFrame2,
 }
 
+impl FromStr for TileBatchTextureID {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "OldStyleFrame" => Ok(Self::OldStyleFrame),
+            "RedOldStyleFrame" => Ok(Self::RedOldStyleFrame),
+            "TaishoStyle1" => Ok(Self::TaishoStyle1),
+            "Suzu1" => Ok(Self::Suzu1),
+            "Shoji" => Ok(Self::Shoji),
+            "BlackFrame" => Ok(Self::BlackFrame),
+            "BlackFrame2" => Ok(Self::BlackFrame2),
+            _ => Err(()),
+        }
+    }
+}
+
 pub const LARGE_BOOK_TEXTURE: [TextureID; 3] = [
     TextureID::LargeBook1,
     TextureID::LargeBook2,
@@ -517,7 +271,7 @@ pub const MIDDLE_BOOK_TEXTURE: [TextureID; 3] = [
     TextureID::MiddleBook3,
 ];
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SoundID {
     Title = 0,
     SeTurnThePage,
@@ -531,6 +285,36 @@ pub enum SoundID {
     Unknown,
 }
 
+impl SoundID {
+    ///
+    /// BGMとして再生できるSoundIDかどうか. GameResourceのbgm_tableと対応する
+    ///
+    pub fn is_bgm(&self) -> bool {
+        match self {
+            Self::Title | Self::ScenarioBGM | Self::ShopBGM | Self::EndBGM => true,
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for SoundID {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s {
+            "Title" => Ok(Self::Title),
+            "SeTurnThePage" => Ok(Self::SeTurnThePage),
+            "SeCustomerBell" => Ok(Self::SeCustomerBell),
+            "SeMessage" => Ok(Self::SeMessage),
+            "ScenarioBGM" => Ok(Self::ScenarioBGM),
+            "ShopBGM" => Ok(Self::ShopBGM),
+            "EndBGM" => Ok(Self::EndBGM),
+            "ResultSE" => Ok(Self::ResultSE),
+            "FinalResultSE" => Ok(Self::FinalResultSE),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BookCondition {
     Good,
@@ -848,52 +632,93 @@ pub enum GeneralScenarioID {
 
 impl GeneralScenarioID {
     pub fn from_str(s: &str) -> Self {
+        Self::try_from_str(s).expect("Invalid General Scenario String")
+    }
+
+    pub fn try_from_str(s: &str) -> Option<Self> {
         match s {
-            "NoEnoughMoney" => Self::NoEnoughMoney,
-            _ => panic!("Invalid General Scenario String"),
+            "NoEnoughMoney" => Some(Self::NoEnoughMoney),
+            _ => None,
         }
     }
 }
 
-pub struct ScenarioTable {
-    scenario_table: HashMap<GensoDate, String>,
-    general_scenario: HashMap<GeneralScenarioID, String>,
+impl FromStr for GeneralScenarioID {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        Self::try_from_str(s).ok_or(())
+    }
 }
 
-impl ScenarioTable {
-    pub fn new(ctx: &mut ggez::Context, table_toml_path: &str) -> Self {
-        let mut table = HashMap::new();
-
-        let root = parse_toml_file!(ctx, table_toml_path);
-        let array = root["scenario-table"].as_array().unwrap();
-
-        for elem in array {
-            let date_data = elem.get("date").unwrap().as_table().unwrap();
-            let genso_date = GensoDate::new(
-                date_data.get("season").unwrap().as_integer().unwrap() as u32,
-                date_data.get("month").unwrap().as_integer().unwrap() as u8,
-                date_data.get("day").unwrap().as_integer().unwrap() as u8,
-            );
+impl<'de> Deserialize<'de> for GeneralScenarioID {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        schema::parse_str(deserializer)
+    }
+}
 
-            let path = elem.get("path").unwrap().as_str().unwrap();
+///
+/// scenario-tableの要素. dateの日の始めにpathのシナリオを再生する
+///
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScenarioTableEntry {
+    pub date: GensoDate,
+    pub path: String,
+}
 
-            table.insert(genso_date, path.to_string());
-        }
+///
+/// general-scenario-tableの要素
+///
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeneralScenarioEntry {
+    #[serde(rename = "type")]
+    pub id: GeneralScenarioID,
+    pub path: String,
+}
+
+///
+/// scenario_table.tomlの内容. MODのマニフェストも同じ書き方をする
+///
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScenarioTableFile {
+    #[serde(rename = "scenario-table")]
+    pub scenario_table: Vec<ScenarioTableEntry>,
+    #[serde(rename = "general-scenario-table")]
+    pub general_scenario_table: Vec<GeneralScenarioEntry>,
+}
 
-        let mut general_scenario = HashMap::new();
-        let array = root["general-scenario-table"].as_array().unwrap();
+pub struct ScenarioTable {
+    scenario_table: HashMap<GensoDate, String>,
+    general_scenario: HashMap<GeneralScenarioID, String>,
+}
 
-        for elem in array {
-            let ty = elem.get("type").unwrap().as_str().unwrap();
-            let id = GeneralScenarioID::from_str(ty);
-            let path = elem.get("path").unwrap().as_str().unwrap();
+impl ScenarioTable {
+    pub fn new(ctx: &mut ggez::Context, table_toml_path: &str) -> Result<Self, SuzuError> {
+        let root = try_parse_toml_file!(ctx, table_toml_path)?;
+        Self::from_toml_node(&error::TomlNode::root(table_toml_path, &root))
+    }
 
-            general_scenario.insert(id, path.to_string());
-        }
+    pub fn from_toml_node(root: &error::TomlNode) -> Result<Self, SuzuError> {
+        let file: ScenarioTableFile = schema::deserialize_node(root)?;
+        Ok(Self::from_entries(
+            &file.scenario_table,
+            &file.general_scenario_table,
+        ))
+    }
 
+    fn from_entries(
+        scenario_table: &[ScenarioTableEntry],
+        general_scenario_table: &[GeneralScenarioEntry],
+    ) -> Self {
         ScenarioTable {
-            scenario_table: table,
-            general_scenario: general_scenario,
+            scenario_table: scenario_table
+                .iter()
+                .map(|entry| (entry.date, entry.path.clone()))
+                .collect(),
+            general_scenario: general_scenario_table
+                .iter()
+                .map(|entry| (entry.id.clone(), entry.path.clone()))
+                .collect(),
         }
     }
 
@@ -918,7 +743,9 @@ impl ScenarioTable {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct BookShelfInformation {
+    #[serde(rename = "begin-number")]
     billing_number_begin: u16,
+    #[serde(rename = "end-number")]
     billing_number_end: u16,
 }
 
@@ -1030,6 +857,7 @@ impl AdAgencyCostTable {
     }
 }
 
+#[derive(Deserialize)]
 pub struct DailyCustomerDist {
     sun: Clock,
     mon: Clock,
@@ -1041,17 +869,13 @@ pub struct DailyCustomerDist {
 }
 
 impl DailyCustomerDist {
-    pub fn from_toml_file(ctx: &mut ggez::Context, path: &str) -> Self {
-        let root = parse_toml_file!(ctx, path);
-        DailyCustomerDist {
-            sun: root["sun"].as_integer().unwrap() as Clock,
-            mon: root["mon"].as_integer().unwrap() as Clock,
-            tue: root["tue"].as_integer().unwrap() as Clock,
-            wed: root["wed"].as_integer().unwrap() as Clock,
-            thu: root["thu"].as_integer().unwrap() as Clock,
-            fri: root["fri"].as_integer().unwrap() as Clock,
-            sat: root["sat"].as_integer().unwrap() as Clock,
-        }
+    pub fn from_toml_file(ctx: &mut ggez::Context, path: &str) -> Result<Self, SuzuError> {
+        let root = try_parse_toml_file!(ctx, path)?;
+        Self::from_toml_node(&error::TomlNode::root(path, &root))
+    }
+
+    pub fn from_toml_node(root: &error::TomlNode) -> Result<Self, SuzuError> {
+        schema::deserialize_node(root)
     }
 
     pub fn get_customer_dist_default(&self, day: &GensoDate) -> Clock {
@@ -1074,7 +898,11 @@ impl DailyCustomerDist {
 
 #[derive(Deserialize)]
 pub struct RawConfigFile {
-    texture_paths: Vec<String>,
+    textures: BTreeMap<String, String>,
+    #[serde(default)]
+    texture_groups: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    tachie_speakers: BTreeMap<String, String>,
     font_paths: Vec<String>,
     customers_name: Vec<String>,
     books_information: Vec<BookInformation>,
@@ -1090,41 +918,184 @@ pub struct RawConfigFile {
 }
 
 impl RawConfigFile {
-    pub fn new(ctx: &mut ggez::Context, file_path: String) -> RawConfigFile {
-        let s = util::read_from_resources_as_string(ctx, file_path.as_str());
+    pub fn new(ctx: &mut ggez::Context, file_path: String) -> Result<RawConfigFile, SuzuError> {
+        let s = util::try_read_from_resources_as_string(ctx, file_path.as_str())?;
+        Self::from_toml_str(&file_path, &s)
+    }
+
+    pub fn from_toml_str(path: &str, s: &str) -> Result<RawConfigFile, SuzuError> {
+        toml::from_str(s).map_err(|e| SuzuError::TomlParse {
+            path: path.to_string(),
+            source: e,
+        })
+    }
+
+    pub fn get_daily_customer_dist_path(&self) -> &str {
+        self.daily_customer_dist_path.as_str()
+    }
+}
+
+///
+/// シーンを始める前に, 起動時と店番中に読み込む設定ファイルを検証する
+///
+/// 最初の問題で止まらずに, 見つかった問題を全て返す
+/// game_data.tomlから参照されるファイルは, game_data.tomlに書かれたパスで検証する
+///
+/// 店番の設定ファイルはシーンを始めるたびに読み込み直す. そこで失敗するのは起動後に
+/// ファイルが書き換えられた場合だけなので, 読み込む側は検証を繰り返さずにエラーを呼び出し元へ返す
+///
+pub fn validate_startup_files(
+    ctx: &mut ggez::Context,
+    game_data_path: &str,
+    mods: &ModRegistry,
+) -> Vec<SuzuError> {
+    let game_data = match try_parse_toml_file!(ctx, game_data_path) {
+        Ok(root) => root,
+        Err(e) => return vec![e],
+    };
+
+    // 他のファイルで使うテクスチャの名前を, 検証より先に登録しておく
+    texture_registry::register_textures_from_game_data(&game_data);
+
+    let mut problems = Vec::new();
+    schema::validate::<RawConfigFile>(
+        &error::TomlNode::root(game_data_path, &game_data),
+        &mut problems,
+    );
 
-        let raw_data: Result<RawConfigFile, toml::de::Error> = toml::from_str(&s);
-        match raw_data {
-            Ok(p) => p,
-            Err(e) => panic!("Failed to parse toml: {}", e),
-        }
+    if let Some(path) = game_data
+        .get("daily_customer_dist_path")
+        .and_then(|path| path.as_str())
+    {
+        schema::validate_file(
+            ctx,
+            path,
+            schema::validate::<DailyCustomerDist>,
+            &mut problems,
+        );
+    }
+
+    if let Some(path) = game_data
+        .get("scenario_table_path")
+        .and_then(|path| path.as_str())
+    {
+        schema::validate_file(
+            ctx,
+            path,
+            schema::validate::<ScenarioTableFile>,
+            &mut problems,
+        );
+    }
+
+    let event_map_paths = game_data
+        .get("map_information")
+        .and_then(|maps| maps.as_array())
+        .into_iter()
+        .flat_map(|maps| maps.iter())
+        .filter_map(|map| {
+            map.get("event_map_file_path")
+                .and_then(|path| path.as_str())
+        });
+    for path in event_map_paths {
+        schema::validate_file(ctx, path, MapEventList::validate, &mut problems);
+    }
+
+    schema::validate_file(
+        ctx,
+        SHOP_CLOCK_CONFIG_PATH,
+        schema::validate::<ShopClockConfig>,
+        &mut problems,
+    );
+    schema::validate_file(
+        ctx,
+        NEW_BOOK_SCHEDULE_PATH,
+        NewBookScheduleFile::validate,
+        &mut problems,
+    );
+
+    // MODのscenario-tableとnew-book-scheduleは, マニフェストを読み込むときに型を確かめている
+    // MODが追加するイベントは, 追加先のイベントマップと同じ読み込み処理で検証する
+    for loaded in mods.mods() {
+        let mut root = toml::value::Table::new();
+        root.insert(
+            "event-panel".to_string(),
+            toml::Value::Array(loaded.manifest().event_panel.clone()),
+        );
+        MapEventList::validate(
+            &error::TomlNode::root(loaded.manifest_path(), &toml::Value::Table(root)),
+            &mut problems,
+        );
     }
+
+    problems
 }
 
+///
+/// 起動時に先読みし, シーンを切り替えても解放しないテクスチャのグループ
+///
+const COMMON_TEXTURE_GROUP: &str = "common";
+
+const BASE_RESOURCE_PACK: &str = "./resources.suzupack";
+
+///
+/// 上書き用のパックを置くディレクトリ. ファイル名の順にマウントされ, 後のものが優先される
+///
+const OVERRIDE_PACK_DIRECTORY: &str = "./packs";
+
 pub struct GameResource {
     texture_resource_paths: HashMap<TextureID, String>,
     textures: HashMap<TextureID, ggraphics::Image>,
     fonts: Vec<ggraphics::Font>,
     tile_batchs: Vec<TileBatch>,
-    customers_name: Vec<String>,
-    books_information: Vec<BookInformation>,
+    economy_data: EconomyData,
     map_data: Vec<MapConstractData>,
     scenario_table: ScenarioTable,
     sounds: Vec<sound::SoundData>,
     bgm_manager: sound::SoundManager,
     se_manager: sound::SoundManager,
     bgm_table: HashMap<SoundID, Option<sound::SoundHandler>>,
-    ad_info: AdCostTable,
-    ad_agency_info: AdAgencyCostTable,
-    daily_customer_dist: DailyCustomerDist,
+    voice_manager: sound::SoundManager,
+    voice_cache: HashMap<String, sound::SoundData>,
+    voice_handler: Option<sound::SoundHandler>,
+    translation: Option<TranslationCatalog>,
+    texture_groups: HashMap<String, Vec<TextureID>>,
+    tachie_speakers: HashMap<TextureID, String>,
+    active_texture_group: Option<String>,
+    texture_preloaders: Vec<TexturePreloader>,
+    mods: ModRegistry,
 }
 
 impl GameResource {
-    pub fn new(ctx: &mut ggez::Context, file_path: String) -> GameResource {
+    pub fn new(ctx: &mut ggez::Context, file_path: String) -> Result<GameResource, SuzuError> {
+        // 基本のパックと, packsディレクトリの上書き用のパックをresourcesディレクトリと一緒に使う
+        // MODのパックは, 基本のパックと上書き用のパックの間に読み込み順でマウントする
+        let base_pack = std::path::Path::new(BASE_RESOURCE_PACK);
+        if base_pack.exists() {
+            resource_pack::mount(resource_pack::ResourcePack::open(base_pack)?);
+        }
+        let (mut mods, mod_errors) =
+            ModRegistry::load_directory(std::path::Path::new(mod_loader::MOD_DIRECTORY));
+        for e in mod_errors.iter().chain(mods.mount_packs().iter()) {
+            eprintln!("{}", e);
+        }
+        for e in resource_pack::mount_directory(std::path::Path::new(OVERRIDE_PACK_DIRECTORY)) {
+            eprintln!("{}", e);
+        }
+
+        // 設定ファイルの問題は, 読み込み途中で止まる前にまとめて報告する
+        let problems = validate_startup_files(ctx, &file_path, &mods);
+        if !problems.is_empty() {
+            return Err(SuzuError::Validation { problems: problems });
+        }
+
         let init_display = InitialDisplay::new(ctx);
-        init_display.draw(ctx);
+        init_display.draw(ctx, 0.0);
 
-        let src_file = RawConfigFile::new(ctx, file_path);
+        let mut src_file = RawConfigFile::new(ctx, file_path.clone())?;
+        mods.merge_books_and_customers(
+            &mut src_file.books_information,
+            &mut src_file.customers_name,
+        );
 
         let textures = HashMap::new();
         let mut fonts = Vec::new();
@@ -1133,45 +1104,128 @@ impl GameResource {
         let mut texture_paths_map = HashMap::new();
 
         print!("Setup textures delay loading ... ");
-        for (index, texture_path) in src_file.texture_paths.iter().enumerate() {
-            texture_paths_map.insert(
-                TextureID::from_u32(index as u32).unwrap(),
-                texture_path.clone(),
-            );
+        for (name, texture_path) in src_file.textures.iter() {
+            texture_paths_map.insert(TextureID::declare(name), texture_path.clone());
+        }
+
+        // プログラムから参照しているテクスチャは, 必ずパスが宣言されていなければならない
+        for texture_id in TextureID::builtin_ids() {
+            if !texture_paths_map.contains_key(&texture_id) {
+                return Err(SuzuError::MissingKey {
+                    path: file_path.clone(),
+                    parent: "textures".to_string(),
+                    key: texture_id.name(),
+                });
+            }
         }
         println!("done");
 
+        let mut texture_groups = HashMap::new();
+        for (group, names) in src_file.texture_groups.iter() {
+            let mut ids = Vec::new();
+            for (index, name) in names.iter().enumerate() {
+                match TextureID::from_str(name) {
+                    Ok(id) if texture_paths_map.contains_key(&id) => ids.push(id),
+                    _ => {
+                        return Err(SuzuError::InvalidValue {
+                            path: file_path.clone(),
+                            key: format!("texture_groups.{}[{}]", group, index),
+                            value: name.clone(),
+                        })
+                    }
+                }
+            }
+            texture_groups.insert(group.clone(), ids);
+        }
+
+        let mut tachie_speakers = HashMap::new();
+        for (name, speaker) in src_file.tachie_speakers.iter() {
+            match TextureID::from_str(name) {
+                Ok(id) if texture_paths_map.contains_key(&id) => {
+                    tachie_speakers.insert(id, speaker.clone());
+                }
+                _ => {
+                    return Err(SuzuError::InvalidValue {
+                        path: file_path.clone(),
+                        key: "tachie_speakers".to_string(),
+                        value: name.clone(),
+                    })
+                }
+            }
+        }
+
+        // commonグループは, フォントや音声を読み込んでいる間にワーカースレッドでデコードしておく
+        let mut common_preloader = TexturePreloader::spawn(
+            ctx,
+            COMMON_TEXTURE_GROUP,
+            texture_groups
+                .get(COMMON_TEXTURE_GROUP)
+                .map_or(Vec::new(), |ids| {
+                    ids.iter()
+                        .map(|id| (*id, texture_paths_map[id].clone()))
+                        .collect()
+                }),
+        );
+
+        let total_steps = src_file.font_paths.len()
+            + src_file.sprite_batch_information.len()
+            + src_file.sound_file_path.len()
+            + common_preloader.total();
+        let mut done_steps = 0;
+        let progress = |done: usize| done as f32 / total_steps.max(1) as f32;
+
         for font_path in &src_file.font_paths {
             print!("Loading font {}...", font_path);
-            fonts.push(ggraphics::Font::new(ctx, font_path).unwrap());
+            fonts.push(
+                ggraphics::Font::new(ctx, font_path)
+                    .map_err(|e| SuzuError::missing_asset(font_path, e))?,
+            );
             println!(" done!");
+            done_steps += 1;
+            init_display.draw(ctx, progress(done_steps));
         }
 
         for sb_data in &src_file.sprite_batch_information {
             print!("Loading font {}...", sb_data.path);
             sprite_batchs.push(TileBatch::new(
-                ggraphics::Image::new(ctx, &sb_data.path).unwrap(),
+                ggraphics::Image::new(ctx, &sb_data.path)
+                    .map_err(|e| SuzuError::missing_asset(&sb_data.path, e))?,
                 numeric::Vector2u::new(sb_data.sprite_x_size as u32, sb_data.sprite_y_size as u32),
                 numeric::Point2f::new(0.0, 0.0),
                 0,
             ));
             println!(" done!");
+            done_steps += 1;
+            init_display.draw(ctx, progress(done_steps));
         }
 
         for sound_path in &src_file.sound_file_path {
-            let sound_data = sound::SoundData::new(ctx, sound_path).unwrap();
+            let sound_data = sound::SoundData::new(ctx, sound_path)
+                .map_err(|e| SuzuError::missing_asset(sound_path, e))?;
             println!(
                 "sound path -> {}, canplay? => {:?}",
                 sound_path,
                 sound_data.can_play()
             );
             sounds.push(sound_data);
+            done_steps += 1;
+            init_display.draw(ctx, progress(done_steps));
         }
 
-        let scenario_table = ScenarioTable::new(ctx, &src_file.scenario_table_path);
+        let mut scenario_table = ScenarioTable::new(ctx, &src_file.scenario_table_path)?;
+        mods.merge_scenario_table(&mut scenario_table);
+
+        for map_data in src_file.map_information.iter() {
+            let event_map_path = map_data.event_map_file_path.as_str();
+            if mods.has_event_panels_for(event_map_path) {
+                let root = try_parse_toml_file!(ctx, event_map_path)?;
+                mods.check_event_panel_conflicts(event_map_path, &root);
+            }
+        }
 
         let daily_customer_dist =
-            DailyCustomerDist::from_toml_file(ctx, &src_file.daily_customer_dist_path);
+            DailyCustomerDist::from_toml_file(ctx, &src_file.daily_customer_dist_path)?;
+        let economy_data = EconomyData::from_raw_config(&src_file, daily_customer_dist);
 
         let mut bgm_table: HashMap<SoundID, Option<sound::SoundHandler>> = HashMap::new();
         bgm_table.insert(SoundID::Title, None);
@@ -1179,36 +1233,166 @@ impl GameResource {
         bgm_table.insert(SoundID::ShopBGM, None);
         bgm_table.insert(SoundID::EndBGM, None);
 
-        GameResource {
+        let mut resource = GameResource {
             texture_resource_paths: texture_paths_map,
             textures: textures,
             fonts: fonts,
 
             tile_batchs: sprite_batchs,
-            customers_name: src_file.customers_name,
-            books_information: src_file.books_information,
+            economy_data: economy_data,
             map_data: src_file.map_information,
             scenario_table: scenario_table,
             sounds: sounds,
             bgm_manager: sound::SoundManager::new(),
             se_manager: sound::SoundManager::new(),
             bgm_table: bgm_table,
-            ad_info: AdCostTable::from_data(src_file.ad_cost_table, src_file.ad_gain_table),
-            ad_agency_info: AdAgencyCostTable::from_data(
-                src_file.ad_agency_cost_table,
-                src_file.ad_agency_gain_table,
-            ),
-            daily_customer_dist: daily_customer_dist,
+            voice_manager: sound::SoundManager::new(),
+            voice_cache: HashMap::new(),
+            voice_handler: None,
+            translation: None,
+            texture_groups: texture_groups,
+            tachie_speakers: tachie_speakers,
+            active_texture_group: None,
+            texture_preloaders: Vec::new(),
+            mods: mods,
+        };
+
+        // デコードが終わるたびに転送し, 進み具合を描画する
+        while let Some(decoded) = common_preloader.recv() {
+            Self::upload_decoded_texture(ctx, &mut resource.textures, decoded);
+            init_display.draw(ctx, progress(done_steps + common_preloader.received()));
+        }
+
+        Ok(resource)
+    }
+
+    fn upload_decoded_texture(
+        ctx: &mut ggez::Context,
+        textures: &mut HashMap<TextureID, ggraphics::Image>,
+        decoded: DecodedTexture,
+    ) {
+        // 先読みが終わる前に必要になり, 既に読み込まれている場合
+        if textures.contains_key(&decoded.id) {
+            return;
+        }
+
+        match decoded.upload(ctx) {
+            Ok(texture) => {
+                textures.insert(decoded.id, texture);
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    ///
+    /// 先読みが終わったテクスチャをGPUに転送する. 毎フレーム呼び出す
+    ///
+    pub fn update_texture_preload(&mut self, ctx: &mut ggez::Context) {
+        for preloader in self.texture_preloaders.iter_mut() {
+            for decoded in preloader.poll() {
+                Self::upload_decoded_texture(ctx, &mut self.textures, decoded);
+            }
+        }
+
+        self.texture_preloaders
+            .retain(|preloader| !preloader.is_finished());
+    }
+
+    pub fn is_preloading_textures(&self) -> bool {
+        !self.texture_preloaders.is_empty()
+    }
+
+    ///
+    /// グループのうち, まだ読み込まれていないテクスチャをワーカースレッドで先読みする
+    ///
+    pub fn preload_texture_group(&mut self, ctx: &mut ggez::Context, group: &str) {
+        let sources = match self.texture_groups.get(group) {
+            Some(ids) => ids
+                .iter()
+                .filter(|id| !self.textures.contains_key(*id))
+                .map(|id| (*id, self.texture_resource_paths[id].clone()))
+                .collect::<Vec<_>>(),
+            None => return,
+        };
+
+        if !sources.is_empty() {
+            self.texture_preloaders
+                .push(TexturePreloader::spawn(ctx, group, sources));
+        }
+    }
+
+    ///
+    /// シーンを切り替えるときに呼び出す
+    /// 前のシーンのグループのうち, 次のグループとcommonで使わないテクスチャを解放し, 次のグループを先読みする
+    ///
+    pub fn switch_texture_group(&mut self, ctx: &mut ggez::Context, group: &str) {
+        if self.active_texture_group.as_deref() == Some(group) {
+            return;
+        }
+
+        if let Some(prev_group) = self.active_texture_group.take() {
+            let keep = [group, COMMON_TEXTURE_GROUP]
+                .iter()
+                .filter_map(|name| self.texture_groups.get(*name))
+                .flatten()
+                .copied()
+                .collect::<std::collections::HashSet<TextureID>>();
+
+            if let Some(ids) = self.texture_groups.get(&prev_group) {
+                for id in ids.iter().filter(|id| !keep.contains(*id)) {
+                    self.textures.remove(id);
+                }
+            }
+        }
+
+        // 解放したグループの先読みが残っていると, 解放したテクスチャが再び読み込まれてしまう
+        self.texture_preloaders.retain(|preloader| {
+            preloader.group() == group || preloader.group() == COMMON_TEXTURE_GROUP
+        });
+
+        self.active_texture_group = Some(group.to_string());
+        self.preload_texture_group(ctx, group);
+    }
+
+    ///
+    /// localeの翻訳カタログを読み込む. Noneの場合は原文で表示する
+    ///
+    pub fn load_translation(
+        &mut self,
+        ctx: &mut ggez::Context,
+        locale: Option<&str>,
+    ) -> Result<(), SuzuError> {
+        self.translation = match locale {
+            Some(locale) => Some(TranslationCatalog::load(ctx, locale)?),
+            None => None,
+        };
+
+        Ok(())
+    }
+
+    ///
+    /// 読み込んだTOMLの文字列を, 選ばれているlocaleの訳文で置き換える
+    ///
+    pub fn translate(&self, path: &str, root: &mut toml::Value) {
+        if let Some(translation) = self.translation.as_ref() {
+            translation.inject(path, root);
         }
     }
 
+    pub fn get_mods(&self) -> &ModRegistry {
+        &self.mods
+    }
+
     fn load_texture_delay(&mut self, ctx: &mut ggez::Context, id: TextureID) -> ggraphics::Image {
         let path = self
             .texture_resource_paths
             .get(&id)
             .expect("Delay texture load: Invalid TextureID");
         print!("delay texture loading -> {} ... ", path);
-        let texture = ggraphics::Image::new(ctx, path).expect("Delay texture load: Invalid Path");
+        let texture = match texture_loader::load_image(ctx, path) {
+            Ok(texture) => texture,
+            Err(e) => panic!("Delay texture load: {}", e),
+        };
         self.textures.insert(id, texture.clone());
         println!("done!");
 
@@ -1225,6 +1409,15 @@ impl GameResource {
         }
     }
 
+    ///
+    /// 立ち絵の人物の名前. 話者の名前にこの名前が含まれていれば, その人物が話しているとみなす
+    ///
+    pub fn get_tachie_speaker(&self, id: TextureID) -> Option<&str> {
+        self.tachie_speakers
+            .get(&id)
+            .map(|speaker| speaker.as_str())
+    }
+
     pub fn get_font(&self, id: FontID) -> ggraphics::Font {
         match self.fonts.get(id as usize) {
             Some(font) => *font,
@@ -1241,32 +1434,24 @@ impl GameResource {
         None
     }
 
+    pub fn economy_data(&self) -> &EconomyData {
+        &self.economy_data
+    }
+
     pub fn book_random_select(&self) -> &BookInformation {
-        &self
-            .books_information
-            .get(rand::random::<usize>() % self.books_information.len())
-            .unwrap()
+        self.economy_data.book_random_select()
     }
 
     pub fn search_book_with_title(&self, title: &str) -> Option<&BookInformation> {
-        for book_info in self.books_information.iter() {
-            if book_info.name == title {
-                return Some(book_info);
-            }
-        }
-
-        None
+        self.economy_data.search_book_with_title(title)
     }
 
     pub fn iter_available_books(&self) -> std::slice::Iter<BookInformation> {
-        self.books_information.iter()
+        self.economy_data.iter_available_books()
     }
 
     pub fn customer_random_select(&self) -> &str {
-        &self
-            .customers_name
-            .get(rand::random::<usize>() % self.customers_name.len())
-            .unwrap()
+        self.economy_data.customer_random_select()
     }
 
     pub fn ref_tile_batch(&self, id: TileBatchTextureID) -> TileBatch {
@@ -1329,6 +1514,33 @@ impl GameResource {
         }
     }
 
+    ///
+    /// 再生中のBGMのSoundIDを返す
+    ///
+    pub fn playing_bgm_list(&self) -> Vec<SoundID> {
+        self.bgm_table
+            .iter()
+            .filter(|(_, handler)| handler.is_some())
+            .map(|(sound_id, _)| sound_id.clone())
+            .collect()
+    }
+
+    pub fn stop_all_bgm(&mut self, ctx: &mut ggez::Context) {
+        for sound_id in self.playing_bgm_list() {
+            self.stop_bgm(ctx, sound_id);
+        }
+    }
+
+    ///
+    /// 再生中のBGM1つだけの音量を変更する. 再生していない場合は何もしない
+    ///
+    pub fn set_bgm_volume(&mut self, sound_id: &SoundID, volume: f32) {
+        if let Some(Some(handler)) = self.bgm_table.get(sound_id) {
+            let handler = handler.clone();
+            self.bgm_manager.ref_sound_mut(handler).set_volume(volume);
+        }
+    }
+
     pub fn stop_se(&mut self, ctx: &mut ggez::Context, handler: sound::SoundHandler) {
         self.bgm_manager.stop(ctx, handler);
     }
@@ -1357,27 +1569,68 @@ impl GameResource {
         self.se_manager.change_global_volume(volume);
     }
 
+    pub fn change_voice_volume(&mut self, volume: f32) {
+        self.voice_manager.change_global_volume(volume);
+    }
+
+    ///
+    /// ボイスを再生する. 再生中のボイスは止める
+    /// 一度読み込んだボイスは, 次からは読み込み済みのものを使う
+    ///
+    pub fn play_voice(
+        &mut self,
+        ctx: &mut ggez::Context,
+        path: &str,
+        flags: Option<sound::SoundPlayFlags>,
+    ) -> Result<(), SuzuError> {
+        self.stop_voice(ctx);
+
+        if !self.voice_cache.contains_key(path) {
+            let sound_data =
+                sound::SoundData::new(ctx, path).map_err(|e| SuzuError::missing_asset(path, e))?;
+            self.voice_cache.insert(path.to_string(), sound_data);
+        }
+
+        let sound_data = self.voice_cache.get(path).unwrap().clone();
+        self.voice_handler = Some(self.voice_manager.play(ctx, sound_data, flags));
+
+        Ok(())
+    }
+
+    pub fn stop_voice(&mut self, ctx: &mut ggez::Context) {
+        if let Some(handler) = self.voice_handler.take() {
+            self.voice_manager.stop(ctx, handler);
+        }
+    }
+
+    pub fn is_voice_playing(&self) -> bool {
+        match self.voice_handler {
+            Some(handler) => self.voice_manager.ref_sound(handler).playing(),
+            None => false,
+        }
+    }
+
     pub fn get_default_ad_cost(&self, ty: crate::object::scenario_object::SuzunaAdType) -> u32 {
-        self.ad_info.get_cost(ty)
+        self.economy_data.get_default_ad_cost(ty)
     }
 
     pub fn get_default_ad_reputation_gain(
         &self,
         ty: crate::object::scenario_object::SuzunaAdType,
     ) -> u32 {
-        self.ad_info.get_reputation_gain(ty)
+        self.economy_data.get_default_ad_reputation_gain(ty)
     }
 
     pub fn get_default_ad_agency_cost(&self, ty: &SuzunaAdAgencyType) -> u32 {
-        self.ad_agency_info.get_cost(ty)
+        self.economy_data.get_default_ad_agency_cost(ty)
     }
 
     pub fn get_default_ad_agency_money_gain(&self, ty: &SuzunaAdAgencyType) -> u32 {
-        self.ad_agency_info.get_money_gain(ty)
+        self.economy_data.get_default_ad_agency_money_gain(ty)
     }
 
     pub fn get_todays_customer_dist(&self, date: &GensoDate) -> Clock {
-        self.daily_customer_dist.get_customer_dist_default(date)
+        self.economy_data.get_todays_customer_dist(date)
     }
 }
 
@@ -1751,7 +2004,7 @@ impl ReturnBookInformation {
     }
 
     pub fn new_random(
-        game_data: &GameResource,
+        game_data: &EconomyData,
         borrow_date: GensoDate,
         return_date: GensoDate,
     ) -> Self {
@@ -1783,7 +2036,7 @@ pub struct SuzunaBookPool {
 }
 
 impl SuzunaBookPool {
-    pub fn new(game_data: &GameResource) -> Self {
+    pub fn new(game_data: &EconomyData) -> Self {
         let mut books = Vec::new();
 
         for book_info in game_data.iter_available_books() {
@@ -1891,11 +2144,7 @@ pub struct ReturningRequestPool {
 }
 
 impl ReturningRequestPool {
-    pub fn new(
-        book_pool: &mut SuzunaBookPool,
-        game_data: &GameResource,
-        today: &GensoDate,
-    ) -> Self {
+    pub fn new(book_pool: &mut SuzunaBookPool, game_data: &EconomyData, today: &GensoDate) -> Self {
         let mut returning_request = Vec::new();
 
         let mut day = today.clone();
@@ -1985,10 +2234,11 @@ pub struct SavableData {
     pub game_mode: GameMode,
     pub run_tutorial: bool,
     pub scenario_save_data: Option<ScenarioSceneSaveData>,
+    pub story_variables: StoryVariables,
 }
 
 impl SavableData {
-    pub fn new(game_data: &GameResource, game_mode: GameMode) -> Self {
+    pub fn new(game_data: &EconomyData, game_mode: GameMode) -> Self {
         let date = GensoDate::new(112, 7, 23);
 
         let mut suzuna_book_pool = SuzunaBookPool::new(game_data);
@@ -2029,6 +2279,7 @@ impl SavableData {
             game_mode: game_mode,
             run_tutorial: true,
 	    scenario_save_data: None,
+	    story_variables: StoryVariables::new(),
         }
     }
 
@@ -2036,7 +2287,7 @@ impl SavableData {
         let mut file = File::create(&format!("./resources/save{}", slot))?;
 
         file.write_all(
-            crypt::crypt_str(&serde_json::to_string(self).unwrap())
+            crypt::crypt_str(&save_format::wrap_save_data(self)?)
                 .unwrap()
                 .as_slice(),
         )
@@ -2053,28 +2304,21 @@ impl SavableData {
         }
     }
 
-    pub fn new_load(slot: u8) -> Result<SavableData, ()> {
-        let file = std::fs::File::open(&format!("./resources/save{}", slot));
-        if file.is_err() {
-            return Err(());
-        }
+    ///
+    /// セーブデータを読み込む
+    /// 古いバージョンのセーブデータは移行処理を適用してから読み込み, 適用した移行処理を報告する
+    ///
+    pub fn new_load(slot: u8) -> Result<(SavableData, save_format::SaveLoadReport), SuzuError> {
+        let path = format!("./resources/save{}", slot);
+        let mut file = File::open(&path).map_err(|e| SuzuError::io(&path, e))?;
 
         let mut buf = Vec::new();
-        if let Ok(_) = file.unwrap().read_to_end(&mut buf) {
-            ()
-        } else {
-            return Err(());
-        }
-
-        let content = crypt::decrypt_str(&buf);
+        file.read_to_end(&mut buf)
+            .map_err(|e| SuzuError::io(&path, e))?;
 
-        let savable_data = serde_json::from_str(&content.unwrap());
+        let content = crypt::decrypt_str(&buf).ok_or_else(|| SuzuError::crypt(&path))?;
 
-        if savable_data.is_err() {
-            Err(())
-        } else {
-            Ok(savable_data.unwrap())
-        }
+        save_format::load_save_data(&path, &content)
     }
 
     pub fn replace(&mut self, data: SavableData) {
@@ -2089,6 +2333,7 @@ impl SavableData {
         self.award_data = data.award_data;
         self.game_mode = data.game_mode;
         self.run_tutorial = data.run_tutorial;
+        self.story_variables = data.story_variables;
     }
 
     pub fn change_ad_status(&mut self, ad_type: SuzunaAdType, status: bool) {
@@ -2107,7 +2352,7 @@ impl SavableData {
         *self.agency_status.get(agency_type).unwrap()
     }
 
-    pub fn pay_ad_cost(&mut self, resource: &GameResource) -> i32 {
+    pub fn pay_ad_cost(&mut self, resource: &EconomyData) -> i32 {
         let mut total_cost = 0;
 
         for (ad_type, used) in self.ad_status.iter() {
@@ -2136,6 +2381,43 @@ impl SavableData {
     pub fn game_cleared(&self) -> bool {
 	self.task_result.total_money > 100000
     }
+
+    ///
+    /// シナリオの条件式から参照される名前を解決する
+    /// ゲームの状態を表す名前を優先し, それ以外はシナリオ変数, フラグの順に探す
+    ///
+    pub fn story_value(&self, name: &str) -> Option<StoryValue> {
+	match name {
+	    "money" => Some(StoryValue::Int(self.task_result.total_money as i64)),
+	    "reputation" => Some(StoryValue::Int(
+		self.suzunaan_status.get_current_reputation() as i64,
+	    )),
+	    "kosuzu_hp" => Some(StoryValue::Int(self.suzunaan_status.kosuzu_hp as i64)),
+	    "season" | "date.season" => Some(StoryValue::Int(self.date.season as i64)),
+	    "month" | "date.month" => Some(StoryValue::Int(self.date.month as i64)),
+	    "day" | "date.day" => Some(StoryValue::Int(self.date.day as i64)),
+	    "days" => Some(StoryValue::Int(
+		GensoDate::new(112, 7, 23).diff_day(&self.date) as i64,
+	    )),
+	    "cleared" => Some(StoryValue::Bool(self.game_cleared())),
+	    _ => self.story_variables.lookup(name),
+	}
+    }
+
+    ///
+    /// シナリオのテキストに埋め込む文字列を返す
+    /// 文字列の変数を優先し, なければstory_valueの値を文字列にする
+    ///
+    pub fn story_text(&self, name: &str) -> Option<String> {
+	if let Some(text) = self.story_variables.get_text(name) {
+	    return Some(text.to_string());
+	}
+
+	self.story_value(name).map(|value| match value {
+	    StoryValue::Bool(b) => b.to_string(),
+	    StoryValue::Int(n) => n.to_string(),
+	})
+    }
 }
 
 pub struct ResultReportStringTable {
@@ -2293,9 +2575,23 @@ pub struct GameConfig {
     pause_when_inactive: bool,
     fullscreen_mode: bool,
     extra_unlocked: bool,
+    #[serde(default = "GameConfig::default_auto_advance_wait")]
+    auto_advance_wait: Clock,
+    #[serde(default = "GameConfig::default_voice_volume")]
+    voice_volume: f32,
+    #[serde(default)]
+    locale: Option<String>,
 }
 
 impl GameConfig {
+    fn default_auto_advance_wait() -> Clock {
+        90
+    }
+
+    fn default_voice_volume() -> f32 {
+        1.0
+    }
+
     pub fn new_from_toml(ctx: &mut ggez::Context, path: &str) -> Self {
         match File::open("./game_config") {
             Ok(mut file) => {
@@ -2336,6 +2632,10 @@ impl GameConfig {
         self.se_volume = volume / 100.0;
     }
 
+    pub fn set_voice_volume_100(&mut self, volume: f32) {
+        self.voice_volume = volume / 100.0;
+    }
+
     pub fn get_bgm_volume(&self) -> f32 {
         self.bgm_volume
     }
@@ -2344,6 +2644,10 @@ impl GameConfig {
         self.se_volume
     }
 
+    pub fn get_voice_volume(&self) -> f32 {
+        self.voice_volume
+    }
+
     pub fn is_pause_when_inactive(&self) -> bool {
         self.pause_when_inactive
     }
@@ -2352,6 +2656,10 @@ impl GameConfig {
         self.pause_when_inactive = flag;
     }
 
+    pub fn get_locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
     pub fn is_fullscreen_mode_configed(&self) -> bool {
 	self.fullscreen_mode
     }
@@ -2360,6 +2668,17 @@ impl GameConfig {
 	self.fullscreen_mode = flag;
     }
 
+    ///
+    /// オートモードで, テキストを表示し終えてから次に進むまでのフレーム数
+    ///
+    pub fn get_auto_advance_wait(&self) -> Clock {
+        self.auto_advance_wait
+    }
+
+    pub fn set_auto_advance_wait(&mut self, wait: Clock) {
+        self.auto_advance_wait = wait;
+    }
+
     pub fn save_config(&self) {
         let mut file = File::create("./game_config").expect("failed to create game config file.");
 
@@ -2421,6 +2740,8 @@ pub struct PermanentSaveData {
     story_cleared: bool,
     hard_mode_records: Vec<HardModeRecord>,
     story_mode_records: Vec<HardModeRecord>,
+    #[serde(default)]
+    read_scenario_elements: BTreeMap<String, BTreeSet<i32>>,
 }
 
 impl PermanentSaveData {
@@ -2429,35 +2750,32 @@ impl PermanentSaveData {
             story_cleared: false,
             hard_mode_records: Vec::new(),
             story_mode_records: Vec::new(),
+            read_scenario_elements: BTreeMap::new(),
         }
     }
 
-    pub fn from_toml() -> Self {
-        match File::open("./permanent_save") {
-            Ok(mut file) => {
-                let mut buf = Vec::new();
-                match file.read_to_end(&mut buf) {
-                    Ok(_) => (),
-                    Err(_) => return Self::new_empty(),
-                }
+    ///
+    /// ./permanent_saveを読み込む
+    /// ファイルが存在しない場合は空のデータを返す
+    ///
+    pub fn from_toml() -> Result<Self, SuzuError> {
+        let path = "./permanent_save";
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new_empty()),
+            Err(e) => return Err(SuzuError::io(path, e)),
+        };
 
-                let content = crypt::decrypt_str(&buf);
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)
+            .map_err(|e| SuzuError::io(path, e))?;
 
-                if content.is_none() {
-                    return Self::new_empty();
-                }
+        let content = crypt::decrypt_str(&buf).ok_or_else(|| SuzuError::crypt(path))?;
 
-                let permanent_data: Result<PermanentSaveData, toml::de::Error> =
-                    toml::from_str(&content.unwrap());
-                match permanent_data {
-                    Ok(p) => {
-                        return p;
-                    }
-                    Err(_) => return Self::new_empty(),
-                }
-            }
-            Err(_) => Self::new_empty(),
-        }
+        toml::from_str(&content).map_err(|e| SuzuError::TomlParse {
+            path: path.to_string(),
+            source: e,
+        })
     }
 
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
@@ -2498,6 +2816,22 @@ impl PermanentSaveData {
         self.story_cleared
     }
 
+    ///
+    /// シナリオファイルのScenarioElementIDを既読にする
+    ///
+    pub fn mark_scenario_read(&mut self, scenario_file: &str, element_id: i32) {
+        self.read_scenario_elements
+            .entry(scenario_file.to_string())
+            .or_insert_with(BTreeSet::new)
+            .insert(element_id);
+    }
+
+    pub fn is_scenario_read(&self, scenario_file: &str, element_id: i32) -> bool {
+        self.read_scenario_elements
+            .get(scenario_file)
+            .map_or(false, |read| read.contains(&element_id))
+    }
+
     pub fn sort_records(&mut self) {
         self.story_mode_records
             .sort_by(|a, b| b.total_money.partial_cmp(&a.total_money).unwrap());
@@ -2560,11 +2894,40 @@ impl<'ctx> SuzuContext<'ctx> {
         self.config.set_se_volume_100(volume);
     }
 
+    pub fn change_voice_volume(&mut self, volume: f32) {
+        self.resource.change_voice_volume(volume / 100.0);
+        self.config.set_voice_volume_100(volume);
+    }
+
+    pub fn play_voice(&mut self, path: &str) -> Result<(), SuzuError> {
+        let volume = self.config.get_voice_volume();
+        self.resource.play_voice(
+            self.context,
+            path,
+            Some(sound::SoundPlayFlags::new(10, 1.0, false, volume)),
+        )
+    }
+
+    pub fn stop_voice(&mut self) {
+        self.resource.stop_voice(self.context);
+    }
+
+    pub fn is_voice_playing(&self) -> bool {
+        self.resource.is_voice_playing()
+    }
+
+    ///
+    /// セーブデータに対する経営シミュレーションを取り出す
+    ///
+    pub fn simulator(&mut self) -> SuzunaSimulator {
+        SuzunaSimulator::new(
+            self.resource.economy_data(),
+            self.savable_data.as_mut().expect("save data not found"),
+        )
+    }
+
     pub fn pay_ad_cost(&mut self) -> i32 {
-        self.savable_data
-            .as_mut()
-            .expect("save data not found")
-            .pay_ad_cost(self.resource)
+        self.simulator().pay_ad_cost()
     }
 
     pub fn holding_week_schedule_is_available(&self) -> bool {
@@ -2574,69 +2937,29 @@ impl<'ctx> SuzuContext<'ctx> {
     }
 
     pub fn go_next_day(&mut self) {
-        self.take_save_data_mut().date.add_day(1);
+        self.simulator().go_next_day();
     }
 
     pub fn current_total_ad_cost(&self) -> i32 {
-        let mut total_ad_cost = 0;
-
-        for ad_type in vec![
-            SuzunaAdType::AdPaper,
-            SuzunaAdType::Chindon,
-            SuzunaAdType::ShopNobori,
-            SuzunaAdType::TownNobori,
-            SuzunaAdType::NewsPaper,
-            SuzunaAdType::BunBunMaruPaper,
-        ] {
-            if *self.take_save_data().ad_status.get(&ad_type).unwrap() {
-                total_ad_cost += self.resource.get_default_ad_cost(ad_type);
-            }
-        }
-
-        return total_ad_cost as i32;
+        simulation::current_total_ad_cost(self.resource.economy_data(), self.take_save_data())
     }
 
     pub fn current_total_ad_reputation_gain(&self) -> i32 {
-        let mut total_ad_reputation_gain = 0;
-
-        for ad_type in vec![
-            SuzunaAdType::AdPaper,
-            SuzunaAdType::Chindon,
-            SuzunaAdType::ShopNobori,
-            SuzunaAdType::TownNobori,
-            SuzunaAdType::NewsPaper,
-            SuzunaAdType::BunBunMaruPaper,
-        ] {
-            if *self.take_save_data().ad_status.get(&ad_type).unwrap() {
-                total_ad_reputation_gain += self.resource.get_default_ad_reputation_gain(ad_type);
-            }
-        }
-
-        return total_ad_reputation_gain as i32;
+        simulation::current_total_ad_reputation_gain(
+            self.resource.economy_data(),
+            self.take_save_data(),
+        )
     }
 
     pub fn current_total_ad_agency_money_gain(&self) -> i32 {
-        let mut total_ad_agency_money_gain = 0;
-
-        for ad_type in vec![
-            SuzunaAdAgencyType::HakureiJinja,
-            SuzunaAdAgencyType::KirisameMahoten,
-            SuzunaAdAgencyType::GettoDango,
-            SuzunaAdAgencyType::Kusuriya,
-            SuzunaAdAgencyType::Hieda,
-            SuzunaAdAgencyType::YamaJinja,
-        ] {
-            if *self.take_save_data().agency_status.get(&ad_type).unwrap() {
-                total_ad_agency_money_gain +=
-                    self.resource.get_default_ad_agency_money_gain(&ad_type);
-            }
-        }
-
-        return total_ad_agency_money_gain as i32;
+        simulation::current_total_ad_agency_money_gain(
+            self.resource.economy_data(),
+            self.take_save_data(),
+        )
     }
 
     pub fn reset_save_data(&mut self, game_mode: GameMode) {
-        *self.savable_data = Some(SavableData::new(&self.resource, game_mode));
+        *self.savable_data = Some(SavableData::new(self.resource.economy_data(), game_mode));
     }
 
     pub fn save(&mut self, slot_id: u8) -> Result<(), ()> {
@@ -2739,7 +3062,21 @@ impl SceneController {
 	    ggraphics::set_fullscreen(ctx, ggez::conf::FullscreenType::Desktop);
 	}
 
-        let mut permanent_save_data = PermanentSaveData::from_toml();
+        if let Err(e) = game_data.load_translation(ctx, game_config.get_locale()) {
+            eprintln!("{}", e);
+        }
+
+        if let Some(group) = scene::SceneID::Title.texture_group() {
+            game_data.switch_texture_group(ctx, group);
+        }
+
+        let mut permanent_save_data = match PermanentSaveData::from_toml() {
+            Ok(data) => data,
+            Err(e) => {
+                eprintln!("{}", e);
+                PermanentSaveData::new_empty()
+            }
+        };
 
         let mut _redraw_request = scene::DrawRequest::Draw;
 
@@ -2778,7 +3115,11 @@ impl SceneController {
         ctx: &mut ggez::Context,
         game_data: &'a mut GameResource,
         next_scene_id: scene::SceneID,
-    ) {
+    ) -> Result<(), SuzuError> {
+        if let Some(group) = next_scene_id.texture_group() {
+            game_data.switch_texture_group(ctx, group);
+        }
+
         let mut ctx = SuzuContext {
             context: ctx,
             resource: game_data,
@@ -2793,7 +3134,7 @@ impl SceneController {
         match next_scene_id {
             scene::SceneID::SuzunaShop => {
                 self.current_scene =
-                    TopScene::SuzunaScene(scene::suzuna_scene::SuzunaScene::new(&mut ctx, 0))
+                    TopScene::SuzunaScene(scene::suzuna_scene::SuzunaScene::new(&mut ctx, 0)?)
             }
             scene::SceneID::Scenario => match self.current_scene {
                 TopScene::SuzunaScene(_) => {
@@ -2840,6 +3181,8 @@ impl SceneController {
             scene::SceneID::Null => self.current_scene = TopScene::Null(scene::NullScene::new()),
             _ => (),
         }
+
+        Ok(())
     }
 
     fn switch_scene_with_stacking<'a>(
@@ -2847,7 +3190,12 @@ impl SceneController {
         ctx: &mut ggez::Context,
         game_data: &'a mut GameResource,
         next_scene_id: scene::SceneID,
-    ) {
+    ) -> Result<(), SuzuError> {
+        // 下のシーンは残っているので, 解放せずに先読みだけ行う
+        if let Some(group) = next_scene_id.texture_group() {
+            game_data.preload_texture_group(ctx, group);
+        }
+
         let mut ctx = SuzuContext {
             context: ctx,
             resource: game_data,
@@ -2861,7 +3209,7 @@ impl SceneController {
 
         let next_scene = match next_scene_id {
             scene::SceneID::SuzunaShop => Some(TopScene::SuzunaScene(
-                scene::suzuna_scene::SuzunaScene::new(&mut ctx, 0),
+                scene::suzuna_scene::SuzunaScene::new(&mut ctx, 0)?,
             )),
             scene::SceneID::Save => Some(TopScene::SaveScene(scene::save_scene::SaveScene::new(
                 &mut ctx,
@@ -2874,6 +3222,8 @@ impl SceneController {
             std::mem::swap(&mut self.current_scene, &mut scene);
             self.scene_stack.push(scene);
         }
+
+        Ok(())
     }
 
     fn run_pre_process(&mut self, ctx: &mut ggez::Context, game_data: &mut GameResource) {
@@ -2906,7 +3256,11 @@ impl SceneController {
         //) as f32 / 1000000.0);
     }
 
-    fn run_post_process<'a>(&mut self, ctx: &mut ggez::Context, game_data: &'a mut GameResource) {
+    fn run_post_process<'a>(
+        &mut self,
+        ctx: &mut ggez::Context,
+        game_data: &'a mut GameResource,
+    ) -> Result<(), SuzuError> {
         let mut suzu_ctx = SuzuContext {
             context: ctx,
             resource: game_data,
@@ -2922,14 +3276,14 @@ impl SceneController {
             scene::SceneTransition::Keep => (),
             scene::SceneTransition::Reset => (),
             scene::SceneTransition::SwapTransition => {
-                self.switch_scene_with_swap(ctx, game_data, self.current_scene.abs().transition())
+                self.switch_scene_with_swap(ctx, game_data, self.current_scene.abs().transition())?
             }
             scene::SceneTransition::StackingTransition => {
                 self.switch_scene_with_stacking(
                     ctx,
                     game_data,
                     self.current_scene.abs().transition(),
-                );
+                )?;
             }
             scene::SceneTransition::PoppingTransition => {
                 if let Some(scene) = self.scene_stack.pop() {
@@ -2948,6 +3302,8 @@ impl SceneController {
         }
         self.global_clock += 1;
         self.redraw_request = scene::DrawRequest::Skip;
+
+        Ok(())
     }
 
     fn key_down_event(
@@ -3136,6 +3492,7 @@ pub struct State {
 
 impl ggez::event::EventHandler for State {
     fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
+        self.game_data.update_texture_preload(ctx);
         self.scene_controller
             .run_pre_process(ctx, &mut self.game_data);
 
@@ -3160,13 +3517,20 @@ impl ggez::event::EventHandler for State {
 
         graphics::present(ctx)?;
 
+        // シーンの切り替えで設定ファイルを読み込めなかった場合は, ゲームを終了する
         self.scene_controller
-		.run_post_process(ctx, &mut self.game_data);
+		.run_post_process(ctx, &mut self.game_data)
+		.map_err(|e| GameError::ResourceLoadError(e.to_string()))?;
 
         Ok(())
     }
 
     fn quit_event(&mut self, ctx: &mut Context) -> bool {
+        // 既読情報を残すため, 終了時にも永続セーブデータを保存する
+        if let Err(e) = self.scene_controller.permanent_save_data.save() {
+            eprintln!("{}", e);
+        }
+
         ggez::event::quit(ctx);
         false
     }
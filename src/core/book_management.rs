@@ -1,9 +1,59 @@
 use std::collections::HashMap;
 
-use crate::parse_toml_file;
+use serde::Deserialize;
 
+use crate::try_parse_toml_file;
+
+use super::error::TomlNode;
+use super::schema;
 use super::{GameResource, GensoDate};
-use super::{BookInformation, SuzuContext};
+use super::{BookInformation, SuzuContext, SuzuError};
+
+pub const NEW_BOOK_SCHEDULE_PATH: &str = "/other_config/new_book_schedule.toml";
+
+///
+/// 新刊の予定に書かれる本. numは入荷する冊数
+///
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewBookEntry {
+    pub name: String,
+    pub pages: usize,
+    pub size: String,
+    pub billing_number: u16,
+    pub base_price: u32,
+    pub num: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DayNewBookEntry {
+    pub date: GensoDate,
+    pub books_information: Vec<NewBookEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewBookScheduleFile {
+    #[serde(rename = "new-book-schedule")]
+    pub new_book_schedule: Vec<DayNewBookEntry>,
+}
+
+impl NewBookScheduleFile {
+    ///
+    /// 日ごとに検証し, 問題のある日を全てproblemsに追加する
+    ///
+    pub fn validate(root: &TomlNode, problems: &mut Vec<SuzuError>) {
+        match root.get("new-book-schedule") {
+            Ok(schedule) => {
+                schema::deserialize_each::<DayNewBookEntry>(&schedule, problems);
+            }
+            Err(e) => problems.push(e),
+        }
+    }
+
+    pub fn load(ctx: &mut ggez::Context, path: &str) -> Result<Self, SuzuError> {
+        let root = try_parse_toml_file!(ctx, path)?;
+        schema::deserialize(path, root)
+    }
+}
 
 #[derive(Clone)]
 pub struct DayNewBooks {
@@ -11,23 +61,19 @@ pub struct DayNewBooks {
 }
 
 impl DayNewBooks {
-    pub fn from_toml_value(toml_value: &toml::Value) -> Self {
+    pub fn from_entries(entries: &[NewBookEntry]) -> Self {
         let mut new_books = Vec::new();
 
-        for books_information in toml_value.as_array().unwrap() {
-            let struct_table = books_information.as_table().unwrap();
-
-            let book_num = struct_table["num"].as_integer().unwrap();
-
+        for entry in entries {
             let book_info = BookInformation::new(
-                struct_table["name"].as_str().unwrap().to_string(),
-                struct_table["pages"].as_integer().unwrap() as usize,
-                struct_table["size"].as_str().unwrap().to_string(),
-                struct_table["billing_number"].as_integer().unwrap() as u16,
-                struct_table["base_price"].as_integer().unwrap() as u32,
+                entry.name.clone(),
+                entry.pages,
+                entry.size.clone(),
+                entry.billing_number,
+                entry.base_price,
             );
 
-            for _ in 0..book_num {
+            for _ in 0..entry.num {
                 new_books.push(book_info.clone_with_new_id_condition());
             }
         }
@@ -61,32 +107,35 @@ pub struct NewBookSchedule {
 }
 
 impl NewBookSchedule {
-    pub fn from_toml<'a>(ctx: &mut SuzuContext<'a>, file_path: &str) -> Self {
+    pub fn from_toml<'a>(
+        ctx: &mut SuzuContext<'a>,
+        file_path: &str,
+    ) -> Result<Self, SuzuError> {
         let mut schedule_map = HashMap::new();
 
-        let root = parse_toml_file!(ctx.context, file_path);
-
-        let new_book_schedule = root["new-book-schedule"].as_array().unwrap();
+        let schedule_file = NewBookScheduleFile::load(ctx.context, file_path)?;
 
-        for day_new_book in new_book_schedule {
-            let struct_table = day_new_book.as_table().unwrap();
-
-            let date_data = struct_table["date"].as_table().unwrap();
-
-            let genso_date = GensoDate::new(
-                date_data["season"].as_integer().unwrap() as u32,
-                date_data["month"].as_integer().unwrap() as u8,
-                date_data["day"].as_integer().unwrap() as u8,
+        for day_new_book in schedule_file.new_book_schedule.iter() {
+            schedule_map.insert(
+                day_new_book.date,
+                DayNewBooks::from_entries(&day_new_book.books_information),
             );
+        }
 
-            let day_new_books = DayNewBooks::from_toml_value(&struct_table["books_information"]);
-
-            schedule_map.insert(genso_date, day_new_books);
+        // MODの新刊は, 同じ日の新刊に追加する
+        for day_new_book in ctx.resource.get_mods().new_book_schedules() {
+            schedule_map
+                .entry(day_new_book.date)
+                .or_insert_with(|| DayNewBooks {
+                    new_books: Vec::new(),
+                })
+                .new_books
+                .extend(DayNewBooks::from_entries(&day_new_book.books_information).new_books);
         }
 
-        NewBookSchedule {
+        Ok(NewBookSchedule {
             new_book_schedule: schedule_map,
-        }
+        })
     }
 
     pub fn get_schedule_at(&self, date: &GensoDate) -> Option<&DayNewBooks> {
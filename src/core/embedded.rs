@@ -0,0 +1,46 @@
+///
+/// # 実行ファイルに埋め込まれているリソース
+///
+/// リソースのパスと内容の組. ファイルを追加する場合はここに一行追加する
+/// リソースを読み込む関数は, resourcesディレクトリよりも先にここを探す
+///
+const EMBEDDED_RESOURCES: &[(&str, &str)] = &[
+    (
+        "/scenario/day_7_23.toml",
+        include_str!("../../embedded/scenario/day_7_23.toml"),
+    ),
+    (
+        "/scenario/time_attack_first.toml",
+        include_str!("../../embedded/scenario/time_attack_first.toml"),
+    ),
+    (
+        "/scenario/time_attack_default.toml",
+        include_str!("../../embedded/scenario/time_attack_default.toml"),
+    ),
+    (
+        "/scenario/time_attack_week_first.toml",
+        include_str!("../../embedded/scenario/time_attack_week_first.toml"),
+    ),
+    (
+        "/scenario/no_enough_hp.toml",
+        include_str!("../../embedded/scenario/no_enough_hp.toml"),
+    ),
+];
+
+///
+/// 埋め込まれているリソースの内容を返す
+/// 該当するものがなければNoneを返す
+///
+pub fn embedded_resource(path: &str) -> Option<&'static str> {
+    EMBEDDED_RESOURCES
+        .iter()
+        .find(|(embedded_path, _)| *embedded_path == path)
+        .map(|(_, content)| *content)
+}
+
+///
+/// 埋め込まれているリソースのパスの一覧
+///
+pub fn embedded_resource_paths() -> impl Iterator<Item = &'static str> {
+    EMBEDDED_RESOURCES.iter().map(|(path, _)| *path)
+}
@@ -0,0 +1,304 @@
+use std::fmt;
+
+///
+/// # 読み込み処理で発生するエラー
+///
+/// どのファイルのどのキーで失敗したのかを保持し,
+/// "day_7_25.toml: scenario-group[4] missing next-id" のような形式で表示できる
+///
+#[derive(Debug)]
+pub enum SuzuError {
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    TomlParse {
+        path: String,
+        source: toml::de::Error,
+    },
+    JsonParse {
+        path: String,
+        source: serde_json::Error,
+    },
+    MissingKey {
+        path: String,
+        parent: String,
+        key: String,
+    },
+    InvalidType {
+        path: String,
+        key: String,
+        expected: &'static str,
+    },
+    InvalidValue {
+        path: String,
+        key: String,
+        value: String,
+    },
+    ///
+    /// 型付きの構造体に変換できなかった. detailはserdeのエラー
+    ///
+    InvalidData {
+        path: String,
+        key: String,
+        detail: String,
+    },
+    Crypt {
+        path: String,
+    },
+    MissingAsset {
+        path: String,
+        detail: String,
+    },
+    Network {
+        url: String,
+        detail: String,
+    },
+    ScriptParse {
+        path: String,
+        line: usize,
+        detail: String,
+    },
+    Validation {
+        problems: Vec<SuzuError>,
+    },
+}
+
+impl SuzuError {
+    pub fn io(path: &str, source: std::io::Error) -> Self {
+        SuzuError::Io {
+            path: path.to_string(),
+            source: source,
+        }
+    }
+
+    pub fn missing_asset<E: fmt::Display>(path: &str, detail: E) -> Self {
+        SuzuError::MissingAsset {
+            path: path.to_string(),
+            detail: detail.to_string(),
+        }
+    }
+
+    pub fn script_parse<E: fmt::Display>(path: &str, line: usize, detail: E) -> Self {
+        SuzuError::ScriptParse {
+            path: path.to_string(),
+            line: line,
+            detail: detail.to_string(),
+        }
+    }
+
+    pub fn crypt(path: &str) -> Self {
+        SuzuError::Crypt {
+            path: path.to_string(),
+        }
+    }
+
+    ///
+    /// ファイルが存在しないことによるエラーかどうか
+    ///
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            SuzuError::Io { source, .. } => source.kind() == std::io::ErrorKind::NotFound,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for SuzuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SuzuError::Io { path, source } => write!(f, "{}: {}", path, source),
+            SuzuError::TomlParse { path, source } => write!(f, "{}: {}", path, source),
+            SuzuError::JsonParse { path, source } => write!(f, "{}: {}", path, source),
+            SuzuError::MissingKey { path, parent, key } => {
+                if parent.is_empty() {
+                    write!(f, "{}: missing {}", path, key)
+                } else {
+                    write!(f, "{}: {} missing {}", path, parent, key)
+                }
+            }
+            SuzuError::InvalidType {
+                path,
+                key,
+                expected,
+            } => write!(f, "{}: {} must be {}", path, key, expected),
+            SuzuError::InvalidValue { path, key, value } => {
+                write!(f, "{}: {} has unknown value \"{}\"", path, key, value)
+            }
+            SuzuError::InvalidData { path, key, detail } => {
+                if key.is_empty() {
+                    write!(f, "{}: {}", path, detail)
+                } else {
+                    write!(f, "{}: {} {}", path, key, detail)
+                }
+            }
+            SuzuError::Crypt { path } => write!(f, "{}: failed to decrypt", path),
+            SuzuError::MissingAsset { path, detail } => {
+                write!(f, "{}: failed to load asset ({})", path, detail)
+            }
+            SuzuError::Network { url, detail } => write!(f, "{}: {}", url, detail),
+            SuzuError::ScriptParse { path, line, detail } => {
+                write!(f, "{}:{}: {}", path, line, detail)
+            }
+            SuzuError::Validation { problems } => {
+                write!(f, "{} problems found in config files", problems.len())?;
+                for problem in problems.iter() {
+                    write!(f, "\n  {}", problem)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SuzuError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SuzuError::Io { source, .. } => Some(source),
+            SuzuError::TomlParse { source, .. } => Some(source),
+            SuzuError::JsonParse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse_toml_str(path: &str, content: &str) -> Result<toml::Value, SuzuError> {
+    content
+        .parse::<toml::Value>()
+        .map_err(|e| SuzuError::TomlParse {
+            path: path.to_string(),
+            source: e,
+        })
+}
+
+///
+/// # ファイル名とキーの位置を覚えているTOMLの値
+///
+/// 値が存在しない場合や型が違う場合に, その位置を含んだSuzuErrorを返す
+///
+#[derive(Clone)]
+pub struct TomlNode<'a> {
+    file: &'a str,
+    key_path: String,
+    value: &'a toml::Value,
+}
+
+impl<'a> TomlNode<'a> {
+    pub fn root(file: &'a str, value: &'a toml::Value) -> Self {
+        TomlNode {
+            file: file,
+            key_path: String::new(),
+            value: value,
+        }
+    }
+
+    pub fn value(&self) -> &'a toml::Value {
+        self.value
+    }
+
+    pub fn file(&self) -> &'a str {
+        self.file
+    }
+
+    pub fn key_path(&self) -> &str {
+        self.key_path.as_str()
+    }
+
+    fn child_path(&self, key: &str) -> String {
+        if self.key_path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.key_path, key)
+        }
+    }
+
+    pub fn get_opt(&self, key: &str) -> Option<TomlNode<'a>> {
+        self.value.get(key).map(|value| TomlNode {
+            file: self.file,
+            key_path: self.child_path(key),
+            value: value,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Result<TomlNode<'a>, SuzuError> {
+        self.get_opt(key).ok_or_else(|| SuzuError::MissingKey {
+            path: self.file.to_string(),
+            parent: self.key_path.clone(),
+            key: key.to_string(),
+        })
+    }
+
+    pub fn invalid_type(&self, expected: &'static str) -> SuzuError {
+        SuzuError::InvalidType {
+            path: self.file.to_string(),
+            key: self.key_path.clone(),
+            expected: expected,
+        }
+    }
+
+    pub fn invalid_value(&self, value: &str) -> SuzuError {
+        SuzuError::InvalidValue {
+            path: self.file.to_string(),
+            key: self.key_path.clone(),
+            value: value.to_string(),
+        }
+    }
+
+    pub fn invalid_data<E: fmt::Display>(&self, detail: E) -> SuzuError {
+        SuzuError::InvalidData {
+            path: self.file.to_string(),
+            key: self.key_path.clone(),
+            detail: detail.to_string(),
+        }
+    }
+
+    pub fn as_str(&self) -> Result<&'a str, SuzuError> {
+        self.value
+            .as_str()
+            .ok_or_else(|| self.invalid_type("string"))
+    }
+
+    pub fn as_integer(&self) -> Result<i64, SuzuError> {
+        self.value
+            .as_integer()
+            .ok_or_else(|| self.invalid_type("integer"))
+    }
+
+    pub fn as_float(&self) -> Result<f64, SuzuError> {
+        self.value
+            .as_float()
+            .ok_or_else(|| self.invalid_type("float"))
+    }
+
+    pub fn as_bool(&self) -> Result<bool, SuzuError> {
+        self.value
+            .as_bool()
+            .ok_or_else(|| self.invalid_type("boolean"))
+    }
+
+    pub fn as_table(&self) -> Result<&'a toml::value::Table, SuzuError> {
+        self.value
+            .as_table()
+            .ok_or_else(|| self.invalid_type("table"))
+    }
+
+    ///
+    /// 配列の各要素を, "key[index]" という位置を持つTomlNodeとして返す
+    ///
+    pub fn as_array(&self) -> Result<Vec<TomlNode<'a>>, SuzuError> {
+        let array = self
+            .value
+            .as_array()
+            .ok_or_else(|| self.invalid_type("array"))?;
+
+        Ok(array
+            .iter()
+            .enumerate()
+            .map(|(index, value)| TomlNode {
+                file: self.file,
+                key_path: format!("{}[{}]", self.key_path, index),
+                value: value,
+            })
+            .collect())
+    }
+}
@@ -1,6 +1,6 @@
 use super::*;
 
-#[derive(Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum GoingOutEvent {
     AkyuTei,
     Dangoya,
@@ -10,7 +10,7 @@ pub enum GoingOutEvent {
 pub const GOING_OUT_MONEY_COST: i64 = 400;
 pub const TAKING_REST_REPUTATION_COST: i64 = 2;
 
-#[derive(Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum DayWorkType {
     ShopWork,
     GoingOut(GoingOutEvent),
@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::book_management::DayNewBookEntry;
+use super::resource_pack::{self, ResourcePack};
+use super::{error, schema, BookInformation, ScenarioTable, SuzuError};
+use super::{GeneralScenarioEntry, ScenarioTableEntry};
+
+pub const MOD_DIRECTORY: &str = "./mods";
+pub const MOD_MANIFEST_FILE: &str = "mod.toml";
+pub const MOD_PACK_FILE: &str = "resources.suzupack";
+
+///
+/// MODではなく, ゲーム本体で定義されていることを表す名前
+///
+const BASE_DEFINITION: &str = "base";
+
+///
+/// MODが追加する本の情報. 状態やidは読み込むときに決める
+///
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModBookInformation {
+    pub name: String,
+    pub pages: usize,
+    pub size: String,
+    pub billing_number: u16,
+    pub base_price: u32,
+}
+
+impl ModBookInformation {
+    pub fn to_book_information(&self) -> BookInformation {
+        BookInformation::new(
+            self.name.clone(),
+            self.pages,
+            self.size.clone(),
+            self.billing_number,
+            self.base_price,
+        )
+    }
+}
+
+///
+/// # MODのマニフェスト
+///
+/// mods/<MOD>/mod.tomlに置く
+/// new-book-schedule, scenario-table, general-scenario-tableは本体のファイルと同じ書き方をする
+/// event-panelには, 追加先のイベントマップのパスをmapに書く
+///
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModManifest {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub load_order: i32,
+    #[serde(default)]
+    pub books_information: Vec<ModBookInformation>,
+    #[serde(default)]
+    pub customers_name: Vec<String>,
+    #[serde(default, rename = "new-book-schedule")]
+    pub new_book_schedule: Vec<DayNewBookEntry>,
+    #[serde(default, rename = "scenario-table")]
+    pub scenario_table: Vec<ScenarioTableEntry>,
+    #[serde(default, rename = "general-scenario-table")]
+    pub general_scenario_table: Vec<GeneralScenarioEntry>,
+    #[serde(default, rename = "event-panel")]
+    pub event_panel: Vec<toml::Value>,
+}
+
+impl ModManifest {
+    pub fn from_toml_str(path: &str, s: &str) -> Result<ModManifest, SuzuError> {
+        schema::deserialize(path, error::parse_toml_str(path, s)?)
+    }
+}
+
+pub struct LoadedMod {
+    manifest: ModManifest,
+    manifest_path: String,
+    directory: PathBuf,
+}
+
+impl LoadedMod {
+    pub fn new(manifest: ModManifest, manifest_path: &str, directory: &Path) -> Self {
+        LoadedMod {
+            manifest: manifest,
+            manifest_path: manifest_path.to_string(),
+            directory: directory.to_path_buf(),
+        }
+    }
+
+    pub fn load(directory: &Path) -> Result<Self, SuzuError> {
+        let manifest_path = directory.join(MOD_MANIFEST_FILE);
+        let path_str = manifest_path.to_string_lossy().to_string();
+        let content =
+            std::fs::read_to_string(&manifest_path).map_err(|e| SuzuError::io(&path_str, e))?;
+
+        Ok(Self::new(
+            ModManifest::from_toml_str(&path_str, &content)?,
+            &path_str,
+            directory,
+        ))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    pub fn version(&self) -> &str {
+        &self.manifest.version
+    }
+
+    pub fn manifest_path(&self) -> &str {
+        &self.manifest_path
+    }
+
+    pub fn manifest(&self) -> &ModManifest {
+        &self.manifest
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+}
+
+///
+/// 同じものを複数の定義元が定義していることを表す
+/// 後から読み込まれたMODの定義が使われる
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModConflict {
+    pub mod_name: String,
+    pub previous: String,
+    pub kind: &'static str,
+    pub key: String,
+}
+
+impl std::fmt::Display for ModConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "mod {}: {} \"{}\" is already defined by {}, overridden",
+            self.mod_name, self.kind, self.key, self.previous
+        )
+    }
+}
+
+fn panel_position(panel: &toml::Value) -> Option<(i64, i64)> {
+    let position = panel.get("position")?;
+    Some((
+        position.get("x")?.as_integer()?,
+        position.get("y")?.as_integer()?,
+    ))
+}
+
+///
+/// # 読み込んだMODの一覧
+///
+/// MODはload_orderの小さい順, 同じならディレクトリ名の順に読み込む
+/// 後から読み込んだMODほど優先され, 定義が重なった場合はconflictsに記録する
+///
+pub struct ModRegistry {
+    mods: Vec<LoadedMod>,
+    conflicts: Vec<ModConflict>,
+}
+
+impl ModRegistry {
+    pub fn new(mut mods: Vec<LoadedMod>) -> Self {
+        mods.sort_by(|a, b| {
+            a.manifest
+                .load_order
+                .cmp(&b.manifest.load_order)
+                .then_with(|| a.directory.cmp(&b.directory))
+        });
+
+        ModRegistry {
+            mods: mods,
+            conflicts: Vec::new(),
+        }
+    }
+
+    ///
+    /// ディレクトリ内の, mod.tomlを持つディレクトリをMODとして読み込む
+    /// 読み込めなかったMODは飛ばして, そのエラーを返す
+    ///
+    pub fn load_directory(dir: &Path) -> (Self, Vec<SuzuError>) {
+        let mod_dirs = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.join(MOD_MANIFEST_FILE).is_file())
+                .collect::<Vec<_>>(),
+            Err(_) => return (Self::new(Vec::new()), Vec::new()),
+        };
+
+        let mut mods = Vec::new();
+        let mut errors = Vec::new();
+        for mod_dir in mod_dirs {
+            match LoadedMod::load(&mod_dir) {
+                Ok(loaded) => mods.push(loaded),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        (Self::new(mods), errors)
+    }
+
+    pub fn mods(&self) -> &[LoadedMod] {
+        &self.mods
+    }
+
+    pub fn conflicts(&self) -> &[ModConflict] {
+        &self.conflicts
+    }
+
+    fn report(&mut self, conflict: ModConflict) {
+        eprintln!("{}", conflict);
+        self.conflicts.push(conflict);
+    }
+
+    ///
+    /// MODのディレクトリにあるリソースパックを, 読み込み順にマウントする
+    ///
+    pub fn mount_packs(&self) -> Vec<SuzuError> {
+        let mut errors = Vec::new();
+        for loaded in self.mods.iter() {
+            let pack_path = loaded.directory.join(MOD_PACK_FILE);
+            if !pack_path.is_file() {
+                continue;
+            }
+
+            match ResourcePack::open(&pack_path) {
+                Ok(pack) => resource_pack::mount(pack),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        errors
+    }
+
+    ///
+    /// 本の情報とお客さんの名前を追加する
+    /// 同じ名前の本は後から読み込んだ定義で置き換え, 同じ名前のお客さんは一人として扱う
+    ///
+    pub fn merge_books_and_customers(
+        &mut self,
+        books: &mut Vec<BookInformation>,
+        customers: &mut Vec<String>,
+    ) {
+        let mut book_owners = books
+            .iter()
+            .map(|book| (book.name.clone(), BASE_DEFINITION.to_string()))
+            .collect::<HashMap<_, _>>();
+
+        let mut conflicts = Vec::new();
+        for loaded in self.mods.iter() {
+            for mod_book in loaded.manifest.books_information.iter() {
+                let book = mod_book.to_book_information();
+
+                match book_owners.insert(book.name.clone(), loaded.name().to_string()) {
+                    Some(previous) => {
+                        conflicts.push(ModConflict {
+                            mod_name: loaded.name().to_string(),
+                            previous: previous,
+                            kind: "books_information",
+                            key: book.name.clone(),
+                        });
+                        if let Some(elem) = books.iter_mut().find(|elem| elem.name == book.name) {
+                            *elem = book;
+                        }
+                    }
+                    None => books.push(book),
+                }
+            }
+
+            for name in loaded.manifest.customers_name.iter() {
+                if !customers.contains(name) {
+                    customers.push(name.clone());
+                }
+            }
+        }
+
+        for conflict in conflicts {
+            self.report(conflict);
+        }
+    }
+
+    ///
+    /// シナリオの日付表と汎用シナリオ表を追加する
+    ///
+    pub fn merge_scenario_table(&mut self, table: &mut ScenarioTable) {
+        let mut date_owners = table
+            .scenario_table
+            .keys()
+            .map(|date| (*date, BASE_DEFINITION.to_string()))
+            .collect::<HashMap<_, _>>();
+        let mut general_owners = table
+            .general_scenario
+            .keys()
+            .map(|id| (id.clone(), BASE_DEFINITION.to_string()))
+            .collect::<HashMap<_, _>>();
+
+        let mut conflicts = Vec::new();
+        for loaded in self.mods.iter() {
+            for entry in loaded.manifest.scenario_table.iter() {
+                let date = entry.date;
+                if let Some(previous) = date_owners.insert(date, loaded.name().to_string()) {
+                    conflicts.push(ModConflict {
+                        mod_name: loaded.name().to_string(),
+                        previous: previous,
+                        kind: "scenario-table",
+                        key: format!("{}/{}/{}", date.season, date.month, date.day),
+                    });
+                }
+                table.scenario_table.insert(date, entry.path.clone());
+            }
+
+            for entry in loaded.manifest.general_scenario_table.iter() {
+                let id = entry.id.clone();
+                if let Some(previous) = general_owners.insert(id.clone(), loaded.name().to_string())
+                {
+                    conflicts.push(ModConflict {
+                        mod_name: loaded.name().to_string(),
+                        previous: previous,
+                        kind: "general-scenario-table",
+                        key: format!("{:?}", id),
+                    });
+                }
+                table.general_scenario.insert(id, entry.path.clone());
+            }
+        }
+
+        for conflict in conflicts {
+            self.report(conflict);
+        }
+    }
+
+    ///
+    /// MODが追加する新刊の予定を, 読み込み順に返す
+    ///
+    pub fn new_book_schedules(&self) -> impl Iterator<Item = &DayNewBookEntry> {
+        self.mods
+            .iter()
+            .flat_map(|loaded| loaded.manifest.new_book_schedule.iter())
+    }
+
+    fn event_panels_for<'a>(
+        &'a self,
+        event_map_path: &'a str,
+    ) -> impl Iterator<Item = (&'a LoadedMod, &'a toml::Value)> {
+        self.mods.iter().flat_map(move |loaded| {
+            loaded
+                .manifest
+                .event_panel
+                .iter()
+                .filter(move |panel| {
+                    panel.get("map").and_then(|map| map.as_str()) == Some(event_map_path)
+                })
+                .map(move |panel| (loaded, panel))
+        })
+    }
+
+    pub fn has_event_panels_for(&self, event_map_path: &str) -> bool {
+        self.event_panels_for(event_map_path).next().is_some()
+    }
+
+    ///
+    /// イベントマップのevent-panelの末尾に, MODのイベントを追加する
+    /// 同じ位置のイベントは, 後にあるものが使われる
+    ///
+    pub fn append_event_panels(&self, event_map_path: &str, root: &mut toml::Value) {
+        let panels = self
+            .event_panels_for(event_map_path)
+            .map(|(_, panel)| panel.clone())
+            .collect::<Vec<_>>();
+        if panels.is_empty() {
+            return;
+        }
+
+        if let Some(root) = root.as_table_mut() {
+            let array = root
+                .entry("event-panel".to_string())
+                .or_insert_with(|| toml::Value::Array(Vec::new()));
+            if let Some(array) = array.as_array_mut() {
+                array.extend(panels);
+            }
+        }
+    }
+
+    ///
+    /// イベントマップの同じ位置にイベントを置くMODを記録する
+    /// rootは本体のイベントマップ
+    ///
+    pub fn check_event_panel_conflicts(&mut self, event_map_path: &str, root: &toml::Value) {
+        let mut owners = root
+            .get("event-panel")
+            .and_then(|array| array.as_array())
+            .into_iter()
+            .flat_map(|array| array.iter())
+            .filter_map(panel_position)
+            .map(|position| (position, BASE_DEFINITION.to_string()))
+            .collect::<HashMap<_, _>>();
+
+        let mut conflicts = Vec::new();
+        for (loaded, panel) in self.event_panels_for(event_map_path) {
+            let position = match panel_position(panel) {
+                Some(position) => position,
+                None => continue,
+            };
+
+            if let Some(previous) = owners.insert(position, loaded.name().to_string()) {
+                conflicts.push(ModConflict {
+                    mod_name: loaded.name().to_string(),
+                    previous: previous,
+                    kind: "event-panel",
+                    key: format!("{} ({}, {})", event_map_path, position.0, position.1),
+                });
+            }
+        }
+
+        for conflict in conflicts {
+            self.report(conflict);
+        }
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::{crypt, SuzuError};
+
+pub const PACK_EXTENSION: &str = "suzupack";
+
+const PACK_MAGIC: &[u8; 8] = b"SUZUPACK";
+const PACK_VERSION: u32 = 1;
+const PACK_FLAG_ENCRYPTED: u32 = 1;
+const PACK_HEADER_SIZE: u64 = 24;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PackIndexEntry {
+    path: String,
+    offset: u64,
+    size: u64,
+}
+
+///
+/// # リソースパック
+///
+/// ヘッダ, インデックス(JSON), ファイルの中身を順に並べた一つのファイル
+///
+/// ヘッダは "SUZUPACK", バージョン(u32), フラグ(u32), インデックスの長さ(u64)をリトルエンディアンで並べる
+/// 暗号化されたパックでは, インデックスと各ファイルをcryptモジュールで暗号化して格納する
+///
+pub struct ResourcePack {
+    file_path: PathBuf,
+    encrypted: bool,
+    data_offset: u64,
+    index: HashMap<String, PackIndexEntry>,
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(&bytes[..4]);
+    u32::from_le_bytes(buf)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(buf)
+}
+
+fn encrypt_blob(bytes: &[u8]) -> Vec<u8> {
+    crypt::crypt_str(&base64::encode(bytes)).unwrap()
+}
+
+fn decrypt_blob(path: &str, bytes: &[u8]) -> Result<Vec<u8>, SuzuError> {
+    let encoded = crypt::decrypt_str(&bytes.to_vec()).ok_or_else(|| SuzuError::crypt(path))?;
+    base64::decode(&encoded).map_err(|_| SuzuError::crypt(path))
+}
+
+impl ResourcePack {
+    pub fn open(file_path: &Path) -> Result<Self, SuzuError> {
+        let path_str = file_path.to_string_lossy().to_string();
+        let invalid = |detail: &str| SuzuError::missing_asset(&path_str, detail);
+
+        let mut file = File::open(file_path).map_err(|e| SuzuError::io(&path_str, e))?;
+
+        let mut header = [0; PACK_HEADER_SIZE as usize];
+        file.read_exact(&mut header)
+            .map_err(|e| SuzuError::io(&path_str, e))?;
+
+        if &header[..8] != PACK_MAGIC {
+            return Err(invalid("not a resource pack"));
+        }
+        if read_u32(&header[8..]) != PACK_VERSION {
+            return Err(invalid("unsupported resource pack version"));
+        }
+        let encrypted = read_u32(&header[12..]) & PACK_FLAG_ENCRYPTED != 0;
+        let index_size = read_u64(&header[16..]);
+
+        let file_size = file
+            .metadata()
+            .map_err(|e| SuzuError::io(&path_str, e))?
+            .len();
+        if index_size > file_size - PACK_HEADER_SIZE {
+            return Err(invalid("index size exceeds file"));
+        }
+
+        let mut index_bytes = vec![0; index_size as usize];
+        file.read_exact(&mut index_bytes)
+            .map_err(|e| SuzuError::io(&path_str, e))?;
+        if encrypted {
+            index_bytes = decrypt_blob(&path_str, &index_bytes)?;
+        }
+
+        let entries: Vec<PackIndexEntry> =
+            serde_json::from_slice(&index_bytes).map_err(|e| SuzuError::JsonParse {
+                path: path_str.clone(),
+                source: e,
+            })?;
+
+        Ok(ResourcePack {
+            file_path: file_path.to_path_buf(),
+            encrypted: encrypted,
+            data_offset: PACK_HEADER_SIZE + index_size,
+            index: entries
+                .into_iter()
+                .map(|entry| (entry.path.clone(), entry))
+                .collect(),
+        })
+    }
+
+    ///
+    /// filesの内容でパックを書き出す. パスはresourcesと同じく"/"から始める
+    ///
+    pub fn write(
+        file_path: &Path,
+        files: &[(String, Vec<u8>)],
+        encrypted: bool,
+    ) -> Result<(), SuzuError> {
+        let path_str = file_path.to_string_lossy().to_string();
+
+        let mut index = Vec::new();
+        let mut blobs = Vec::new();
+        let mut offset = 0;
+        for (path, bytes) in files.iter() {
+            let blob = if encrypted {
+                encrypt_blob(bytes)
+            } else {
+                bytes.clone()
+            };
+
+            index.push(PackIndexEntry {
+                path: path.clone(),
+                offset: offset,
+                size: blob.len() as u64,
+            });
+            offset += blob.len() as u64;
+            blobs.push(blob);
+        }
+
+        let mut index_bytes = serde_json::to_vec(&index).unwrap();
+        if encrypted {
+            index_bytes = encrypt_blob(&index_bytes);
+        }
+
+        let flags = if encrypted { PACK_FLAG_ENCRYPTED } else { 0 };
+
+        let mut file = File::create(file_path).map_err(|e| SuzuError::io(&path_str, e))?;
+        let mut write = |bytes: &[u8]| {
+            file.write_all(bytes)
+                .map_err(|e| SuzuError::io(&path_str, e))
+        };
+        write(PACK_MAGIC)?;
+        write(&PACK_VERSION.to_le_bytes())?;
+        write(&flags.to_le_bytes())?;
+        write(&(index_bytes.len() as u64).to_le_bytes())?;
+        write(&index_bytes)?;
+        for blob in blobs.iter() {
+            write(blob)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.index.contains_key(path)
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(|path| path.as_str())
+    }
+
+    ///
+    /// パックに含まれるファイルの位置を返す. 含まれていなければNoneを返す
+    ///
+    pub fn locate(&self, path: &str) -> Option<PackEntry> {
+        let entry = self.index.get(path)?;
+
+        Some(PackEntry {
+            path: path.to_string(),
+            pack_path: self.file_path.clone(),
+            encrypted: self.encrypted,
+            offset: self.data_offset + entry.offset,
+            size: entry.size,
+        })
+    }
+
+    ///
+    /// パックに含まれるファイルを読む. 含まれていなければNoneを返す
+    ///
+    pub fn read(&self, path: &str) -> Option<Result<Vec<u8>, SuzuError>> {
+        self.locate(path).map(|entry| entry.read())
+    }
+}
+
+///
+/// # パックに含まれる一つのファイルの位置
+///
+/// マウントされているパックはスレッドごとに管理されているので, 他のスレッドで読む場合はこれを渡す
+///
+pub struct PackEntry {
+    path: String,
+    pack_path: PathBuf,
+    encrypted: bool,
+    offset: u64,
+    size: u64,
+}
+
+impl PackEntry {
+    pub fn read(&self) -> Result<Vec<u8>, SuzuError> {
+        let pack_path = self.pack_path.to_string_lossy().to_string();
+
+        let mut file = File::open(&self.pack_path).map_err(|e| SuzuError::io(&pack_path, e))?;
+        file.seek(SeekFrom::Start(self.offset))
+            .map_err(|e| SuzuError::io(&pack_path, e))?;
+
+        let mut bytes = vec![0; self.size as usize];
+        file.read_exact(&mut bytes)
+            .map_err(|e| SuzuError::io(&pack_path, e))?;
+
+        if self.encrypted {
+            decrypt_blob(&self.path, &bytes)
+        } else {
+            Ok(bytes)
+        }
+    }
+}
+
+///
+/// 先読みのワーカースレッドからも同じパックを読めるように, プロセス全体で共有する
+///
+static MOUNTED_PACKS: RwLock<Vec<ResourcePack>> = RwLock::new(Vec::new());
+
+///
+/// パックをマウントする. 後からマウントしたパックほど優先される
+///
+pub fn mount(pack: ResourcePack) {
+    MOUNTED_PACKS.write().unwrap().push(pack);
+}
+
+pub fn unmount_all() {
+    MOUNTED_PACKS.write().unwrap().clear();
+}
+
+///
+/// ディレクトリ内のパックを, ファイル名の順にマウントする
+/// 読み込めなかったパックは飛ばして, そのエラーを返す
+///
+pub fn mount_directory(dir: &Path) -> Vec<SuzuError> {
+    let mut pack_paths = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == PACK_EXTENSION))
+            .collect::<Vec<_>>(),
+        Err(_) => return Vec::new(),
+    };
+    pack_paths.sort();
+
+    let mut errors = Vec::new();
+    for pack_path in pack_paths {
+        match ResourcePack::open(&pack_path) {
+            Ok(pack) => mount(pack),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    errors
+}
+
+///
+/// マウントされているパックからファイルを読む
+///
+pub fn read_mounted(path: &str) -> Option<Result<Vec<u8>, SuzuError>> {
+    MOUNTED_PACKS
+        .read()
+        .unwrap()
+        .iter()
+        .rev()
+        .find_map(|pack| pack.read(path))
+}
+
+///
+/// read_mountedと同じ順でパックを探し, ファイルの位置を返す
+///
+pub fn locate_mounted(path: &str) -> Option<PackEntry> {
+    MOUNTED_PACKS
+        .read()
+        .unwrap()
+        .iter()
+        .rev()
+        .find_map(|pack| pack.locate(path))
+}
+
+///
+/// "/game_data.toml"に対するパッチは"/game_data.patch.toml"に置く
+///
+pub fn patch_path(path: &str) -> Option<String> {
+    if path.ends_with(".toml") {
+        Some(format!("{}.patch.toml", path.trim_end_matches(".toml")))
+    } else {
+        None
+    }
+}
+
+///
+/// マウントされているパックのパッチを, マウントした順に返す
+///
+pub fn mounted_patches(path: &str) -> Vec<Result<Vec<u8>, SuzuError>> {
+    let patch_path = match patch_path(path) {
+        Some(patch_path) => patch_path,
+        None => return Vec::new(),
+    };
+
+    MOUNTED_PACKS
+        .read()
+        .unwrap()
+        .iter()
+        .filter_map(|pack| pack.read(&patch_path))
+        .collect()
+}
+
+///
+/// パッチのキーの末尾にこれを付けると, 配列を置き換えずに末尾へ追加する
+///
+pub const PATCH_APPEND_SUFFIX: &str = "+";
+
+///
+/// テーブルの配列で, 同じ要素かを判定するキー. ここにない配列ではidを使う
+///
+const PATCH_ARRAY_KEYS: [(&str, &str); 3] = [
+    ("books_information", "name"),
+    ("scenario-table", "date"),
+    ("general-scenario-table", "type"),
+];
+
+fn patch_array_key(array: &str) -> &'static str {
+    PATCH_ARRAY_KEYS
+        .iter()
+        .find(|(key, _)| *key == array)
+        .map_or("id", |(_, element_key)| *element_key)
+}
+
+fn merge_toml_table(base: &mut toml::value::Table, patch: toml::value::Table) {
+    for (key, value) in patch {
+        if let Some(array) = key.strip_suffix(PATCH_APPEND_SUFFIX) {
+            match value {
+                toml::Value::Array(values) => match base.get_mut(array) {
+                    Some(toml::Value::Array(base_values)) => base_values.extend(values),
+                    _ => {
+                        base.insert(array.to_string(), toml::Value::Array(values));
+                    }
+                },
+                value => {
+                    base.insert(array.to_string(), value);
+                }
+            }
+            continue;
+        }
+
+        match base.get_mut(&key) {
+            Some(base_value) => merge_toml_value(base_value, value, &key),
+            None => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn merge_toml_value(base: &mut toml::Value, patch: toml::Value, key: &str) {
+    match (base, patch) {
+        (toml::Value::Table(base), toml::Value::Table(patch)) => merge_toml_table(base, patch),
+        (toml::Value::Array(base), toml::Value::Array(patch))
+            if patch.iter().all(|value| value.is_table()) && !patch.is_empty() =>
+        {
+            let element_key = patch_array_key(key);
+            for value in patch {
+                let existing = value.get(element_key).and_then(|id| {
+                    base.iter_mut()
+                        .find(|elem| elem.get(element_key) == Some(id))
+                });
+
+                match existing {
+                    Some(elem) => *elem = value,
+                    None => base.push(value),
+                }
+            }
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+///
+/// TOMLのパッチを当てる
+///
+/// テーブルは再帰的に統合し, それ以外の値は置き換える
+///
+/// テーブルの配列では, 同じキーの値を持つ要素を置き換え, それ以外は末尾に追加する
+/// キーはbooks_informationではname, scenario-tableではdate, general-scenario-tableではtype,
+/// それ以外の配列ではidを使う. キーを持たない要素は常に追加される
+///
+/// 文字列などの配列は丸ごと置き換える. "customers_name+" = ["アヤ"]のように
+/// キーの末尾に+を付けると, 置き換えずに末尾へ追加する. テーブルの配列でも同じ
+///
+pub fn merge_toml_patch(base: &mut toml::Value, patch: toml::Value) {
+    match (base, patch) {
+        (toml::Value::Table(base), toml::Value::Table(patch)) => merge_toml_table(base, patch),
+        (base, patch) => *base = patch,
+    }
+}
@@ -0,0 +1,216 @@
+use super::*;
+
+use serde_json::{Map, Value};
+
+///
+/// # セーブデータの外箱
+///
+/// 保存したときのゲームのバージョンと, SavableDataをシリアライズしたJSONを持つ
+///
+#[derive(Serialize, Deserialize)]
+struct SaveEnvelope {
+    version: String,
+    data: Value,
+}
+
+///
+/// # セーブデータの移行処理
+///
+/// targetより古いバージョンで保存されたデータと, バージョン情報を持たない旧形式のデータに対して,
+/// applyが順番に適用される. applyはデータを書き換えた場合にtrueを返す
+///
+pub struct SaveMigration {
+    pub target: &'static str,
+    pub description: &'static str,
+    apply: fn(&mut Map<String, Value>) -> bool,
+}
+
+///
+/// # セーブデータ読み込みの結果
+///
+/// 保存されていたバージョンと, 読み込み時にデータを書き換えた移行処理の説明を持つ
+/// saved_versionは, SaveEnvelopeで包まれる前の旧形式のデータではNoneになる
+///
+#[derive(Debug, Clone)]
+pub struct SaveLoadReport {
+    pub saved_version: Option<Version>,
+    pub applied_migrations: Vec<&'static str>,
+}
+
+impl SaveLoadReport {
+    pub fn migrated(&self) -> bool {
+        !self.applied_migrations.is_empty()
+    }
+}
+
+fn insert_if_missing(map: &mut Map<String, Value>, key: &str, value: Value) -> bool {
+    if map.contains_key(key) {
+        false
+    } else {
+        map.insert(key.to_string(), value);
+        true
+    }
+}
+
+fn migrate_legacy_award_data(map: &mut Map<String, Value>) -> bool {
+    let mut changed = insert_if_missing(map, "award_data", Value::Object(Map::new()));
+
+    if let Some(award_data) = map.get_mut("award_data").and_then(|v| v.as_object_mut()) {
+        for key in &[
+            "borrowing_count",
+            "returning_count",
+            "shelving_count",
+            "customer_count",
+            "returning_check_mistake_count",
+            "shop_work_count",
+            "taking_rest_count",
+            "going_out_count",
+        ] {
+            changed |= insert_if_missing(award_data, key, Value::from(0));
+        }
+    }
+
+    changed
+}
+
+fn migrate_legacy_scenario_status(map: &mut Map<String, Value>) -> bool {
+    let run_tutorial = insert_if_missing(map, "run_tutorial", Value::Bool(true));
+    let scenario_save_data = insert_if_missing(map, "scenario_save_data", Value::Null);
+    run_tutorial || scenario_save_data
+}
+
+///
+/// 選択肢の記録とテキスト変数は, 続く移行処理で追加する
+///
+fn migrate_story_variables(map: &mut Map<String, Value>) -> bool {
+    insert_if_missing(
+        map,
+        "story_variables",
+        serde_json::json!({ "flags": {}, "variables": {} }),
+    )
+}
+
+fn migrate_story_choices_and_texts(map: &mut Map<String, Value>) -> bool {
+    let mut changed = false;
+
+    if let Some(story_variables) = map
+        .get_mut("story_variables")
+        .and_then(|v| v.as_object_mut())
+    {
+        changed |= insert_if_missing(story_variables, "choices", Value::Object(Map::new()));
+        changed |= insert_if_missing(story_variables, "texts", Value::Object(Map::new()));
+    }
+
+    changed
+}
+
+///
+/// 移行処理の一覧
+/// 新しいフィールドをSavableDataに追加したときは, Cargo.tomlのバージョンを上げてから,
+/// ここに移行処理を追加する
+/// targetはそのフィールドを保存するようになったバージョンで, 古い順に並べること
+/// 最後の移行処理のtargetは, 現在のバージョンと一致していなければならない
+///
+pub const SAVE_MIGRATIONS: &[SaveMigration] = &[
+    SaveMigration {
+        target: "1.0.2",
+        description: "実績データの欠落した項目を0で補完",
+        apply: migrate_legacy_award_data,
+    },
+    SaveMigration {
+        target: "1.0.2",
+        description: "チュートリアル実行フラグとシナリオ再開データを追加",
+        apply: migrate_legacy_scenario_status,
+    },
+    SaveMigration {
+        target: "1.0.3",
+        description: "シナリオのフラグと変数を追加",
+        apply: migrate_story_variables,
+    },
+    SaveMigration {
+        target: "1.0.3",
+        description: "選択肢の記録とテキスト変数を追加",
+        apply: migrate_story_choices_and_texts,
+    },
+];
+
+///
+/// SavableDataをバージョン情報付きのJSON文字列に変換する
+///
+pub fn wrap_save_data(data: &SavableData) -> Result<String, serde_json::Error> {
+    let envelope = SaveEnvelope {
+        version: VERSION.to_string(),
+        data: serde_json::to_value(data)?,
+    };
+
+    serde_json::to_string(&envelope)
+}
+
+///
+/// セーブデータのJSON文字列を読み込む
+/// 保存時のバージョンが古い場合は, 移行処理を順番に適用してからSavableDataに変換する
+///
+/// versionを持たないデータは, SaveEnvelopeで包む前の旧形式のSavableDataとして扱う
+/// versionを持つが外箱として読めないデータは, 壊れたセーブデータとしてエラーを返す
+///
+pub fn load_save_data(
+    path: &str,
+    content: &str,
+) -> Result<(SavableData, SaveLoadReport), SuzuError> {
+    let json_error = |e| SuzuError::JsonParse {
+        path: path.to_string(),
+        source: e,
+    };
+
+    let root: Value = serde_json::from_str(content).map_err(json_error)?;
+
+    let (saved_version, mut data) = if root.get("version").is_some() {
+        let envelope = serde_json::from_value::<SaveEnvelope>(root).map_err(json_error)?;
+        let version =
+            Version::try_from_str(&envelope.version).ok_or_else(|| SuzuError::InvalidValue {
+                path: path.to_string(),
+                key: "version".to_string(),
+                value: envelope.version.clone(),
+            })?;
+
+        if version > Version::this() {
+            return Err(SuzuError::InvalidValue {
+                path: path.to_string(),
+                key: "version".to_string(),
+                value: envelope.version,
+            });
+        }
+
+        (Some(version), envelope.data)
+    } else {
+        (None, root)
+    };
+
+    let mut applied_migrations = Vec::new();
+    let map = data.as_object_mut().ok_or_else(|| SuzuError::InvalidType {
+        path: path.to_string(),
+        key: "data".to_string(),
+        expected: "object",
+    })?;
+
+    for migration in SAVE_MIGRATIONS.iter() {
+        let outdated = match saved_version.as_ref() {
+            Some(version) => *version < Version::from_str(migration.target),
+            None => true,
+        };
+
+        if outdated && (migration.apply)(map) {
+            applied_migrations.push(migration.description);
+        }
+    }
+
+    let savable_data = serde_json::from_value(data).map_err(json_error)?;
+
+    Ok((
+        savable_data,
+        SaveLoadReport {
+            saved_version: saved_version,
+            applied_migrations: applied_migrations,
+        },
+    ))
+}
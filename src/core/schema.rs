@@ -0,0 +1,123 @@
+use std::str::FromStr;
+
+use serde::de::{self, DeserializeOwned, Deserializer};
+use serde::Deserialize;
+use serde_path_to_error::Segment;
+
+use super::error::TomlNode;
+use super::SuzuError;
+use crate::try_parse_toml_file;
+
+///
+/// serdeが失敗した位置を, nodeからのキーの位置に変換する
+///
+fn error_key_path(node: &TomlNode, path: &serde_path_to_error::Path) -> String {
+    let mut key_path = node.key_path().to_string();
+
+    for segment in path.iter() {
+        match segment {
+            Segment::Seq { index } => key_path.push_str(&format!("[{}]", index)),
+            Segment::Map { key } => {
+                if !key_path.is_empty() {
+                    key_path.push('.');
+                }
+                key_path.push_str(key);
+            }
+            Segment::Enum { .. } | Segment::Unknown => (),
+        }
+    }
+
+    key_path
+}
+
+///
+/// nodeを型付きの構造体に変換する. 失敗した場合は, 失敗したキーの位置を含むエラーを返す
+///
+/// 設定ファイルの形は構造体の定義だけで決まり, 起動時の検証も同じ定義で行う
+///
+pub fn deserialize_node<T: DeserializeOwned>(node: &TomlNode) -> Result<T, SuzuError> {
+    serde_path_to_error::deserialize(node.value().clone()).map_err(
+        |e: serde_path_to_error::Error<toml::de::Error>| SuzuError::InvalidData {
+            path: node.file().to_string(),
+            key: error_key_path(node, e.path()),
+            detail: e.inner().to_string(),
+        },
+    )
+}
+
+///
+/// ファイル全体を型付きの構造体に変換する
+///
+pub fn deserialize<T: DeserializeOwned>(path: &str, root: toml::Value) -> Result<T, SuzuError> {
+    deserialize_node(&TomlNode::root(path, &root))
+}
+
+///
+/// 配列の要素を一つずつ変換する. 変換できなかった要素の問題は, 全てproblemsに追加する
+///
+pub fn deserialize_each<T: DeserializeOwned>(
+    node: &TomlNode,
+    problems: &mut Vec<SuzuError>,
+) -> Vec<T> {
+    let elems = match node.as_array() {
+        Ok(elems) => elems,
+        Err(e) => {
+            problems.push(e);
+            return Vec::new();
+        }
+    };
+
+    elems
+        .iter()
+        .filter_map(|elem| match deserialize_node(elem) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                problems.push(e);
+                None
+            }
+        })
+        .collect()
+}
+
+///
+/// nodeがTとして読み込めるかを調べ, 読み込めない場合は問題をproblemsに追加する
+///
+pub fn validate<T: DeserializeOwned>(node: &TomlNode, problems: &mut Vec<SuzuError>) {
+    if let Err(e) = deserialize_node::<T>(node) {
+        problems.push(e);
+    }
+}
+
+///
+/// ファイルを読み込み, validateで検証する. 読み込めた場合は, その内容を返す
+///
+pub fn validate_file(
+    ctx: &mut ggez::Context,
+    path: &str,
+    validate: fn(&TomlNode, &mut Vec<SuzuError>),
+    problems: &mut Vec<SuzuError>,
+) -> Option<toml::Value> {
+    match try_parse_toml_file!(ctx, path) {
+        Ok(root) => {
+            validate(&TomlNode::root(path, &root), problems);
+            Some(root)
+        }
+        Err(e) => {
+            problems.push(e);
+            None
+        }
+    }
+}
+
+///
+/// FromStrで変換する文字列を読み込む. #[serde(deserialize_with = "schema::parse_str")]で使う
+///
+pub fn parse_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+{
+    let s = String::deserialize(deserializer)?;
+    let type_name = std::any::type_name::<T>().rsplit("::").next().unwrap_or("");
+    T::from_str(&s).map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&s), &type_name))
+}
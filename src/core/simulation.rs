@@ -0,0 +1,462 @@
+use super::game_system::{DayWorkType, WeekWorkSchedule};
+use super::*;
+
+use crate::object::task_object::tt_main_component::CustomerRequest;
+
+///
+/// # ggez::Contextに依存しない経営データ
+///
+/// 本の情報, 客の名前, 宣伝のコスト表, 曜日ごとの客の出現間隔を保持する
+/// GameResourceが保持し, シミュレーション部分はこれだけを参照する
+///
+pub struct EconomyData {
+    customers_name: Vec<String>,
+    books_information: Vec<BookInformation>,
+    ad_info: AdCostTable,
+    ad_agency_info: AdAgencyCostTable,
+    daily_customer_dist: DailyCustomerDist,
+}
+
+impl EconomyData {
+    pub fn from_raw_config(
+        src_file: &RawConfigFile,
+        daily_customer_dist: DailyCustomerDist,
+    ) -> Self {
+        EconomyData {
+            customers_name: src_file.customers_name.clone(),
+            books_information: src_file.books_information.clone(),
+            ad_info: AdCostTable::from_data(
+                src_file.ad_cost_table.clone(),
+                src_file.ad_gain_table.clone(),
+            ),
+            ad_agency_info: AdAgencyCostTable::from_data(
+                src_file.ad_agency_cost_table.clone(),
+                src_file.ad_agency_gain_table.clone(),
+            ),
+            daily_customer_dist: daily_customer_dist,
+        }
+    }
+
+    ///
+    /// game_data.tomlとdaily_customer_dist.tomlの中身から直接生成する
+    /// ウィンドウを開かずにシミュレーションを行う場合に使う
+    ///
+    pub fn from_toml_str(
+        game_data_toml: &str,
+        daily_customer_dist_toml: &str,
+    ) -> Result<Self, SuzuError> {
+        let src_file = RawConfigFile::from_toml_str("game_data.toml", game_data_toml)?;
+        let root = error::parse_toml_str("daily_customer_dist.toml", daily_customer_dist_toml)?;
+        let daily_customer_dist = DailyCustomerDist::from_toml_node(&error::TomlNode::root(
+            "daily_customer_dist.toml",
+            &root,
+        ))?;
+
+        Ok(Self::from_raw_config(&src_file, daily_customer_dist))
+    }
+
+    pub fn book_random_select(&self) -> &BookInformation {
+        &self
+            .books_information
+            .get(rand::random::<usize>() % self.books_information.len())
+            .unwrap()
+    }
+
+    pub fn search_book_with_title(&self, title: &str) -> Option<&BookInformation> {
+        self.books_information
+            .iter()
+            .find(|book_info| book_info.name == title)
+    }
+
+    pub fn iter_available_books(&self) -> std::slice::Iter<BookInformation> {
+        self.books_information.iter()
+    }
+
+    pub fn customer_random_select(&self) -> &str {
+        &self
+            .customers_name
+            .get(rand::random::<usize>() % self.customers_name.len())
+            .unwrap()
+    }
+
+    pub fn get_default_ad_cost(&self, ty: SuzunaAdType) -> u32 {
+        self.ad_info.get_cost(ty)
+    }
+
+    pub fn get_default_ad_reputation_gain(&self, ty: SuzunaAdType) -> u32 {
+        self.ad_info.get_reputation_gain(ty)
+    }
+
+    pub fn get_default_ad_agency_cost(&self, ty: &SuzunaAdAgencyType) -> u32 {
+        self.ad_agency_info.get_cost(ty)
+    }
+
+    pub fn get_default_ad_agency_money_gain(&self, ty: &SuzunaAdAgencyType) -> u32 {
+        self.ad_agency_info.get_money_gain(ty)
+    }
+
+    pub fn get_todays_customer_dist(&self, date: &GensoDate) -> Clock {
+        self.daily_customer_dist.get_customer_dist_default(date)
+    }
+}
+
+///
+/// 一日の予定を実行した結果, 所持金, 意欲, 評判がどれだけ変化するか
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DayScheduleEffect {
+    pub money_diff: i32,
+    pub hp_diff: f32,
+    pub reputation_diff: f32,
+}
+
+///
+/// 予定が実行できなかった理由
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleRejection {
+    NotScheduled,
+    NoEnoughHp,
+    NoEnoughMoney,
+}
+
+///
+/// 一日分のシミュレーションの記録
+///
+#[derive(Debug, Clone)]
+pub struct DaySimulationLog {
+    pub date: GensoDate,
+    pub work_type: Option<DayWorkType>,
+    pub result: Result<DayScheduleEffect, ScheduleRejection>,
+}
+
+pub const SHOP_WORK_HP_COST: f32 = 20.0;
+pub const GOING_OUT_HP_GAIN: f32 = 40.0;
+pub const TAKING_REST_HP_GAIN: f32 = 20.0;
+pub const KOSUZU_HP_MAX: f32 = 100.0;
+
+const ALL_AD_TYPES: [SuzunaAdType; 6] = [
+    SuzunaAdType::AdPaper,
+    SuzunaAdType::Chindon,
+    SuzunaAdType::ShopNobori,
+    SuzunaAdType::TownNobori,
+    SuzunaAdType::NewsPaper,
+    SuzunaAdType::BunBunMaruPaper,
+];
+
+const ALL_AD_AGENCY_TYPES: [SuzunaAdAgencyType; 6] = [
+    SuzunaAdAgencyType::HakureiJinja,
+    SuzunaAdAgencyType::KirisameMahoten,
+    SuzunaAdAgencyType::GettoDango,
+    SuzunaAdAgencyType::Kusuriya,
+    SuzunaAdAgencyType::Hieda,
+    SuzunaAdAgencyType::YamaJinja,
+];
+
+pub fn current_total_ad_cost(data: &EconomyData, save_data: &SavableData) -> i32 {
+    ALL_AD_TYPES
+        .iter()
+        .filter(|ad_type| save_data.get_ad_status(**ad_type))
+        .map(|ad_type| data.get_default_ad_cost(*ad_type) as i32)
+        .sum()
+}
+
+pub fn current_total_ad_reputation_gain(data: &EconomyData, save_data: &SavableData) -> i32 {
+    ALL_AD_TYPES
+        .iter()
+        .filter(|ad_type| save_data.get_ad_status(**ad_type))
+        .map(|ad_type| data.get_default_ad_reputation_gain(*ad_type) as i32)
+        .sum()
+}
+
+pub fn current_total_ad_agency_money_gain(data: &EconomyData, save_data: &SavableData) -> i32 {
+    ALL_AD_AGENCY_TYPES
+        .iter()
+        .filter(|agency_type| save_data.get_ad_agency_status(agency_type))
+        .map(|agency_type| data.get_default_ad_agency_money_gain(agency_type) as i32)
+        .sum()
+}
+
+///
+/// # 鈴奈庵の経営シミュレーション
+///
+/// EconomyDataとSavableDataだけで日付の進行, 予定の実行, 宣伝費の支払い,
+/// 受付業務の記録を行う. シーンはこの構造体を通してセーブデータを更新する
+///
+pub struct SuzunaSimulator<'a> {
+    data: &'a EconomyData,
+    save_data: &'a mut SavableData,
+}
+
+impl<'a> SuzunaSimulator<'a> {
+    pub fn new(data: &'a EconomyData, save_data: &'a mut SavableData) -> Self {
+        SuzunaSimulator {
+            data: data,
+            save_data: save_data,
+        }
+    }
+
+    pub fn ref_save_data(&self) -> &SavableData {
+        self.save_data
+    }
+
+    pub fn ref_economy_data(&self) -> &EconomyData {
+        self.data
+    }
+
+    pub fn go_next_day(&mut self) {
+        self.save_data.date.add_day(1);
+    }
+
+    pub fn pay_ad_cost(&mut self) -> i32 {
+        self.save_data.pay_ad_cost(self.data)
+    }
+
+    pub fn current_total_ad_cost(&self) -> i32 {
+        current_total_ad_cost(self.data, self.save_data)
+    }
+
+    pub fn current_total_ad_reputation_gain(&self) -> i32 {
+        current_total_ad_reputation_gain(self.data, self.save_data)
+    }
+
+    pub fn current_total_ad_agency_money_gain(&self) -> i32 {
+        current_total_ad_agency_money_gain(self.data, self.save_data)
+    }
+
+    fn hp_gain_with_limit(&self, gain: f32) -> f32 {
+        util::min(
+            gain,
+            KOSUZU_HP_MAX - self.save_data.suzunaan_status.kosuzu_hp,
+        )
+    }
+
+    ///
+    /// 評判が0より下がらないように変化量を制限する
+    ///
+    fn reputation_diff_with_limit(&self, diff: f32) -> f32 {
+        let current = self.save_data.suzunaan_status.get_current_reputation();
+        if current + diff < 0.0 {
+            -current
+        } else {
+            diff
+        }
+    }
+
+    ///
+    /// 予定を実行した場合の変化量を計算する. セーブデータは変更しない
+    ///
+    pub fn plan_schedule(
+        &self,
+        work_type: DayWorkType,
+    ) -> Result<DayScheduleEffect, ScheduleRejection> {
+        let ad_money_diff =
+            self.current_total_ad_agency_money_gain() - self.current_total_ad_cost();
+        let ad_reputation_gain = self.current_total_ad_reputation_gain() as f32;
+
+        match work_type {
+            DayWorkType::ShopWork => {
+                if self.save_data.suzunaan_status.kosuzu_hp - SHOP_WORK_HP_COST < 0.0 {
+                    return Err(ScheduleRejection::NoEnoughHp);
+                }
+
+                Ok(DayScheduleEffect {
+                    money_diff: ad_money_diff,
+                    hp_diff: -SHOP_WORK_HP_COST,
+                    reputation_diff: ad_reputation_gain,
+                })
+            }
+            DayWorkType::GoingOut(_) => {
+                let money_diff = ad_money_diff - game_system::GOING_OUT_MONEY_COST as i32;
+                if self.save_data.task_result.total_money + money_diff < 0 {
+                    return Err(ScheduleRejection::NoEnoughMoney);
+                }
+
+                Ok(DayScheduleEffect {
+                    money_diff: money_diff,
+                    hp_diff: self.hp_gain_with_limit(GOING_OUT_HP_GAIN),
+                    reputation_diff: ad_reputation_gain,
+                })
+            }
+            DayWorkType::TakingRest => Ok(DayScheduleEffect {
+                money_diff: ad_money_diff,
+                hp_diff: self.hp_gain_with_limit(TAKING_REST_HP_GAIN),
+                reputation_diff: self.reputation_diff_with_limit(
+                    ad_reputation_gain - game_system::TAKING_REST_REPUTATION_COST as f32,
+                ),
+            }),
+        }
+    }
+
+    ///
+    /// 予定が実行できずに休んだ場合の評判の変化量
+    ///
+    pub fn plan_forced_rest(&self) -> f32 {
+        let current = self.save_data.suzunaan_status.get_current_reputation();
+        -util::min(current, game_system::TAKING_REST_REPUTATION_COST as f32)
+    }
+
+    pub fn apply_money_diff(&mut self, diff: i32) {
+        self.save_data.task_result.add_total_money(diff);
+    }
+
+    pub fn apply_hp_diff(&mut self, diff: f32) {
+        let next = self.save_data.suzunaan_status.kosuzu_hp + diff;
+        self.save_data.suzunaan_status.kosuzu_hp = util::min(next, KOSUZU_HP_MAX);
+    }
+
+    pub fn apply_reputation_diff(&mut self, diff: f32) {
+        self.save_data.suzunaan_status.add_reputation(diff);
+    }
+
+    pub fn apply_schedule_effect(&mut self, effect: &DayScheduleEffect) {
+        self.apply_money_diff(effect.money_diff);
+        self.apply_hp_diff(effect.hp_diff);
+        self.apply_reputation_diff(effect.reputation_diff);
+    }
+
+    ///
+    /// 予定の実行を記録する. 店番以外はここで日付が進む
+    /// 店番の日付は受付業務の結果を精算した後に進める
+    ///
+    pub fn finish_schedule(&mut self, work_type: DayWorkType) {
+        match work_type {
+            DayWorkType::ShopWork => {
+                self.save_data.award_data.shop_work_count += 1;
+            }
+            DayWorkType::GoingOut(_) => {
+                self.save_data.award_data.going_out_count += 1;
+                self.go_next_day();
+            }
+            DayWorkType::TakingRest => {
+                self.save_data.award_data.taking_rest_count += 1;
+                self.go_next_day();
+            }
+        }
+    }
+
+    ///
+    /// 客の依頼の受付を開始したときの記録
+    ///
+    pub fn begin_customer_request(&mut self, request: &CustomerRequest) {
+        match request {
+            CustomerRequest::Borrowing(_) => self.save_data.award_data.borrowing_count += 1,
+            CustomerRequest::Returning(_) => self.save_data.award_data.returning_count += 1,
+        }
+    }
+
+    ///
+    /// 客の依頼を処理し終わったときの記録
+    ///
+    pub fn complete_customer_request(&mut self, request: CustomerRequest) {
+        let task_result = &mut self.save_data.task_result;
+        match request {
+            CustomerRequest::Borrowing(request_information) => {
+                // 貸出本を記録
+                task_result.done_works += 1;
+                task_result.total_money += request_information.calc_fee();
+                task_result
+                    .borrowing_books
+                    .extend(request_information.borrowing);
+            }
+            CustomerRequest::Returning(request_information) => {
+                // 返却本を記録
+                task_result.done_works += 1;
+                task_result
+                    .not_shelved_books
+                    .extend(request_information.returning);
+            }
+        }
+    }
+
+    ///
+    /// 店番の結果を精算する. 評判の変化量を返す
+    ///
+    pub fn settle_shop_work_result(&mut self, report: &ResultReport, done_works: u32) -> f32 {
+        self.save_data
+            .award_data
+            .add_customer_count(done_works as u16);
+
+        let eval = report.generate_eval_result();
+        self.apply_reputation_diff(eval);
+
+        eval
+    }
+
+    ///
+    /// 今日の予定を一日分まとめて実行する
+    /// 店番の場合はdesk_workで受付業務の内容と結果を受け取る
+    ///
+    pub fn simulate_day<F>(&mut self, desk_work: F) -> DaySimulationLog
+    where
+        F: FnOnce(&EconomyData, &mut SavableData) -> (Vec<CustomerRequest>, ResultReport),
+    {
+        let date = self.save_data.date.clone();
+        let work_type = self.save_data.get_todays_schedule();
+
+        let work_type = match work_type {
+            Some(work_type) => work_type,
+            None => {
+                return DaySimulationLog {
+                    date: date,
+                    work_type: None,
+                    result: Err(ScheduleRejection::NotScheduled),
+                }
+            }
+        };
+
+        let result = self.plan_schedule(work_type);
+
+        match result {
+            Ok(effect) => {
+                self.apply_schedule_effect(&effect);
+                self.finish_schedule(work_type);
+
+                if work_type == DayWorkType::ShopWork {
+                    let before_done_works = self.save_data.task_result.done_works;
+                    let (requests, report) = desk_work(self.data, &mut *self.save_data);
+
+                    for request in requests {
+                        self.begin_customer_request(&request);
+                        self.complete_customer_request(request);
+                    }
+
+                    let done_works = self.save_data.task_result.done_works - before_done_works;
+                    self.settle_shop_work_result(&report, done_works);
+                    self.go_next_day();
+                }
+            }
+            Err(_) => {
+                let diff = self.plan_forced_rest();
+                self.apply_reputation_diff(diff);
+                self.go_next_day();
+            }
+        }
+
+        DaySimulationLog {
+            date: date,
+            work_type: Some(work_type),
+            result: result,
+        }
+    }
+
+    ///
+    /// 一週間分の予定を設定し, 七日分シミュレーションを行う
+    ///
+    pub fn simulate_week<F>(
+        &mut self,
+        schedule: [DayWorkType; 7],
+        mut desk_work: F,
+    ) -> Vec<DaySimulationLog>
+    where
+        F: FnMut(&EconomyData, &mut SavableData) -> (Vec<CustomerRequest>, ResultReport),
+    {
+        let first_day = self.save_data.date.clone();
+        self.save_data
+            .update_week_schedule(WeekWorkSchedule::new(first_day, schedule));
+
+        (0..7)
+            .map(|_| self.simulate_day(|data, save_data| desk_work(data, save_data)))
+            .collect()
+    }
+}
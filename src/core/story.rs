@@ -0,0 +1,490 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+///
+/// # シナリオから読み書きできる値
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StoryValue {
+    Bool(bool),
+    Int(i64),
+}
+
+impl StoryValue {
+    pub fn as_bool(&self) -> bool {
+        match self {
+            StoryValue::Bool(b) => *b,
+            StoryValue::Int(n) => *n != 0,
+        }
+    }
+
+    pub fn as_int(&self) -> i64 {
+        match self {
+            StoryValue::Bool(b) => *b as i64,
+            StoryValue::Int(n) => *n,
+        }
+    }
+}
+
+///
+/// # プレイヤーが選んだ選択肢
+///
+/// indexは0から始まる選択肢の番号, patternは選んだ選択肢の文字列
+///
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChoiceRecord {
+    pub index: usize,
+    pub pattern: String,
+}
+
+///
+/// # シナリオで使用するフラグと変数
+///
+/// SavableDataに保存され, シナリオのset要素で書き換え, switch要素の条件式で参照する
+/// 選択肢の結果もシナリオファイルとScenarioElementIDごとにここへ記録される
+/// textsはシナリオのテキスト中の{name}で埋め込む文字列
+///
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StoryVariables {
+    flags: HashMap<String, bool>,
+    variables: HashMap<String, i64>,
+    choices: BTreeMap<String, BTreeMap<i32, ChoiceRecord>>,
+    texts: HashMap<String, String>,
+}
+
+///
+/// "/scenario/day_7_25.toml" -> "day_7_25"
+///
+fn scenario_file_stem(file_path: &str) -> &str {
+    Path::new(file_path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(file_path)
+}
+
+impl StoryVariables {
+    pub fn new() -> Self {
+        StoryVariables::default()
+    }
+
+    pub fn set_flag(&mut self, name: &str, value: bool) {
+        self.flags.insert(name.to_string(), value);
+    }
+
+    pub fn get_flag(&self, name: &str) -> bool {
+        *self.flags.get(name).unwrap_or(&false)
+    }
+
+    pub fn set_variable(&mut self, name: &str, value: i64) {
+        self.variables.insert(name.to_string(), value);
+    }
+
+    pub fn add_variable(&mut self, name: &str, diff: i64) {
+        *self.variables.entry(name.to_string()).or_insert(0) += diff;
+    }
+
+    pub fn get_variable(&self, name: &str) -> Option<i64> {
+        self.variables.get(name).copied()
+    }
+
+    pub fn set_text(&mut self, name: &str, value: &str) {
+        self.texts.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn get_text(&self, name: &str) -> Option<&str> {
+        self.texts.get(name).map(|s| s.as_str())
+    }
+
+    pub fn record_choice(
+        &mut self,
+        scenario_file: &str,
+        element_id: i32,
+        index: usize,
+        pattern: &str,
+    ) {
+        self.choices
+            .entry(scenario_file.to_string())
+            .or_insert_with(BTreeMap::new)
+            .insert(
+                element_id,
+                ChoiceRecord {
+                    index: index,
+                    pattern: pattern.to_string(),
+                },
+            );
+    }
+
+    ///
+    /// 選択肢の結果を探す. scenario_fileはパスでもファイル名(拡張子なし)でもよい
+    ///
+    pub fn get_choice(&self, scenario_file: &str, element_id: i32) -> Option<&ChoiceRecord> {
+        if let Some(records) = self.choices.get(scenario_file) {
+            return records.get(&element_id);
+        }
+
+        self.choices
+            .iter()
+            .find(|(path, _)| scenario_file_stem(path) == scenario_file)
+            .and_then(|(_, records)| records.get(&element_id))
+    }
+
+    ///
+    /// 記録されたすべての選択肢を, (シナリオファイル, ScenarioElementID, 結果)の形で返す
+    ///
+    pub fn iter_choices(&self) -> impl Iterator<Item = (&str, i32, &ChoiceRecord)> {
+        self.choices.iter().flat_map(|(path, records)| {
+            records
+                .iter()
+                .map(move |(id, record)| (path.as_str(), *id, record))
+        })
+    }
+
+    ///
+    /// "choice.day_7_25.5" -> day_7_25.tomlのid 5の選択肢で選んだ番号(1から始まる)
+    /// まだ選んでいない場合は0になる
+    ///
+    fn lookup_choice(&self, name: &str) -> Option<StoryValue> {
+        let rest = name.strip_prefix("choice.")?;
+        let (file, id) = rest.split_at(rest.rfind('.')?);
+        let id = id[1..].parse::<i32>().ok()?;
+
+        Some(StoryValue::Int(
+            self.get_choice(file, id)
+                .map(|record| record.index as i64 + 1)
+                .unwrap_or(0),
+        ))
+    }
+
+    ///
+    /// 名前から値を探す. 変数を優先し, 見つからなければフラグを探す
+    /// "choice."で始まる名前は選択肢の結果を表す
+    ///
+    pub fn lookup(&self, name: &str) -> Option<StoryValue> {
+        if let Some(value) = self.lookup_choice(name) {
+            Some(value)
+        } else if let Some(n) = self.variables.get(name) {
+            Some(StoryValue::Int(*n))
+        } else if let Some(b) = self.flags.get(name) {
+            Some(StoryValue::Bool(*b))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StoryBinaryOp {
+    And,
+    Or,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+}
+
+///
+/// # switch要素の条件式
+///
+/// `reputation >= 60 && met_akyu` のような式を表す
+/// 未定義の名前はfalse(0)として評価される
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum StoryExpr {
+    Bool(bool),
+    Int(i64),
+    Name(String),
+    Not(Box<StoryExpr>),
+    Binary(StoryBinaryOp, Box<StoryExpr>, Box<StoryExpr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoryExprError {
+    pub position: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for StoryExprError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.position + 1)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Int(i64),
+    Name(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+fn tokenize(src: &str) -> Result<Vec<(usize, Token)>, StoryExprError> {
+    let chars = src.chars().collect::<Vec<char>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let begin = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let n = chars[begin..i]
+                .iter()
+                .collect::<String>()
+                .parse::<i64>()
+                .map_err(|_| StoryExprError {
+                    position: begin,
+                    message: "number is too large",
+                })?;
+            tokens.push((begin, Token::Int(n)));
+        } else if c.is_alphabetic() || c == '_' {
+            let begin = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            tokens.push((begin, Token::Name(chars[begin..i].iter().collect())));
+        } else if c == '(' {
+            tokens.push((i, Token::LParen));
+            i += 1;
+        } else if c == ')' {
+            tokens.push((i, Token::RParen));
+            i += 1;
+        } else {
+            let two = chars[i..].iter().take(2).collect::<String>();
+            let op = ["&&", "||", "==", "!=", "<=", ">="]
+                .iter()
+                .find(|op| **op == two)
+                .copied()
+                .or_else(|| {
+                    ["<", ">", "!", "+", "-"]
+                        .iter()
+                        .find(|op| op.starts_with(c))
+                        .copied()
+                });
+
+            match op {
+                Some(op) => {
+                    tokens.push((i, Token::Op(op)));
+                    i += op.len();
+                }
+                None => {
+                    return Err(StoryExprError {
+                        position: i,
+                        message: "unexpected character",
+                    })
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.index)
+            .map(|(position, _)| *position)
+            .unwrap_or(self.end)
+    }
+
+    fn error(&self, message: &'static str) -> StoryExprError {
+        StoryExprError {
+            position: self.position(),
+            message: message,
+        }
+    }
+
+    fn eat_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.index += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<StoryExpr, StoryExprError> {
+        let mut lhs = self.parse_and()?;
+        while self.eat_op(&["||"]).is_some() {
+            let rhs = self.parse_and()?;
+            lhs = StoryExpr::Binary(StoryBinaryOp::Or, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<StoryExpr, StoryExprError> {
+        let mut lhs = self.parse_comparison()?;
+        while self.eat_op(&["&&"]).is_some() {
+            let rhs = self.parse_comparison()?;
+            lhs = StoryExpr::Binary(StoryBinaryOp::And, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_comparison(&mut self) -> Result<StoryExpr, StoryExprError> {
+        let lhs = self.parse_sum()?;
+        let op = match self.eat_op(&["==", "!=", "<=", ">=", "<", ">"]) {
+            Some("==") => StoryBinaryOp::Eq,
+            Some("!=") => StoryBinaryOp::Ne,
+            Some("<=") => StoryBinaryOp::Le,
+            Some(">=") => StoryBinaryOp::Ge,
+            Some("<") => StoryBinaryOp::Lt,
+            Some(">") => StoryBinaryOp::Gt,
+            _ => return Ok(lhs),
+        };
+        let rhs = self.parse_sum()?;
+        Ok(StoryExpr::Binary(op, Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn parse_sum(&mut self) -> Result<StoryExpr, StoryExprError> {
+        let mut lhs = self.parse_unary()?;
+        while let Some(op) = self.eat_op(&["+", "-"]) {
+            let rhs = self.parse_unary()?;
+            let op = if op == "+" {
+                StoryBinaryOp::Add
+            } else {
+                StoryBinaryOp::Sub
+            };
+            lhs = StoryExpr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<StoryExpr, StoryExprError> {
+        if self.eat_op(&["!"]).is_some() {
+            return Ok(StoryExpr::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat_op(&["-"]).is_some() {
+            let operand = self.parse_unary()?;
+            return Ok(StoryExpr::Binary(
+                StoryBinaryOp::Sub,
+                Box::new(StoryExpr::Int(0)),
+                Box::new(operand),
+            ));
+        }
+
+        let token = match self.peek() {
+            Some(token) => token.clone(),
+            None => return Err(self.error("unexpected end of expression")),
+        };
+
+        match token {
+            Token::Int(n) => {
+                self.index += 1;
+                Ok(StoryExpr::Int(n))
+            }
+            Token::Name(name) => {
+                self.index += 1;
+                Ok(match name.as_str() {
+                    "true" => StoryExpr::Bool(true),
+                    "false" => StoryExpr::Bool(false),
+                    _ => StoryExpr::Name(name),
+                })
+            }
+            Token::LParen => {
+                self.index += 1;
+                let expr = self.parse_or()?;
+                match self.peek() {
+                    Some(Token::RParen) => {
+                        self.index += 1;
+                        Ok(expr)
+                    }
+                    _ => Err(self.error("expected ')'")),
+                }
+            }
+            _ => Err(self.error("expected value")),
+        }
+    }
+}
+
+impl StoryExpr {
+    pub fn parse(src: &str) -> Result<StoryExpr, StoryExprError> {
+        let mut parser = Parser {
+            tokens: tokenize(src)?,
+            index: 0,
+            end: src.chars().count(),
+        };
+
+        let expr = parser.parse_or()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("unexpected token"));
+        }
+
+        Ok(expr)
+    }
+
+    ///
+    /// 式を評価する. lookupは名前から値を探す関数で, 見つからない名前はfalseとして扱う
+    ///
+    pub fn eval<F>(&self, lookup: &F) -> StoryValue
+    where
+        F: Fn(&str) -> Option<StoryValue>,
+    {
+        match self {
+            StoryExpr::Bool(b) => StoryValue::Bool(*b),
+            StoryExpr::Int(n) => StoryValue::Int(*n),
+            StoryExpr::Name(name) => lookup(name).unwrap_or(StoryValue::Bool(false)),
+            StoryExpr::Not(expr) => StoryValue::Bool(!expr.eval(lookup).as_bool()),
+            StoryExpr::Binary(op, lhs, rhs) => {
+                let lhs = lhs.eval(lookup);
+                match op {
+                    StoryBinaryOp::And => {
+                        StoryValue::Bool(lhs.as_bool() && rhs.eval(lookup).as_bool())
+                    }
+                    StoryBinaryOp::Or => {
+                        StoryValue::Bool(lhs.as_bool() || rhs.eval(lookup).as_bool())
+                    }
+                    _ => {
+                        let lhs = lhs.as_int();
+                        let rhs = rhs.eval(lookup).as_int();
+                        match op {
+                            StoryBinaryOp::Eq => StoryValue::Bool(lhs == rhs),
+                            StoryBinaryOp::Ne => StoryValue::Bool(lhs != rhs),
+                            StoryBinaryOp::Lt => StoryValue::Bool(lhs < rhs),
+                            StoryBinaryOp::Le => StoryValue::Bool(lhs <= rhs),
+                            StoryBinaryOp::Gt => StoryValue::Bool(lhs > rhs),
+                            StoryBinaryOp::Ge => StoryValue::Bool(lhs >= rhs),
+                            StoryBinaryOp::Add => StoryValue::Int(lhs.saturating_add(rhs)),
+                            StoryBinaryOp::Sub => StoryValue::Int(lhs.saturating_sub(rhs)),
+                            StoryBinaryOp::And | StoryBinaryOp::Or => unreachable!(),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn eval_bool<F>(&self, lookup: &F) -> bool
+    where
+        F: Fn(&str) -> Option<StoryValue>,
+    {
+        self.eval(lookup).as_bool()
+    }
+}
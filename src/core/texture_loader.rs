@@ -0,0 +1,215 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use super::resource_pack::PackEntry;
+use super::{SuzuError, TextureID};
+
+///
+/// ワーカースレッドでデコードされたテクスチャ
+///
+/// GPUへの転送はggez::Contextが必要なので, メインスレッドで行う
+///
+pub struct DecodedTexture {
+    pub id: TextureID,
+    pub path: String,
+    pub result: Result<(u16, u16, Vec<u8>), String>,
+}
+
+impl DecodedTexture {
+    pub fn upload(&self, ctx: &mut ggez::Context) -> Result<ggez::graphics::Image, SuzuError> {
+        upload(ctx, &self.path, &self.result)
+    }
+}
+
+fn upload(
+    ctx: &mut ggez::Context,
+    path: &str,
+    decoded: &Result<(u16, u16, Vec<u8>), String>,
+) -> Result<ggez::graphics::Image, SuzuError> {
+    match decoded {
+        Ok((width, height, rgba)) => ggez::graphics::Image::from_rgba8(ctx, *width, *height, rgba)
+            .map_err(|e| SuzuError::missing_asset(path, e)),
+        Err(e) => Err(SuzuError::missing_asset(path, e)),
+    }
+}
+
+fn decode(bytes: &[u8]) -> Result<(u16, u16, Vec<u8>), String> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| e.to_string())?
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+    if width > u16::MAX as u32 || height > u16::MAX as u32 {
+        return Err(format!("texture is too large ({}x{})", width, height));
+    }
+
+    Ok((width as u16, height as u16, image.into_raw()))
+}
+
+///
+/// テクスチャを読み込む
+/// パックに含まれているテクスチャは, パックの内容をデコードして使う
+///
+pub fn load_image(ctx: &mut ggez::Context, path: &str) -> Result<ggez::graphics::Image, SuzuError> {
+    match super::resource_pack::read_mounted(path) {
+        Some(bytes) => {
+            let decoded = bytes?;
+            upload(ctx, path, &decode(&decoded))
+        }
+        None => {
+            ggez::graphics::Image::new(ctx, path).map_err(|e| SuzuError::missing_asset(path, e))
+        }
+    }
+}
+
+///
+/// # 先読みするテクスチャの場所
+///
+/// メインスレッドでは場所を解決するだけで, 読み込みはワーカースレッドで行う
+///
+enum TextureSource {
+    Pack(PackEntry),
+    Embedded(&'static [u8]),
+    File(PathBuf),
+    ///
+    /// リソースディレクトリにないもの. ggezのファイルシステムでしか読めないので, メインスレッドで読み込んでおく
+    ///
+    Loaded(Result<Vec<u8>, String>),
+}
+
+impl TextureSource {
+    ///
+    /// try_read_from_resourcesと同じ順で探す
+    ///
+    fn resolve(ctx: &mut ggez::Context, path: &str) -> Self {
+        if let Some(entry) = super::resource_pack::locate_mounted(path) {
+            return TextureSource::Pack(entry);
+        }
+
+        if let Some(content) = super::embedded::embedded_resource(path) {
+            return TextureSource::Embedded(content.as_bytes());
+        }
+
+        match super::util::resource_file_path(path) {
+            Some(file_path) => TextureSource::File(file_path),
+            None => TextureSource::Loaded(
+                super::util::try_read_from_resources(ctx, path).map_err(|e| e.to_string()),
+            ),
+        }
+    }
+
+    fn read(self) -> Result<Vec<u8>, String> {
+        match self {
+            TextureSource::Pack(entry) => entry.read().map_err(|e| e.to_string()),
+            TextureSource::Embedded(bytes) => Ok(bytes.to_vec()),
+            TextureSource::File(file_path) => std::fs::read(&file_path).map_err(|e| e.to_string()),
+            TextureSource::Loaded(bytes) => bytes,
+        }
+    }
+}
+
+///
+/// # テクスチャのグループをワーカースレッドで先読みする
+///
+/// ファイルの読み込みとデコードはワーカースレッドで行う
+///
+pub struct TexturePreloader {
+    group: String,
+    receiver: mpsc::Receiver<DecodedTexture>,
+    total: usize,
+    received: usize,
+}
+
+impl TexturePreloader {
+    pub fn spawn(ctx: &mut ggez::Context, group: &str, sources: Vec<(TextureID, String)>) -> Self {
+        let files = sources
+            .into_iter()
+            .map(|(id, path)| {
+                let source = TextureSource::resolve(ctx, &path);
+                (id, path, source)
+            })
+            .collect::<Vec<_>>();
+
+        let total = files.len();
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for (id, path, source) in files {
+                let decoded = DecodedTexture {
+                    id: id,
+                    path: path,
+                    result: source.read().and_then(|bytes| decode(&bytes)),
+                };
+
+                // 受け取る側が破棄された場合は, 残りを読まずに終了する
+                if sender.send(decoded).is_err() {
+                    break;
+                }
+            }
+        });
+
+        TexturePreloader {
+            group: group.to_string(),
+            receiver: receiver,
+            total: total,
+            received: 0,
+        }
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    ///
+    /// デコードが終わったテクスチャを取り出す. 待たずに返る
+    ///
+    pub fn poll(&mut self) -> Vec<DecodedTexture> {
+        let mut decoded = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(texture) => decoded.push(texture),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    // ワーカースレッドが途中で終了した場合, 残りは必要になったときに読み込む
+                    self.received = self.total;
+                    return decoded;
+                }
+            }
+        }
+
+        self.received += decoded.len();
+        decoded
+    }
+
+    ///
+    /// 次のテクスチャのデコードが終わるまで待って取り出す
+    /// 全て取り出した後と, ワーカースレッドが途中で終了した場合はNoneを返す
+    ///
+    pub fn recv(&mut self) -> Option<DecodedTexture> {
+        if self.is_finished() {
+            return None;
+        }
+
+        match self.receiver.recv() {
+            Ok(texture) => {
+                self.received += 1;
+                Some(texture)
+            }
+            Err(_) => {
+                self.received = self.total;
+                None
+            }
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.received >= self.total
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn received(&self) -> usize {
+        self.received
+    }
+}
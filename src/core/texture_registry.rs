@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+
+use serde::{Deserialize, Deserializer};
+
+use super::schema;
+
+///
+/// # テクスチャのハンドル
+///
+/// game_data.tomlのtexturesで宣言された名前ごとに, 読み込み時に割り当てられる
+/// プログラムから直接参照するテクスチャは, builtin_texture_ids!で定数として宣言する
+///
+#[derive(Clone, Copy, Eq, PartialEq, Hash)]
+pub struct TextureID(u32);
+
+macro_rules! builtin_texture_ids {
+    ( $( $name:ident ),* $(,)? ) => {
+        #[allow(dead_code)]
+        enum BuiltinTexture {
+            $( $name, )*
+        }
+
+        #[allow(non_upper_case_globals)]
+        impl TextureID {
+            $( pub const $name: TextureID = TextureID(BuiltinTexture::$name as u32); )*
+        }
+
+        const BUILTIN_TEXTURE_NAMES: &[&str] = &[ $( stringify!($name), )* ];
+    };
+}
+
+builtin_texture_ids! {
+    Ghost1,
+    LotusPink,
+    LotusBlue,
+    LotusYellow,
+    TextBackground,
+    Paper1,
+    Paper2,
+    LargeBook1,
+    LargeBook2,
+    LargeBook3,
+    MiddleBook1,
+    MiddleBook2,
+    MiddleBook3,
+    Wood1,
+    WafuTexture1,
+    WafuTexture2,
+    Chobo1,
+    ChoicePanel1,
+    ChoicePanel2,
+    ChoicePanel3,
+    ChoicePanel4,
+    ChoicePanel5,
+    JunkoTachieDefault,
+    SightBackground1,
+    ArrowRight,
+    ArrowLeft,
+    KosuzuDotFront1,
+    KosuzuDotFront2,
+    KosuzuDotFront3,
+    KosuzuDotBack1,
+    KosuzuDotBack2,
+    KosuzuDotBack3,
+    KosuzuDotRight1,
+    KosuzuDotRight2,
+    KosuzuDotRight3,
+    KosuzuDotLeft1,
+    KosuzuDotLeft2,
+    KosuzuDotLeft3,
+    StoreButton,
+    ResetButton,
+    MenuArt1,
+    MenuArt2,
+    JpHouseTexture,
+    BookBoxFront,
+    BookBoxBack,
+    Paper3,
+    Paper4,
+    Paper5,
+    Paper6,
+    Paper7,
+    Clock1,
+    ClockNeedle1,
+    ShortClockNeedle1,
+    Hanko,
+    NextLineIcon,
+    LargeBookScratchFair1,
+    LargeBookScratchFair2,
+    LargeBookScratchFair3,
+    LargeBookScratchFair4,
+    LargeBookScratchBad1,
+    LargeBookScratchBad2,
+    LargeBookScratchBad3,
+    LargeBookScratchBad4,
+    ManualPageBookTitles,
+    ManualPageBorrowingFlow,
+    ManualPageReturnFlow,
+    GoNextPageLeft,
+    GoNextPageRight,
+    Library,
+    KosuzuTachie1,
+    CheckCircle,
+    MoneyBox,
+    KosuzuSmile1,
+    Coin100Yen,
+    Coin50Yen,
+    Coin500Yen,
+    BaraBG,
+    SuzunaanMap,
+    Mob1TachieDefault,
+    SuzuLogo,
+    AkyuTachieDefault,
+    Mob1DotFront1,
+    Mob1DotFront2,
+    Mob1DotFront3,
+    Mob1DotBack1,
+    Mob1DotBack2,
+    Mob1DotBack3,
+    Mob1DotRight1,
+    Mob1DotRight2,
+    Mob1DotRight3,
+    Mob1DotLeft1,
+    Mob1DotLeft2,
+    Mob1DotLeft3,
+    GoNextPageLeftNotAlpha,
+    GoNextPageRightNotAlpha,
+    Knob,
+    NitoriTachieDefault,
+    NitoriTachieSunGlass,
+    TownBackground,
+}
+
+///
+/// # テクスチャの名前とハンドルの対応
+///
+/// 組み込みのテクスチャは最初から登録されていて, それ以外は名前が現れた順に追加される
+/// declaredは, game_data.tomlでパスが宣言されたテクスチャ
+///
+struct TextureRegistry {
+    names: Vec<String>,
+    index: HashMap<String, TextureID>,
+    declared: Vec<TextureID>,
+}
+
+impl TextureRegistry {
+    fn with_builtin() -> Self {
+        let mut registry = TextureRegistry {
+            names: Vec::new(),
+            index: HashMap::new(),
+            declared: Vec::new(),
+        };
+
+        for name in BUILTIN_TEXTURE_NAMES.iter() {
+            registry.register(name);
+        }
+
+        registry
+    }
+
+    fn register(&mut self, name: &str) -> TextureID {
+        if let Some(id) = self.index.get(name) {
+            return *id;
+        }
+
+        let id = TextureID(self.names.len() as u32);
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), id);
+        id
+    }
+
+    fn declare(&mut self, name: &str) -> TextureID {
+        let id = self.register(name);
+        if !self.declared.contains(&id) {
+            self.declared.push(id);
+        }
+        id
+    }
+}
+
+///
+/// 先読みのワーカースレッドからも名前を引けるように, プロセス全体で一つの表を共有する
+///
+static TEXTURE_REGISTRY: OnceLock<RwLock<TextureRegistry>> = OnceLock::new();
+
+fn registry() -> &'static RwLock<TextureRegistry> {
+    TEXTURE_REGISTRY.get_or_init(|| RwLock::new(TextureRegistry::with_builtin()))
+}
+
+impl TextureID {
+    ///
+    /// 名前を登録してハンドルを返す. 登録済みの名前なら同じハンドルを返す
+    ///
+    pub fn register(name: &str) -> TextureID {
+        registry().write().unwrap().register(name)
+    }
+
+    ///
+    /// パスが宣言されたテクスチャとして名前を登録する
+    ///
+    pub fn declare(name: &str) -> TextureID {
+        registry().write().unwrap().declare(name)
+    }
+
+    pub fn lookup(name: &str) -> Option<TextureID> {
+        registry().read().unwrap().index.get(name).copied()
+    }
+
+    pub fn name(&self) -> String {
+        registry()
+            .read()
+            .unwrap()
+            .names
+            .get(self.0 as usize)
+            .cloned()
+            .unwrap_or_else(|| format!("#{}", self.0))
+    }
+
+    pub fn registered_count() -> usize {
+        registry().read().unwrap().names.len()
+    }
+
+    pub fn builtin_ids() -> impl Iterator<Item = TextureID> {
+        (0..BUILTIN_TEXTURE_NAMES.len() as u32).map(TextureID)
+    }
+
+    ///
+    /// パスが宣言されたテクスチャから一つ選ぶ. 何も宣言されていない場合は組み込みのテクスチャから選ぶ
+    ///
+    pub fn select_random() -> Self {
+        let registry = registry().read().unwrap();
+        if registry.declared.is_empty() {
+            TextureID(rand::random::<u32>() % BUILTIN_TEXTURE_NAMES.len() as u32)
+        } else {
+            registry.declared[rand::random::<usize>() % registry.declared.len()]
+        }
+    }
+}
+
+impl FromStr for TextureID {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, ()> {
+        TextureID::lookup(s).ok_or(())
+    }
+}
+
+///
+/// テクスチャの名前から読み込む. 名前はgame_data.tomlのtexturesで宣言されていなければならない
+///
+impl<'de> Deserialize<'de> for TextureID {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        schema::parse_str(deserializer)
+    }
+}
+
+impl fmt::Debug for TextureID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+///
+/// game_data.tomlのtexturesの名前を登録する
+/// テクスチャを読み込まないツールが, シナリオのテクスチャ名を解決するために使う
+///
+pub fn register_textures_from_game_data(root: &toml::Value) {
+    if let Some(textures) = root
+        .get("textures")
+        .and_then(|textures| textures.as_table())
+    {
+        for name in textures.keys() {
+            TextureID::declare(name);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::SuzuError;
+
+///
+/// # 翻訳対象の文字列を持つファイルの種類
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TranslationSourceKind {
+    Scenario,
+    MapEvent,
+    TitleMenu,
+}
+
+impl TranslationSourceKind {
+    ///
+    /// TOMLの最上位のキーからファイルの種類を判定する
+    ///
+    pub fn detect(root: &toml::Value) -> Option<Self> {
+        if root.get("scenario-group").is_some() {
+            Some(TranslationSourceKind::Scenario)
+        } else if root.get("event-panel").is_some() {
+            Some(TranslationSourceKind::MapEvent)
+        } else if root.get("each_entry_data").is_some() {
+            Some(TranslationSourceKind::TitleMenu)
+        } else {
+            None
+        }
+    }
+}
+
+fn array_tables_mut<'a>(
+    table: &'a mut toml::value::Table,
+    key: &str,
+) -> impl Iterator<Item = &'a mut toml::value::Table> {
+    table
+        .get_mut(key)
+        .and_then(|value| value.as_array_mut())
+        .into_iter()
+        .flat_map(|array| array.iter_mut())
+        .filter_map(|value| value.as_table_mut())
+}
+
+///
+/// 翻訳対象の文字列を持つテーブルを, キーとフィールド名と一緒に順に渡す
+///
+/// キーはファイルの中で要素を特定するもので, シナリオなら"要素のid.フィールド.番号"になる
+///
+fn for_each_text_slot<F>(kind: TranslationSourceKind, root: &mut toml::Value, mut f: F)
+where
+    F: FnMut(String, &mut toml::value::Table, &'static str),
+{
+    let root = match root.as_table_mut() {
+        Some(root) => root,
+        None => return,
+    };
+
+    match kind {
+        TranslationSourceKind::Scenario => {
+            for elem in array_tables_mut(root, "scenario-group") {
+                let id = match elem.get("id").and_then(|id| id.as_integer()) {
+                    Some(id) => id,
+                    None => continue,
+                };
+
+                match elem.get("type").and_then(|t| t.as_str()) {
+                    Some("scenario") => {
+                        for (index, text) in array_tables_mut(elem, "text").enumerate() {
+                            f(format!("{}.text.{}", id, index), text, "text");
+                        }
+                    }
+                    Some("choice") => {
+                        f(format!("{}.header_text", id), elem, "header_text");
+                        for (index, pattern) in array_tables_mut(elem, "choice-pattern").enumerate()
+                        {
+                            f(format!("{}.pattern.{}", id, index), pattern, "pattern");
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        TranslationSourceKind::MapEvent => {
+            for elem in array_tables_mut(root, "event-panel") {
+                if elem.get("type").and_then(|t| t.as_str()) != Some("text") {
+                    continue;
+                }
+
+                let position = elem.get("position").map(|position| {
+                    (
+                        position.get("x").and_then(|x| x.as_integer()).unwrap_or(0),
+                        position.get("y").and_then(|y| y.as_integer()).unwrap_or(0),
+                    )
+                });
+                if let Some((x, y)) = position {
+                    f(format!("event.{}.{}", x, y), elem, "text");
+                }
+            }
+        }
+        TranslationSourceKind::TitleMenu => {
+            for (index, entry) in array_tables_mut(root, "each_entry_data").enumerate() {
+                f(format!("entry.{}", index), entry, "text");
+            }
+        }
+    }
+}
+
+///
+/// 翻訳カタログの一項目. translationが空の場合は原文のまま表示する
+///
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranslationEntry {
+    pub source: String,
+    #[serde(default)]
+    pub translation: String,
+}
+
+///
+/// # 翻訳カタログ
+///
+/// ファイルのパスと, ファイル内のキーで原文と訳文を引く
+/// resources/locale/<locale>.jsonに置かれ, GameConfigのlocaleで選ばれる
+///
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranslationCatalog {
+    pub locale: String,
+    pub files: BTreeMap<String, BTreeMap<String, TranslationEntry>>,
+}
+
+impl TranslationCatalog {
+    pub fn new(locale: &str) -> Self {
+        TranslationCatalog {
+            locale: locale.to_string(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    pub fn catalog_path(locale: &str) -> String {
+        format!("/locale/{}.json", locale)
+    }
+
+    pub fn from_json(path: &str, content: &str) -> Result<Self, SuzuError> {
+        serde_json::from_str(content).map_err(|e| SuzuError::JsonParse {
+            path: path.to_string(),
+            source: e,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    ///
+    /// resourcesからlocaleのカタログを読み込む
+    ///
+    pub fn load(ctx: &mut ggez::Context, locale: &str) -> Result<Self, SuzuError> {
+        let path = Self::catalog_path(locale);
+        let content = super::util::try_read_from_resources_as_string(ctx, &path)?;
+        Self::from_json(&path, &content)
+    }
+
+    pub fn lookup(&self, path: &str, key: &str) -> Option<&str> {
+        self.files
+            .get(path)
+            .and_then(|entries| entries.get(key))
+            .map(|entry| entry.translation.as_str())
+            .filter(|translation| !translation.is_empty())
+    }
+
+    ///
+    /// ファイルの翻訳対象の文字列をカタログに追加する
+    ///
+    /// 原文が変わっていない項目は, 既存の訳文をそのまま残す
+    /// text_src_pathで指定されたテキストは, ゲームと同じくリソースディレクトリから読んで原文とする
+    ///
+    pub fn extract(&mut self, path: &str, root: &toml::Value) -> Result<usize, SuzuError> {
+        let kind = match TranslationSourceKind::detect(root) {
+            Some(kind) => kind,
+            None => return Ok(0),
+        };
+
+        let mut sources = Vec::new();
+        let mut error = None;
+        let mut root = root.clone();
+        for_each_text_slot(kind, &mut root, |key, table, field| {
+            let source = match table.get("text_src_path").and_then(|p| p.as_str()) {
+                Some(text_path) if field == "text" => {
+                    match super::util::try_read_resource_text(text_path) {
+                        Ok(text) => text,
+                        Err(e) => {
+                            error.get_or_insert(e);
+                            return;
+                        }
+                    }
+                }
+                _ => match table.get(field).and_then(|s| s.as_str()) {
+                    Some(text) => text.to_string(),
+                    None => return,
+                },
+            };
+            sources.push((key, source));
+        });
+
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        let entries = self
+            .files
+            .entry(path.to_string())
+            .or_insert_with(BTreeMap::new);
+        let count = sources.len();
+        for (key, source) in sources {
+            let entry = entries.entry(key).or_insert_with(TranslationEntry::default);
+            if entry.source != source {
+                entry.source = source;
+                entry.translation.clear();
+            }
+        }
+
+        Ok(count)
+    }
+
+    ///
+    /// 読み込んだTOMLの文字列を, カタログの訳文で置き換える
+    /// 置き換えた文字列の数を返す
+    ///
+    pub fn inject(&self, path: &str, root: &mut toml::Value) -> usize {
+        let kind = match TranslationSourceKind::detect(root) {
+            Some(kind) => kind,
+            None => return 0,
+        };
+
+        let mut count = 0;
+        for_each_text_slot(kind, root, |key, table, field| {
+            if let Some(translation) = self.lookup(path, &key) {
+                table.insert(
+                    field.to_string(),
+                    toml::Value::String(translation.to_string()),
+                );
+                if field == "text" {
+                    table.remove("text_src_path");
+                }
+                count += 1;
+            }
+        });
+
+        count
+    }
+}
@@ -1,7 +1,10 @@
 use std::cell::RefCell;
 use std::io::Read;
+use std::path::PathBuf;
 use torifune::numeric;
 
+use super::SuzuError;
+
 pub fn year_to_season(year: i64) -> i64 {
     year
 }
@@ -168,10 +171,110 @@ pub fn random_point_in_rect(rect: numeric::Rect) -> numeric::Point2f {
 }
 
 pub fn read_from_resources_as_string(ctx: &mut ggez::Context, path: &str) -> String {
-    let mut file = ggez::filesystem::open(ctx, path).unwrap();
+    match try_read_from_resources_as_string(ctx, path) {
+        Ok(s) => s,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+thread_local! {
+    static RESOURCE_DIRECTORY: RefCell<Option<PathBuf>> = RefCell::new(None);
+}
+
+///
+/// ggezに追加したリソースディレクトリを登録する
+/// ggezのファイルシステムを通さずに, 他のスレッドからリソースを読むときに使う
+///
+pub fn set_resource_directory(dir: PathBuf) {
+    RESOURCE_DIRECTORY.with(|resource_dir| *resource_dir.borrow_mut() = Some(dir));
+}
+
+///
+/// リソースのパスに対応する, リソースディレクトリ内のファイルのパスを返す
+/// リソースディレクトリが登録されていない場合や, ファイルが存在しない場合はNoneを返す
+///
+pub fn resource_file_path(path: &str) -> Option<PathBuf> {
+    RESOURCE_DIRECTORY.with(|resource_dir| {
+        resource_dir
+            .borrow()
+            .as_ref()
+            .map(|dir| dir.join(path.trim_start_matches('/')))
+            .filter(|file_path| file_path.is_file())
+    })
+}
+
+///
+/// ggez::Contextを使わずにリソースを読む
+/// マウントされているパック, 埋め込まれているリソース, set_resource_directoryで登録したディレクトリの順に探す
+/// どこにも見つからない場合はNoneを返す
+///
+pub fn read_from_resource_directory(path: &str) -> Option<Result<Vec<u8>, SuzuError>> {
+    if let Some(bytes) = super::resource_pack::read_mounted(path) {
+        return Some(bytes);
+    }
+
+    if let Some(content) = super::embedded::embedded_resource(path) {
+        return Some(Ok(content.as_bytes().to_vec()));
+    }
+
+    resource_file_path(path).map(|file_path| {
+        std::fs::read(&file_path).map_err(|e| SuzuError::io(&file_path.to_string_lossy(), e))
+    })
+}
+
+///
+/// ggez::Contextを持たない処理から, リソースのテキストファイルを読む
+///
+pub fn try_read_resource_text(path: &str) -> Result<String, SuzuError> {
+    let buffer = read_from_resource_directory(path)
+        .unwrap_or_else(|| Err(SuzuError::missing_asset(path, "not found in resources")))?;
+    String::from_utf8(buffer).map_err(|e| SuzuError::missing_asset(path, e))
+}
+
+///
+/// リソースを読む
+/// マウントされているパック, 埋め込まれているリソース, resourcesディレクトリの順に探す
+///
+pub fn try_read_from_resources(ctx: &mut ggez::Context, path: &str) -> Result<Vec<u8>, SuzuError> {
+    if let Some(bytes) = read_from_resource_directory(path) {
+        return bytes;
+    }
+
+    let mut file =
+        ggez::filesystem::open(ctx, path).map_err(|e| SuzuError::missing_asset(path, e))?;
     let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer).unwrap();
-    std::str::from_utf8(&buffer).unwrap().to_string()
+    file.read_to_end(&mut buffer)
+        .map_err(|e| SuzuError::io(path, e))?;
+    Ok(buffer)
+}
+
+///
+/// リソースを文字列として読む
+/// TOMLファイルにパックのパッチがある場合は, パッチを当てた内容を返す
+///
+pub fn try_read_from_resources_as_string(
+    ctx: &mut ggez::Context,
+    path: &str,
+) -> Result<String, SuzuError> {
+    let buffer = try_read_from_resources(ctx, path)?;
+    let content = String::from_utf8(buffer).map_err(|e| SuzuError::missing_asset(path, e))?;
+
+    let patches = super::resource_pack::mounted_patches(path);
+    if patches.is_empty() {
+        return Ok(content);
+    }
+
+    let mut root = super::error::parse_toml_str(path, &content)?;
+    let patch_path = super::resource_pack::patch_path(path).unwrap();
+    for patch in patches {
+        let patch = String::from_utf8(patch?).map_err(|e| SuzuError::missing_asset(&patch_path, e))?;
+        super::resource_pack::merge_toml_patch(
+            &mut root,
+            super::error::parse_toml_str(&patch_path, &patch)?,
+        );
+    }
+
+    toml::to_string(&root).map_err(|e| SuzuError::missing_asset(path, e))
 }
 
 #[macro_export]
@@ -184,3 +287,15 @@ macro_rules! parse_toml_file {
             .expect("Failed to parse toml file")
     }};
 }
+
+///
+/// parse_toml_fileのエラーを返す版
+/// 失敗した場合はファイル名を含んだSuzuErrorを返す
+///
+#[macro_export]
+macro_rules! try_parse_toml_file {
+    ( $ctx:expr, $path:expr) => {{
+        crate::core::util::try_read_from_resources_as_string($ctx, $path)
+            .and_then(|content| crate::core::error::parse_toml_str($path, &content))
+    }};
+}
@@ -26,7 +26,7 @@ pub fn main() {
                 .title("電氣貸本屋")
                 .samples(ggez::conf::NumSamples::Four),
         )
-        .add_resource_path(resource_dir)
+        .add_resource_path(resource_dir.clone())
         .window_mode(WindowMode {
             width: 1366.0,
             height: 768.0,
@@ -43,7 +43,15 @@ pub fn main() {
         .build()
         .unwrap();
 
-    let game_data: GameResource = GameResource::new(&mut ctx, "/game_data.toml".to_owned());
+    util::set_resource_directory(resource_dir);
+
+    let game_data: GameResource = match GameResource::new(&mut ctx, "/game_data.toml".to_owned()) {
+        Ok(game_data) => game_data,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
 
     {
         let state = State::new(&mut ctx, game_data).unwrap();
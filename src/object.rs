@@ -8,11 +8,16 @@ pub mod move_fn;
 pub mod notify;
 pub mod save_scene_object;
 pub mod scenario;
+pub mod scenario_backlog;
+pub mod scenario_graph;
+pub mod scenario_lint;
 pub mod scenario_object;
+pub mod scenario_script;
 pub mod shop_object;
 pub mod simulation_ui;
 pub mod task_object;
 pub mod task_result_object;
+pub mod text_markup;
 pub mod title_object;
 pub mod util_object;
 
@@ -161,12 +166,25 @@ impl TextureObject for BlackOutTexture {
     impl_texture_object_for_wrapped! {texture}
 }
 
+impl Effectable for BlackOutTexture {
+    fn effect(&mut self, ctx: &mut ggez::Context, t: Clock) {
+        self.texture.effect(ctx, t);
+    }
+}
+
 pub struct DarkEffectPanel {
     canvas: EffectableWrap<MovableWrap<SubScreen>>,
 }
 
 impl DarkEffectPanel {
     pub fn new(ctx: &mut ggez::Context, rect: numeric::Rect, now: Clock) -> Self {
+        Self::with_color(ctx, rect, 0x000000, now)
+    }
+
+    ///
+    /// 黒以外の色で画面を覆うDarkEffectPanelを生成する. rgbは0xRRGGBBの24bit値
+    ///
+    pub fn with_color(ctx: &mut ggez::Context, rect: numeric::Rect, rgb: u32, now: Clock) -> Self {
         DarkEffectPanel {
             canvas: EffectableWrap::new(
                 MovableWrap::new(
@@ -174,7 +192,7 @@ impl DarkEffectPanel {
                         ctx,
                         rect,
                         0,
-                        ggraphics::Color::from_rgba_u32(0),
+                        ggraphics::Color::from_rgba_u32(rgb << 8),
                     )),
                     None,
                     now,
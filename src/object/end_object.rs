@@ -3,7 +3,9 @@ use torifune::graphics::object::*;
 use torifune::numeric;
 use torifune::{core::*, sound::SoundPlayFlags};
 
-use crate::core::util::read_from_resources_as_string;
+use crate::core::error::{parse_toml_str, TomlNode};
+use crate::core::story::StoryExpr;
+use crate::core::util::{read_from_resources_as_string, try_read_from_resources_as_string};
 use crate::{
     core::*,
     flush_delay_event, flush_delay_event_and_redraw_check,
@@ -12,6 +14,58 @@ use crate::{
 
 use super::{effect, util_object::FramedButton};
 
+const ENDING_TABLE_PATH: &str = "/ending_table.toml";
+
+fn ending_text_from_toml(
+    root: &TomlNode,
+    save_data: &SavableData,
+) -> Result<Option<String>, SuzuError> {
+    let endings = match root.get_opt("ending") {
+        Some(endings) => endings.as_array()?,
+        None => return Ok(None),
+    };
+
+    for ending in endings {
+        let condition = ending.get("condition")?;
+        let condition_str = condition.as_str()?;
+        let expr = StoryExpr::parse(condition_str)
+            .map_err(|e| condition.invalid_value(&format!("{} ({})", condition_str, e)))?;
+
+        if expr.eval_bool(&|name| save_data.story_value(name)) {
+            return Ok(Some(ending.get("text")?.as_str()?.to_string()));
+        }
+    }
+
+    Ok(None)
+}
+
+///
+/// ending_table.tomlに並んだ結末を上から評価し, 条件を満たした最初の結末の文章を返す
+/// 選択肢の結果やフラグによって終了画面の内容を変えるために使う
+///
+fn select_ending_text<'a>(ctx: &mut SuzuContext<'a>) -> Option<String> {
+    if !ggez::filesystem::exists(ctx.context, ENDING_TABLE_PATH) {
+        return None;
+    }
+
+    let result = try_read_from_resources_as_string(ctx.context, ENDING_TABLE_PATH)
+        .and_then(|content| parse_toml_str(ENDING_TABLE_PATH, &content))
+        .and_then(|root| {
+            ending_text_from_toml(
+                &TomlNode::root(ENDING_TABLE_PATH, &root),
+                ctx.take_save_data(),
+            )
+        });
+
+    match result {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
+
 pub struct EndSceneFlow {
     thanks_text: EffectableWrap<MovableWrap<UniText>>,
     resul_main_vtext: EffectableWrap<MovableWrap<VerticalText>>,
@@ -40,7 +94,7 @@ impl EndSceneFlow {
         let mut book_collection = Vec::new();
         let mut pos = numeric::Point2f::new(1000.0, 90.0);
 
-        vec![
+        let mut result_texts = vec![
             format!(
                 "評判\n　{}",
                 number_to_jk::number_to_jk(
@@ -77,9 +131,13 @@ impl EndSceneFlow {
                         .returning_check_mistake_count as u64
                 )
             ),
-        ]
-        .into_iter()
-        .for_each(|s| {
+        ];
+
+        if let Some(ending_text) = select_ending_text(ctx) {
+            result_texts.push(ending_text);
+        }
+
+        result_texts.into_iter().for_each(|s| {
             let mut vtext = VerticalText::new(
                 s,
                 pos,
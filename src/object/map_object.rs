@@ -10,13 +10,18 @@ use torifune::graphics::drawable::*;
 use torifune::graphics::object::*;
 use torifune::numeric;
 
+use serde::Deserialize;
+
+use crate::core::error::TomlNode;
 use crate::core::map_parser as mp;
+use crate::core::story::StoryExpr;
 use crate::core::*;
 use crate::flush_delay_event;
 use crate::object::collision::*;
 use crate::object::task_object::tt_main_component::CustomerRequest;
 use crate::object::util_object::*;
 use crate::scene::{DelayEventList, SceneID};
+use crate::try_parse_toml_file;
 
 use super::task_object::tt_main_component::CustomerRequestOrder;
 
@@ -928,7 +933,7 @@ impl CustomerCharacter {
 
         match random_select {
             0 => CustomerRequest::Returning(ReturnBookInformation::new_random(
-                ctx.resource,
+                ctx.resource.economy_data(),
                 today,
                 GensoDate::new(128, 12, 20),
             )),
@@ -950,7 +955,11 @@ impl CustomerCharacter {
 
         match order {
             CustomerRequestOrder::ReturningOrder => CustomerRequest::Returning(
-                ReturnBookInformation::new_random(ctx.resource, today, GensoDate::new(128, 12, 20)),
+                ReturnBookInformation::new_random(
+                    ctx.resource.economy_data(),
+                    today,
+                    GensoDate::new(128, 12, 20),
+                ),
             ),
             CustomerRequestOrder::BorrowingOrder => {
                 CustomerRequest::Borrowing(BorrowingInformation::new(
@@ -1224,7 +1233,8 @@ impl OnMap for CustomerCharacter {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum EventTrigger {
     Action,
     Touch,
@@ -1237,7 +1247,7 @@ impl FromStr for EventTrigger {
         match trigger_str {
             "action" => Ok(Self::Action),
             "touch" => Ok(Self::Touch),
-            _ => panic!("Error: EventTrigger::from_str"),
+            _ => Err(()),
         }
     }
 }
@@ -1246,25 +1256,13 @@ pub trait MapEvent {
     fn get_trigger_method(&self) -> EventTrigger;
 }
 
+#[derive(Deserialize)]
 pub struct MapTextEvent {
     trigger: EventTrigger,
     text: String,
 }
 
 impl MapTextEvent {
-    pub fn from_toml_object(toml_script: &toml::value::Value) -> Self {
-        MapTextEvent {
-            trigger: EventTrigger::from_str(toml_script.get("trigger").unwrap().as_str().unwrap())
-                .unwrap(),
-            text: toml_script
-                .get("text")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string(),
-        }
-    }
-
     pub fn get_text(&self) -> &str {
         &self.text
     }
@@ -1276,8 +1274,10 @@ impl MapEvent for MapTextEvent {
     }
 }
 
+#[derive(Deserialize)]
 pub struct MapEventSceneSwitch {
     trigger: EventTrigger,
+    #[serde(rename = "switch-scene-id", deserialize_with = "schema::parse_str")]
     switch_scene: SceneID,
 }
 
@@ -1289,21 +1289,6 @@ impl MapEventSceneSwitch {
         }
     }
 
-    pub fn from_toml_object(toml_script: &toml::value::Value) -> Self {
-        MapEventSceneSwitch {
-            trigger: EventTrigger::from_str(toml_script.get("trigger").unwrap().as_str().unwrap())
-                .unwrap(),
-            switch_scene: SceneID::from_str(
-                toml_script
-                    .get("switch-scene-id")
-                    .unwrap()
-                    .as_str()
-                    .unwrap(),
-            )
-            .unwrap(),
-        }
-    }
-
     pub fn get_switch_scene_id(&self) -> SceneID {
         self.switch_scene
     }
@@ -1315,30 +1300,14 @@ impl MapEvent for MapEventSceneSwitch {
     }
 }
 
+#[derive(Deserialize)]
 pub struct BookStoreEvent {
     trigger: EventTrigger,
+    #[serde(rename = "shelf-info")]
     book_shelf_info: BookShelfInformation,
 }
 
 impl BookStoreEvent {
-    pub fn from_toml_object(toml_script: &toml::value::Value) -> Self {
-        let shelf_info = toml_script.get("shelf-info").unwrap().as_table().unwrap();
-        let book_shelf_info = BookShelfInformation::new(
-            shelf_info
-                .get("begin-number")
-                .unwrap()
-                .as_integer()
-                .unwrap() as u16,
-            shelf_info.get("end-number").unwrap().as_integer().unwrap() as u16,
-        );
-
-        BookStoreEvent {
-            trigger: EventTrigger::from_str(toml_script.get("trigger").unwrap().as_str().unwrap())
-                .unwrap(),
-            book_shelf_info: book_shelf_info,
-        }
-    }
-
     pub fn get_book_shelf_info(&self) -> &BookShelfInformation {
         &self.book_shelf_info
     }
@@ -1350,7 +1319,8 @@ impl MapEvent for BookStoreEvent {
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum BuiltinEventSymbol {
     SelectShelvingBook = 0,
 }
@@ -1361,36 +1331,26 @@ impl FromStr for BuiltinEventSymbol {
     fn from_str(builtin_event_symbol: &str) -> Result<Self, Self::Err> {
         match builtin_event_symbol {
             "select-shelving-book" => Ok(Self::SelectShelvingBook),
-            _ => panic!("Error: BuiltinEventSymbol::from_str"),
+            _ => Err(()),
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize)]
+struct BuiltinEventInfo {
+    symbol: BuiltinEventSymbol,
+}
+
+#[derive(Clone, Copy, Deserialize)]
 pub struct BuiltinEvent {
     trigger: EventTrigger,
-    event_symbol: BuiltinEventSymbol,
+    #[serde(rename = "builtin-event-info")]
+    info: BuiltinEventInfo,
 }
 
 impl BuiltinEvent {
-    pub fn from_toml_object(toml_script: &toml::value::Value) -> Self {
-        let builtin_event_info = toml_script
-            .get("builtin-event-info")
-            .unwrap()
-            .as_table()
-            .unwrap();
-        BuiltinEvent {
-            trigger: EventTrigger::from_str(toml_script.get("trigger").unwrap().as_str().unwrap())
-                .unwrap(),
-            event_symbol: BuiltinEventSymbol::from_str(
-                builtin_event_info.get("symbol").unwrap().as_str().unwrap(),
-            )
-            .unwrap(),
-        }
-    }
-
     pub fn get_event_symbol(&self) -> BuiltinEventSymbol {
-        self.event_symbol
+        self.info.symbol
     }
 }
 
@@ -1418,61 +1378,113 @@ impl MapEvent for MapEventElement {
     }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct PanelPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+///
+/// event-panelの要素のうち, 種類によらず持つ部分. conditionは条件式として別に読み込む
+///
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventPanelHeader {
+    pub position: PanelPosition,
+    #[serde(rename = "type")]
+    pub event_type: String,
+}
+
+///
+/// # マップ上のイベントの一覧
+///
+/// conditionを持つイベントは, その条件式をセーブデータで評価して満たしたときだけ発生する
+///
 pub struct MapEventList {
     event_table: HashMap<numeric::Point2i, MapEventElement>,
+    condition_table: HashMap<numeric::Point2i, StoryExpr>,
 }
 
 impl MapEventList {
-    pub fn from_file<'a>(ctx: &mut SuzuContext<'a>, file_path: &str) -> Self {
-        let mut table = HashMap::new();
+    ///
+    /// event-panelの要素を一つ読み込み, 位置と発生条件とイベントを返す
+    ///
+    pub fn event_from_toml(
+        elem: &TomlNode,
+    ) -> Result<(numeric::Point2i, Option<StoryExpr>, MapEventElement), SuzuError> {
+        let header: EventPanelHeader = schema::deserialize_node(elem)?;
+        let position = numeric::Point2i::new(header.position.x, header.position.y);
+
+        let condition = match elem.get_opt("condition") {
+            Some(condition) => {
+                let src = condition.as_str()?;
+                let expr = StoryExpr::parse(src)
+                    .map_err(|e| condition.invalid_value(&format!("{} ({})", src, e)))?;
+                Some(expr)
+            }
+            None => None,
+        };
 
-        let content = util::read_from_resources_as_string(ctx.context, file_path);
+        let event = match header.event_type.as_str() {
+            "text" => MapEventElement::TextEvent(schema::deserialize_node(elem)?),
+            "switch-scene" => MapEventElement::SwitchScene(schema::deserialize_node(elem)?),
+            "book-shelf" => MapEventElement::BookStoreEvent(schema::deserialize_node(elem)?),
+            "builtin-event" => MapEventElement::BuiltinEvent(schema::deserialize_node(elem)?),
+            s => return Err(elem.get("type")?.invalid_value(s)),
+        };
 
-        let root = content.parse::<toml::Value>().unwrap();
-        let array = root["event-panel"].as_array().unwrap();
+        Ok((position, condition, event))
+    }
 
-        for elem in array {
-            let position_data = elem.get("position").unwrap().as_table().unwrap();
-            let position = numeric::Point2i::new(
-                position_data.get("x").unwrap().as_integer().unwrap() as i32,
-                position_data.get("y").unwrap().as_integer().unwrap() as i32,
-            );
-            if let Some(type_info) = elem.get("type") {
-                match type_info.as_str().unwrap() {
-                    "text" => {
-                        table.insert(
-                            position,
-                            MapEventElement::TextEvent(MapTextEvent::from_toml_object(elem)),
-                        );
-                    }
-                    "switch-scene" => {
-                        table.insert(
-                            position,
-                            MapEventElement::SwitchScene(MapEventSceneSwitch::from_toml_object(
-                                elem,
-                            )),
-                        );
-                    }
-                    "book-shelf" => {
-                        table.insert(
-                            position,
-                            MapEventElement::BookStoreEvent(BookStoreEvent::from_toml_object(elem)),
-                        );
-                    }
-                    "builtin-event" => {
-                        table.insert(
-                            position,
-                            MapEventElement::BuiltinEvent(BuiltinEvent::from_toml_object(elem)),
-                        );
-                    }
-                    _ => eprintln!("Error"),
+    ///
+    /// event-panelの要素を一つずつ読み込み, 読み込めなかった要素の問題を全てproblemsに追加する
+    ///
+    pub fn validate(root: &TomlNode, problems: &mut Vec<SuzuError>) {
+        let panels = match root.get("event-panel").and_then(|panels| panels.as_array()) {
+            Ok(panels) => panels,
+            Err(e) => {
+                problems.push(e);
+                return;
+            }
+        };
+
+        for elem in panels.iter() {
+            if let Err(e) = Self::event_from_toml(elem) {
+                problems.push(e);
+            }
+        }
+    }
+
+    pub fn from_file<'a>(ctx: &mut SuzuContext<'a>, file_path: &str) -> Result<Self, SuzuError> {
+        let mut table = HashMap::new();
+        let mut condition_table = HashMap::new();
+
+        let mut root = try_parse_toml_file!(ctx.context, file_path)?;
+        ctx.resource
+            .get_mods()
+            .append_event_panels(file_path, &mut root);
+        ctx.resource.translate(file_path, &mut root);
+
+        let panels = TomlNode::root(file_path, &root)
+            .get("event-panel")?
+            .as_array()?;
+        for elem in panels.iter() {
+            let (position, condition, event) = Self::event_from_toml(elem)?;
+            match condition {
+                Some(condition) => {
+                    condition_table.insert(position, condition);
+                }
+                // MODが同じ位置のイベントを置き換えた場合, 元のイベントの条件は使わない
+                None => {
+                    condition_table.remove(&position);
                 }
-            } else {
-                eprintln!("Error");
             }
+            table.insert(position, event);
         }
 
-        MapEventList { event_table: table }
+        Ok(MapEventList {
+            event_table: table,
+            condition_table: condition_table,
+        })
     }
 
     pub fn register_event(&mut self, point: numeric::Point2i, event: MapEventElement) -> &mut Self {
@@ -1484,7 +1496,14 @@ impl MapEventList {
         &self,
         trigger: EventTrigger,
         point: numeric::Point2i,
+        save_data: &SavableData,
     ) -> Option<&MapEventElement> {
+        if let Some(condition) = self.condition_table.get(&point) {
+            if !condition.eval_bool(&|name| save_data.story_value(name)) {
+                return None;
+            }
+        }
+
         if let Some(event_element) = self.event_table.get(&point) {
             if event_element.get_trigger_method() == trigger {
                 return Some(&event_element);
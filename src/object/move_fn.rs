@@ -100,3 +100,63 @@ pub fn move_constant_dest(
         },
     ))
 }
+
+///
+/// # required_time
+/// 動きにかける時間. 経過後はbaseに止まる
+///
+/// baseから上にheightだけ跳ねて戻る
+///
+pub fn hop(base: numeric::Point2f, height: f32, required_time: Clock) -> Option<GenericMoveFn> {
+    Some(Box::new(
+        move |_: &dyn tg::object::MovableObject, t: Clock| {
+            if t >= required_time {
+                return Some(base);
+            }
+
+            let ratio = t as f32 / required_time as f32;
+            Some(numeric::Point2f::new(
+                base.x,
+                base.y - (height * (ratio * std::f32::consts::PI).sin()),
+            ))
+        },
+    ))
+}
+
+///
+/// baseを中心に左右に揺れる. 揺れ幅は徐々に小さくなる
+///
+pub fn shake(base: numeric::Point2f, amplitude: f32, required_time: Clock) -> Option<GenericMoveFn> {
+    Some(Box::new(
+        move |_: &dyn tg::object::MovableObject, t: Clock| {
+            if t >= required_time {
+                return Some(base);
+            }
+
+            let ratio = t as f32 / required_time as f32;
+            Some(numeric::Point2f::new(
+                base.x + (amplitude * (1.0 - ratio) * (ratio * std::f32::consts::PI * 6.0).sin()),
+                base.y,
+            ))
+        },
+    ))
+}
+
+///
+/// baseから下にdepthだけ2回沈んで戻る
+///
+pub fn nod(base: numeric::Point2f, depth: f32, required_time: Clock) -> Option<GenericMoveFn> {
+    Some(Box::new(
+        move |_: &dyn tg::object::MovableObject, t: Clock| {
+            if t >= required_time {
+                return Some(base);
+            }
+
+            let ratio = t as f32 / required_time as f32;
+            Some(numeric::Point2f::new(
+                base.x,
+                base.y + (depth * (ratio * std::f32::consts::PI * 2.0).sin().abs()),
+            ))
+        },
+    ))
+}
@@ -5,13 +5,19 @@ use torifune::graphics::object::sub_screen;
 use torifune::graphics::object::sub_screen::SubScreen;
 use torifune::graphics::object::*;
 use torifune::numeric;
-use torifune::{graphics::drawable::*, sound::SoundHandler};
+use torifune::{graphics::drawable::*, sound::SoundHandler, sound::SoundPlayFlags};
 
 use torifune::impl_drawable_object_for_wrapped;
 use torifune::impl_texture_object_for_wrapped;
 
 use super::*;
-use crate::{core::ScenarioSceneSaveData, parse_toml_file};
+use crate::core::error::TomlNode;
+use crate::core::story::{StoryExpr, StoryVariables};
+use crate::{core::SavableData, core::ScenarioSceneSaveData, core::SuzuError};
+use crate::object::scenario_backlog::{BacklogEntry, BacklogWindow, ScenarioBacklog};
+use crate::object::scenario_script::parse_scenario_source;
+use crate::object::simulation_ui::Meter;
+use crate::object::text_markup::{MarkupRun, MarkupStyle, MarkupText};
 use crate::scene::scenario_scene::ScenarioContext;
 use crate::scene::{SceneID, SceneTransition};
 use crate::{core::SoundID, object::util_object::*};
@@ -28,68 +34,141 @@ pub struct ScenarioTextAttribute {
     pub font_info: FontInformation,
 }
 
-pub struct ScenarioTextSegment {
-    text: String,
-    attribute: ScenarioTextAttribute,
+///
+/// # [[scenario-group.text]]1つ分の, インライン装飾を解析したテキスト
+///
+/// styleはテーブルで指定されたfpc, font_scale, colorで, 装飾がない部分に適用される
+/// voiceはこのテキストを表示し始めるときに再生するボイスのパス
+///
+pub struct ScenarioTextSource {
+    markup: MarkupText,
+    style: MarkupStyle,
+    voice: Option<String>,
 }
 
-impl ScenarioTextSegment {
-    pub fn new(text: &str, fpc: f32, font_info: FontInformation) -> Self {
-        ScenarioTextSegment {
-            text: text.to_string(),
-            attribute: ScenarioTextAttribute {
-                fpc: fpc,
-                font_info: font_info,
-            },
+impl ScenarioTextSource {
+    pub fn new(markup: MarkupText, style: MarkupStyle) -> Self {
+        ScenarioTextSource {
+            markup: markup,
+            style: style,
+            voice: None,
         }
     }
 
     pub fn from_toml_using_default(
-        obj: &toml::value::Table,
-        game_data: &GameResource,
-        default: &ScenarioTextAttribute,
-    ) -> Self {
-        let text = if let Some(text_path) = obj.get("text_src_path").as_ref() {
-            std::fs::read_to_string(text_path.as_str().unwrap()).unwrap()
+        obj: &TomlNode,
+        default: &MarkupStyle,
+    ) -> Result<Self, SuzuError> {
+        let (text, text_node) = if let Some(text_path) = obj.get_opt("text_src_path") {
+            let text_path_str = text_path.as_str()?;
+            let text = crate::core::util::try_read_resource_text(text_path_str)
+                .map_err(|e| text_path.invalid_data(e))?;
+            (text, text_path)
         } else {
-            obj.get("text")
-                .expect("You must insert text or text_src_path field.")
-                .as_str()
-                .unwrap()
-                .to_string()
+            let text_node = obj.get("text")?;
+            (text_node.as_str()?.to_string(), text_node)
         };
 
-        let fpc = if let Some(fpc) = obj.get("fpc") {
-            fpc.as_float().unwrap() as f32
+        let markup = MarkupText::parse(&text)
+            .map_err(|e| text_node.invalid_value(&format!("{} ({})", text.trim(), e)))?;
+
+        let fpc = if let Some(fpc) = obj.get_opt("fpc") {
+            fpc.as_float()? as f32
         } else {
             default.fpc
         };
 
-        let font_scale = if let Some(font_scale) = obj.get("font_scale") {
-            font_scale.as_float().unwrap() as f32
+        let font_scale = if let Some(font_scale) = obj.get_opt("font_scale") {
+            font_scale.as_float()? as f32
         } else {
-            default.font_info.scale.x
+            default.font_scale
         };
 
-        let color = if let Some(color) = obj.get("color") {
-            ggraphics::Color::from_rgba_u32(color.as_integer().unwrap() as u32)
+        let color = if let Some(color) = obj.get_opt("color") {
+            color.as_integer()? as u32
         } else {
-            default.font_info.color
+            default.color
         };
 
+        let mut source = ScenarioTextSource::new(
+            markup,
+            MarkupStyle {
+                fpc: fpc,
+                font_scale: font_scale,
+                color: color,
+            },
+        );
+        if let Some(voice) = obj.get_opt("voice") {
+            source.voice = Some(voice.as_str()?.to_string());
+        }
+
+        Ok(source)
+    }
+}
+
+///
+/// 読み仮名の文字の大きさの, 本文に対する比率
+///
+const RUBY_SCALE_RATIO: f32 = 0.5;
+
+pub struct ScenarioTextSegment {
+    text: String,
+    attribute: ScenarioTextAttribute,
+    continues_line: bool,
+    ruby: Option<String>,
+}
+
+impl ScenarioTextSegment {
+    pub fn new(text: &str, fpc: f32, font_info: FontInformation) -> Self {
         ScenarioTextSegment {
-            text: text,
+            text: text.to_string(),
             attribute: ScenarioTextAttribute {
                 fpc: fpc,
+                font_info: font_info,
+            },
+            continues_line: false,
+            ruby: None,
+        }
+    }
+
+    fn from_markup_run(run: MarkupRun, font: ggraphics::Font, continues_line: bool) -> Self {
+        ScenarioTextSegment {
+            text: run.text,
+            attribute: ScenarioTextAttribute {
+                fpc: run.style.fpc,
                 font_info: FontInformation::new(
-                    game_data.get_font(FontID::Cinema),
-                    numeric::Vector2f::new(font_scale, font_scale),
-                    color,
+                    font,
+                    numeric::Vector2f::new(run.style.font_scale, run.style.font_scale),
+                    ggraphics::Color::from_rgba_u32(run.style.color),
                 ),
             },
+            continues_line: continues_line,
+            ruby: run.ruby,
         }
     }
 
+    ///
+    /// 前のセグメントの最後の行に続けて表示するか
+    ///
+    pub fn is_continued_line(&self) -> bool {
+        self.continues_line
+    }
+
+    pub fn get_ruby(&self) -> Option<&str> {
+        self.ruby.as_ref().map(|s| s.as_str())
+    }
+
+    ///
+    /// 読み仮名を表示するためのフォント. 読み仮名がない場合はNone
+    ///
+    pub fn get_ruby_font_info(&self) -> Option<FontInformation> {
+        self.ruby.as_ref().map(|_| {
+            let mut font_info = self.attribute.font_info;
+            font_info.scale *= RUBY_SCALE_RATIO;
+            font_info
+        })
+    }
+
     fn slice_text_bytes(&self, begin: usize, end: usize) -> &str {
         unsafe { self.text.as_str().get_unchecked(begin..end) }
     }
@@ -134,6 +213,10 @@ impl ScenarioTextSegment {
         self.text.chars().count()
     }
 
+    pub fn get_text(&self) -> &str {
+        self.text.as_str()
+    }
+
     pub fn get_font_info(&self) -> &FontInformation {
         &self.attribute.font_info
     }
@@ -147,10 +230,280 @@ impl ScenarioTextSegment {
     }
 }
 
+///
+/// # 立ち絵の表示位置
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TachiePosition {
+    Left,
+    InnerRight,
+    Right,
+}
+
+impl FromStr for TachiePosition {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Self::Left),
+            "inner-right" => Ok(Self::InnerRight),
+            "right" => Ok(Self::Right),
+            _ => Err(()),
+        }
+    }
+}
+
+///
+/// # 話者に合わせた立ち絵の強調
+///
+/// Allは話者がいない地の文, Nobodyは立ち絵のない人物が話している状態
+/// Speakerでは, 指定した位置以外の立ち絵を暗くする
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TachieHighlight {
+    All,
+    Speaker(TachiePosition),
+    Nobody,
+}
+
+const TACHIE_DIMMED_COLOR: u32 = 0x808080ff;
+
+///
+/// # 立ち絵の登場と退場の仕方
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TachieTransition {
+    Cut,
+    Fade,
+    Slide,
+}
+
+impl FromStr for TachieTransition {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cut" => Ok(Self::Cut),
+            "fade" => Ok(Self::Fade),
+            "slide" => Ok(Self::Slide),
+            _ => Err(()),
+        }
+    }
+}
+
+///
+/// # 立ち絵の短い動き
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TachieMotion {
+    Hop,
+    Shake,
+    Nod,
+}
+
+impl FromStr for TachieMotion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hop" => Ok(Self::Hop),
+            "shake" => Ok(Self::Shake),
+            "nod" => Ok(Self::Nod),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TachieAction {
+    Expression(TextureID),
+    Enter(TextureID, TachieTransition),
+    Exit(TachieTransition),
+    Motion(TachieMotion),
+}
+
+///
+/// # 表示中の立ち絵に対する命令
+///
+/// テキストのtachie-commandに書き, そのテキストを表示し始めるときに実行する
+/// tachie-dataと違い, 表示中の立ち絵を作り直さずに表情の差し替えや登場, 退場, 動きを行う
+///
+/// [[scenario-group.tachie-command]]
+/// command = "expression" | "enter" | "exit" | "motion"
+/// position = "left" | "inner-right" | "right"
+/// texture = "AkyuTachieDefault"       # expression, enterで使用
+/// transition = "cut" | "fade" | "slide" # enter, exitで使用. 省略するとfade
+/// motion = "hop" | "shake" | "nod"      # motionで使用
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TachieCommand {
+    pub position: TachiePosition,
+    pub action: TachieAction,
+}
+
+fn enum_from_toml<T: FromStr>(node: &TomlNode) -> Result<T, SuzuError> {
+    let s = node.as_str()?;
+    T::from_str(s).map_err(|_| node.invalid_value(s))
+}
+
+impl TachieCommand {
+    pub fn from_toml_object(toml_scripts: &TomlNode) -> Result<Self, SuzuError> {
+        let position = enum_from_toml(&toml_scripts.get("position")?)?;
+        let transition = match toml_scripts.get_opt("transition") {
+            Some(transition) => enum_from_toml(&transition)?,
+            None => TachieTransition::Fade,
+        };
+
+        let command = toml_scripts.get("command")?;
+        let action = match command.as_str()? {
+            "expression" => {
+                TachieAction::Expression(texture_id_from_toml(&toml_scripts.get("texture")?)?)
+            }
+            "enter" => TachieAction::Enter(
+                texture_id_from_toml(&toml_scripts.get("texture")?)?,
+                transition,
+            ),
+            "exit" => TachieAction::Exit(transition),
+            "motion" => TachieAction::Motion(enum_from_toml(&toml_scripts.get("motion")?)?),
+            s => return Err(command.invalid_value(s)),
+        };
+
+        Ok(TachieCommand {
+            position: position,
+            action: action,
+        })
+    }
+}
+
+fn tachie_commands_from_toml(toml_scripts: &TomlNode) -> Result<Vec<TachieCommand>, SuzuError> {
+    let mut commands = Vec::new();
+
+    if let Some(command_list) = toml_scripts.get_opt("tachie-command") {
+        for command in command_list.as_array()? {
+            commands.push(TachieCommand::from_toml_object(&command)?);
+        }
+    }
+
+    Ok(commands)
+}
+
+///
+/// # シナリオ中の音に対する命令
+///
+/// テキストのsound-commandに書き, そのテキストを表示し始めるときに実行する
+/// fadeはフレーム数で, stop-bgmでは省略すると即座に止め, crossfade-bgmでは省略すると60になる
+///
+/// [[scenario-group.sound-command]]
+/// command = "play-bgm" | "stop-bgm" | "crossfade-bgm" | "se"
+/// sound = "ScenarioBGM"   # stop-bgm以外で使用
+/// fade = 60               # stop-bgm, crossfade-bgmで使用
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScenarioSoundCommand {
+    PlayBgm(SoundID),
+    StopBgm(Clock),
+    CrossfadeBgm(SoundID, Clock),
+    PlaySe(SoundID),
+}
+
+const DEFAULT_BGM_CROSSFADE_TIME: Clock = 60;
+
+///
+/// ボイスを再生している間のBGMの音量の割合
+///
+const BGM_DUCKING_RATIO: f32 = 0.35;
+
+impl ScenarioSoundCommand {
+    pub fn from_toml_object(toml_scripts: &TomlNode) -> Result<Self, SuzuError> {
+        let fade = match toml_scripts.get_opt("fade") {
+            Some(fade) => Some(fade.as_integer()? as Clock),
+            None => None,
+        };
+        let bgm_id = || -> Result<SoundID, SuzuError> {
+            let node = toml_scripts.get("sound")?;
+            let sound_id: SoundID = enum_from_toml(&node)?;
+            if sound_id.is_bgm() {
+                Ok(sound_id)
+            } else {
+                Err(node.invalid_value(node.as_str()?))
+            }
+        };
+
+        let command = toml_scripts.get("command")?;
+        match command.as_str()? {
+            "play-bgm" => Ok(Self::PlayBgm(bgm_id()?)),
+            "stop-bgm" => Ok(Self::StopBgm(fade.unwrap_or(0))),
+            "crossfade-bgm" => Ok(Self::CrossfadeBgm(
+                bgm_id()?,
+                fade.unwrap_or(DEFAULT_BGM_CROSSFADE_TIME),
+            )),
+            "se" => Ok(Self::PlaySe(enum_from_toml(&toml_scripts.get("sound")?)?)),
+            s => Err(command.invalid_value(s)),
+        }
+    }
+}
+
+fn sound_commands_from_toml(
+    toml_scripts: &TomlNode,
+) -> Result<Vec<ScenarioSoundCommand>, SuzuError> {
+    let mut commands = Vec::new();
+
+    if let Some(command_list) = toml_scripts.get_opt("sound-command") {
+        for command in command_list.as_array()? {
+            commands.push(ScenarioSoundCommand::from_toml_object(&command)?);
+        }
+    }
+
+    Ok(commands)
+}
+
+///
+/// # フェードアウト中のBGM
+///
+/// fromのBGMの音量を, startからrequired_timeフレームかけて0まで下げ, 最後に止める
+///
+struct BgmFadeOut {
+    from: Vec<SoundID>,
+    start: Clock,
+    required_time: Clock,
+}
+
+impl BgmFadeOut {
+    fn update<'a>(&self, ctx: &mut SuzuContext<'a>, t: Clock) -> bool {
+        if t >= self.start + self.required_time {
+            self.finish(ctx);
+            return false;
+        }
+
+        let ratio = 1.0 - ((t - self.start) as f32 / self.required_time as f32);
+        let volume = ratio * ctx.config.get_bgm_volume();
+        for sound_id in self.from.iter() {
+            ctx.resource.set_bgm_volume(sound_id, volume);
+        }
+
+        true
+    }
+
+    fn finish<'a>(&self, ctx: &mut SuzuContext<'a>) {
+        for sound_id in self.from.iter() {
+            ctx.resource.stop_bgm(ctx.context, sound_id.clone());
+        }
+    }
+}
+
+const TACHIE_TRANSITION_TIME: Clock = 20;
+const TACHIE_SLIDE_DISTANCE: f32 = 240.0;
+const TACHIE_MOTION_TIME: Clock = 24;
+
 pub struct ScenarioTachie {
     left: Option<SimpleObject>,
     right: Option<SimpleObject>,
     inner_right: Option<SimpleObject>,
+    tachie_data: TachieData,
+    declared: TachieData,
+    leaving: Vec<(SimpleObject, Clock)>,
+    animation_end: Clock,
+    highlight: TachieHighlight,
     drwob_essential: DrawableObjectEssential,
 }
 
@@ -238,74 +591,458 @@ impl ScenarioTachie {
             inner_right: inner_right_texture,
             left: left_texture,
             right: right_texture,
+            declared: tachie_data.clone(),
+            tachie_data: tachie_data,
+            leaving: Vec::new(),
+            animation_end: 0,
+            highlight: TachieHighlight::All,
             drwob_essential: DrawableObjectEssential::new(true, 0),
         }
     }
-}
-
-impl DrawableComponent for ScenarioTachie {
-    fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
-        if self.drwob_essential.visible {
-            if let Some(texture) = self.left.as_mut() {
-                texture.draw(ctx)?;
-            }
 
-            if let Some(texture) = self.right.as_mut() {
-                texture.draw(ctx)?;
-            }
+    pub fn get_tachie_data(&self) -> TachieData {
+        self.tachie_data.clone()
+    }
 
-            if let Some(texture) = self.inner_right.as_mut() {
-                texture.draw(ctx)?;
-            }
+    fn slot_mut(&mut self, position: TachiePosition) -> &mut Option<SimpleObject> {
+        match position {
+            TachiePosition::Left => &mut self.left,
+            TachiePosition::InnerRight => &mut self.inner_right,
+            TachiePosition::Right => &mut self.right,
         }
-
-        Ok(())
     }
 
-    fn hide(&mut self) {
-        self.drwob_essential.visible = false;
+    ///
+    /// 立ち絵が止まっているときの位置. newで配置する位置と同じ
+    ///
+    fn rest_position(&self, position: TachiePosition) -> numeric::Point2f {
+        match position {
+            TachiePosition::Left => numeric::Point2f::new(70.0, 88.0),
+            TachiePosition::InnerRight => numeric::Point2f::new(680.0, 88.0),
+            TachiePosition::Right => numeric::Point2f::new(
+                if self.tachie_data.inner_right.is_some() {
+                    880.0
+                } else {
+                    820.0
+                },
+                60.0,
+            ),
+        }
     }
 
-    fn appear(&mut self) {
-        self.drwob_essential.visible = true;
+    ///
+    /// スライドで登場, 退場するときに, 画面の外側へ向かう方向
+    ///
+    fn slide_direction(position: TachiePosition) -> f32 {
+        match position {
+            TachiePosition::Left => -1.0,
+            TachiePosition::InnerRight | TachiePosition::Right => 1.0,
+        }
     }
 
-    fn is_visible(&self) -> bool {
-        self.drwob_essential.visible
-    }
+    ///
+    /// 表示中の立ち絵を作り直さずに, 表情の差し替えや登場, 退場, 動きを行う
+    ///
+    pub fn apply_command<'a>(
+        &mut self,
+        ctx: &mut SuzuContext<'a>,
+        command: &TachieCommand,
+        t: Clock,
+    ) {
+        let position = command.position;
+
+        match command.action {
+            TachieAction::Expression(texture_id) => {
+                let replaced = if let Some(texture) = self.slot_mut(position).as_mut() {
+                    texture.replace_texture(ctx.ref_texture(texture_id));
+                    texture.set_scale(Self::tachie_texture_scale(texture_id));
+                    true
+                } else {
+                    false
+                };
 
-    fn set_drawing_depth(&mut self, depth: i8) {
-        self.drwob_essential.drawing_depth = depth;
-    }
+                if replaced {
+                    self.tachie_data.set(position, Some(texture_id));
+                }
+            }
+            TachieAction::Enter(texture_id, transition) => {
+                self.tachie_data.set(position, Some(texture_id));
+                let dest = self.rest_position(position);
+                let start = match transition {
+                    TachieTransition::Slide => numeric::Point2f::new(
+                        dest.x + (Self::slide_direction(position) * TACHIE_SLIDE_DISTANCE),
+                        dest.y,
+                    ),
+                    _ => dest,
+                };
 
-    fn get_drawing_depth(&self) -> i8 {
-        self.drwob_essential.drawing_depth
-    }
-}
+                let mut texture = SimpleObject::new(
+                    MovableUniTexture::new(
+                        Box::new(UniTexture::new(
+                            ctx.ref_texture(texture_id),
+                            start,
+                            Self::tachie_texture_scale(texture_id),
+                            0.0,
+                            0,
+                        )),
+                        None,
+                        t,
+                    ),
+                    Vec::new(),
+                );
+
+                if transition != TachieTransition::Cut {
+                    texture.set_alpha(0.0);
+                    texture.add_effect(vec![effect::fade_in(TACHIE_TRANSITION_TIME, t)]);
+                    self.animation_end = self.animation_end.max(t + TACHIE_TRANSITION_TIME);
+                }
+                if transition == TachieTransition::Slide {
+                    texture.override_move_func(move_fn::devide_distance(dest, 0.2), t);
+                }
 
-#[derive(Clone, Debug)]
-pub struct TachieData {
-    right: Option<TextureID>,
-    inner_right: Option<TextureID>,
-    left: Option<TextureID>,
-}
+                // 同じ位置に立ち絵が残っていれば, 入れ替わりで退場させる
+                if let Some(old) = self.slot_mut(position).replace(texture) {
+                    self.leave(old, position, transition, t);
+                }
+            }
+            TachieAction::Exit(transition) => {
+                self.tachie_data.set(position, None);
+                if let Some(old) = self.slot_mut(position).take() {
+                    self.leave(old, position, transition, t);
+                }
+            }
+            TachieAction::Motion(motion) => {
+                let base = self.rest_position(position);
+                let move_func = match motion {
+                    TachieMotion::Hop => move_fn::hop(base, 40.0, TACHIE_MOTION_TIME),
+                    TachieMotion::Shake => move_fn::shake(base, 24.0, TACHIE_MOTION_TIME),
+                    TachieMotion::Nod => move_fn::nod(base, 16.0, TACHIE_MOTION_TIME),
+                };
 
-impl TachieData {
-    pub fn new_empty() -> TachieData {
-        TachieData {
-            inner_right: None,
-            right: None,
-            left: None,
+                if let Some(texture) = self.slot_mut(position).as_mut() {
+                    texture.override_move_func(move_func, t);
+                } else {
+                    return;
+                }
+                self.animation_end = self.animation_end.max(t + TACHIE_MOTION_TIME);
+            }
         }
+
+        self.apply_highlight_color();
     }
 
-    pub fn is_none(&self) -> bool {
-        self.right.is_none() && self.left.is_none() && self.inner_right.is_none()
+    ///
+    /// 要素のtachie-dataを取り込む. 作り直さずに, 直前に取り込んだものから変わった位置だけを差し替える
+    ///
+    /// 変わっていない位置では, 命令による表情の差し替えや登場, 退場と, 途中の動きをそのまま残す
+    ///
+    pub fn merge_data<'a>(
+        &mut self,
+        ctx: &mut SuzuContext<'a>,
+        tachie_data: &TachieData,
+        t: Clock,
+    ) {
+        for position in [
+            TachiePosition::Left,
+            TachiePosition::InnerRight,
+            TachiePosition::Right,
+        ]
+        .iter()
+        {
+            let texture_id = tachie_data.get(*position);
+            if self.declared.get(*position) == texture_id {
+                continue;
+            }
+
+            let action = match texture_id {
+                Some(texture_id) if self.slot_mut(*position).is_some() => {
+                    TachieAction::Expression(texture_id)
+                }
+                Some(texture_id) => TachieAction::Enter(texture_id, TachieTransition::Cut),
+                None => TachieAction::Exit(TachieTransition::Cut),
+            };
+            self.apply_command(
+                ctx,
+                &TachieCommand {
+                    position: *position,
+                    action: action,
+                },
+                t,
+            );
+        }
+
+        self.declared = tachie_data.clone();
     }
-}
+
+    fn leave(
+        &mut self,
+        mut texture: SimpleObject,
+        position: TachiePosition,
+        transition: TachieTransition,
+        t: Clock,
+    ) {
+        if transition == TachieTransition::Cut {
+            return;
+        }
+
+        texture.add_effect(vec![effect::fade_out(TACHIE_TRANSITION_TIME, t)]);
+        if transition == TachieTransition::Slide {
+            texture.override_move_func(
+                move_fn::move_constant(numeric::Vector2f::new(
+                    Self::slide_direction(position) * TACHIE_SLIDE_DISTANCE
+                        / TACHIE_TRANSITION_TIME as f32,
+                    0.0,
+                )),
+                t,
+            );
+        }
+
+        let end = t + TACHIE_TRANSITION_TIME;
+        self.leaving.push((texture, end));
+        self.animation_end = self.animation_end.max(end);
+    }
+
+    ///
+    /// 登場, 退場, 動きを進める. 動いている間はtrueを返す
+    ///
+    pub fn update<'a>(&mut self, ctx: &mut SuzuContext<'a>, t: Clock) -> bool {
+        self.leaving.retain(|(_, end)| *end > t);
+
+        for texture in vec![
+            self.left.as_mut(),
+            self.inner_right.as_mut(),
+            self.right.as_mut(),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.leaving.iter_mut().map(|(texture, _)| texture))
+        {
+            texture.move_with_func(t);
+            texture.effect(ctx.context, t);
+        }
+
+        t <= self.animation_end
+    }
+
+    ///
+    /// 話者以外の立ち絵を暗くする. 変化があった場合はtrueを返す
+    ///
+    pub fn set_highlight(&mut self, highlight: TachieHighlight) -> bool {
+        if self.highlight == highlight {
+            return false;
+        }
+        self.highlight = highlight;
+        self.apply_highlight_color();
+
+        true
+    }
+
+    fn apply_highlight_color(&mut self) {
+        let highlight = self.highlight;
+
+        for (position, texture) in vec![
+            (TachiePosition::Left, self.left.as_mut()),
+            (TachiePosition::InnerRight, self.inner_right.as_mut()),
+            (TachiePosition::Right, self.right.as_mut()),
+        ] {
+            let texture = match texture {
+                Some(texture) => texture,
+                None => continue,
+            };
+
+            let bright = match highlight {
+                TachieHighlight::All => true,
+                TachieHighlight::Speaker(speaker) => speaker == position,
+                TachieHighlight::Nobody => false,
+            };
+
+            texture.set_color(ggraphics::Color::from_rgba_u32(if bright {
+                0xffffffff
+            } else {
+                TACHIE_DIMMED_COLOR
+            }));
+        }
+    }
+}
+
+impl DrawableComponent for ScenarioTachie {
+    fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        if self.drwob_essential.visible {
+            if let Some(texture) = self.left.as_mut() {
+                texture.draw(ctx)?;
+            }
+
+            if let Some(texture) = self.right.as_mut() {
+                texture.draw(ctx)?;
+            }
+
+            if let Some(texture) = self.inner_right.as_mut() {
+                texture.draw(ctx)?;
+            }
+
+            for (texture, _) in self.leaving.iter_mut() {
+                texture.draw(ctx)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn hide(&mut self) {
+        self.drwob_essential.visible = false;
+    }
+
+    fn appear(&mut self) {
+        self.drwob_essential.visible = true;
+    }
+
+    fn is_visible(&self) -> bool {
+        self.drwob_essential.visible
+    }
+
+    fn set_drawing_depth(&mut self, depth: i8) {
+        self.drwob_essential.drawing_depth = depth;
+    }
+
+    fn get_drawing_depth(&self) -> i8 {
+        self.drwob_essential.drawing_depth
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TachieData {
+    right: Option<TextureID>,
+    inner_right: Option<TextureID>,
+    left: Option<TextureID>,
+}
+
+impl TachieData {
+    pub fn new_empty() -> TachieData {
+        TachieData {
+            inner_right: None,
+            right: None,
+            left: None,
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.right.is_none() && self.left.is_none() && self.inner_right.is_none()
+    }
+
+    pub fn get(&self, position: TachiePosition) -> Option<TextureID> {
+        match position {
+            TachiePosition::Left => self.left,
+            TachiePosition::InnerRight => self.inner_right,
+            TachiePosition::Right => self.right,
+        }
+    }
+
+    pub fn set(&mut self, position: TachiePosition, texture_id: Option<TextureID>) {
+        match position {
+            TachiePosition::Left => self.left = texture_id,
+            TachiePosition::InnerRight => self.inner_right = texture_id,
+            TachiePosition::Right => self.right = texture_id,
+        }
+    }
+
+    ///
+    /// 話者に合わせた立ち絵の強調を求める
+    /// speaker_positionが指定されていれば, 名前よりもそちらを優先する
+    /// 立ち絵の人物の名前はgame_data.tomlのtachie_speakersから引く
+    ///
+    pub fn highlight_for_speaker(
+        &self,
+        resource: &GameResource,
+        speaker: Option<&str>,
+        speaker_position: Option<TachiePosition>,
+    ) -> TachieHighlight {
+        let speaker = match speaker {
+            Some(speaker) => speaker,
+            None => return TachieHighlight::All,
+        };
+
+        if let Some(position) = speaker_position {
+            return TachieHighlight::Speaker(position);
+        }
+
+        vec![
+            (TachiePosition::Left, self.left),
+            (TachiePosition::InnerRight, self.inner_right),
+            (TachiePosition::Right, self.right),
+        ]
+        .into_iter()
+        .find(|(_, id)| {
+            id.and_then(|id| resource.get_tachie_speaker(id))
+                .map_or(false, |name| speaker.contains(name))
+        })
+        .map_or(TachieHighlight::Nobody, |(position, _)| {
+            TachieHighlight::Speaker(position)
+        })
+    }
+
+    ///
+    /// シナリオ要素のtachie-dataテーブルを読み込む
+    /// tachie-dataが存在しない場合は, 立ち絵なしとして扱う
+    ///
+    pub fn from_scenario_element(toml_scripts: &TomlNode) -> Result<TachieData, SuzuError> {
+        let tachie_table = match toml_scripts.get_opt("tachie-data") {
+            Some(tachie_table) => tachie_table,
+            None => return Ok(TachieData::new_empty()),
+        };
+
+        let texture_id = |key: &str| -> Result<Option<TextureID>, SuzuError> {
+            match tachie_table.get_opt(key) {
+                Some(tid) => Ok(Some(texture_id_from_toml(&tid)?)),
+                None => Ok(None),
+            }
+        };
+
+        Ok(TachieData {
+            right: texture_id("right")?,
+            inner_right: texture_id("inner-right")?,
+            left: texture_id("left")?,
+        })
+    }
+}
+
+fn texture_id_from_toml(node: &TomlNode) -> Result<TextureID, SuzuError> {
+    let tid_str = node.as_str()?;
+    TextureID::from_str(tid_str).map_err(|_| node.invalid_value(tid_str))
+}
+
+///
+/// speaker-tachieに指定された, 話者の立ち絵の位置を読み込む
+///
+fn tachie_position_from_toml(toml_scripts: &TomlNode) -> Result<Option<TachiePosition>, SuzuError> {
+    match toml_scripts.get_opt("speaker-tachie") {
+        Some(position) => {
+            let position_str = position.as_str()?;
+            TachiePosition::from_str(position_str)
+                .map(Some)
+                .map_err(|_| position.invalid_value(position_str))
+        }
+        None => Ok(None),
+    }
+}
+
+fn background_from_toml(toml_scripts: &TomlNode) -> Result<Option<TextureID>, SuzuError> {
+    match toml_scripts.get_opt("background") {
+        Some(background) => Ok(Some(texture_id_from_toml(&background)?)),
+        None => Ok(None),
+    }
+}
 
 pub struct ScenarioText {
+    sources: Vec<ScenarioTextSource>,
+    font: ggraphics::Font,
     seq_text: Vec<ScenarioTextSegment>,
+    pauses: Vec<(usize, Clock)>,
+    pause_index: usize,
+    pause_remain: Clock,
+    voices: Vec<(usize, String)>,
+    voice_index: usize,
+    variables_expanded: bool,
+    commands_applied: bool,
     iterator: f32,
     current_segment_index: usize,
     total_length: usize,
@@ -313,84 +1050,160 @@ pub struct ScenarioText {
     next_scenario_id: ScenarioElementID,
     background_texture_id: Option<TextureID>,
     tachie_data: TachieData,
+    speaker: Option<String>,
+    speaker_position: Option<TachiePosition>,
+    tachie_commands: Vec<TachieCommand>,
+    sound_commands: Vec<ScenarioSoundCommand>,
 }
 
 impl ScenarioText {
-    pub fn new(toml_scripts: &toml::value::Value, game_data: &GameResource) -> Self {
-        let id = toml_scripts.get("id").unwrap().as_integer().unwrap() as i32;
-        let next_id = toml_scripts.get("next-id").unwrap().as_integer().unwrap() as i32;
-
-        let toml_default_attribute = toml_scripts
-            .get("default-text-attribute")
-            .unwrap()
-            .as_table()
-            .unwrap();
-        let default_font_scale = toml_default_attribute["font_scale"].as_float().unwrap() as f32;
-
-        let default = ScenarioTextAttribute {
-            fpc: toml_default_attribute["fpc"].as_float().unwrap() as f32,
-            font_info: FontInformation::new(
-                game_data.get_font(FontID::Cinema),
-                numeric::Vector2f::new(default_font_scale, default_font_scale),
-                ggraphics::Color::from_rgba_u32(
-                    toml_default_attribute["color"].as_integer().unwrap() as u32,
-                ),
-            ),
+    pub fn new(toml_scripts: &TomlNode, font: ggraphics::Font) -> Result<Self, SuzuError> {
+        let id = toml_scripts.get("id")?.as_integer()? as i32;
+        let next_id = toml_scripts.get("next-id")?.as_integer()? as i32;
+
+        let toml_default_attribute = toml_scripts.get("default-text-attribute")?;
+
+        let default = MarkupStyle {
+            fpc: toml_default_attribute.get("fpc")?.as_float()? as f32,
+            font_scale: toml_default_attribute.get("font_scale")?.as_float()? as f32,
+            color: toml_default_attribute.get("color")?.as_integer()? as u32,
         };
 
-        let mut seq_text = Vec::<ScenarioTextSegment>::new();
+        let mut sources = Vec::<ScenarioTextSource>::new();
 
-        for elem in toml_scripts.get("text").unwrap().as_array().unwrap() {
-            if let toml::Value::Table(scenario) = elem {
-                seq_text.push(ScenarioTextSegment::from_toml_using_default(
-                    scenario, game_data, &default,
-                ));
+        for elem in toml_scripts.get("text")?.as_array()? {
+            if let toml::Value::Table(_) = elem.value() {
+                sources.push(ScenarioTextSource::from_toml_using_default(
+                    &elem, &default,
+                )?);
             }
         }
 
-        let background_texture_id = match toml_scripts.get("background") {
-            Some(background) => Some(TextureID::from_str(background.as_str().unwrap()).unwrap()),
+        let background_texture_id = background_from_toml(toml_scripts)?;
+
+        let tachie_data = TachieData::from_scenario_element(toml_scripts)?;
+
+        let speaker = match toml_scripts.get_opt("speaker") {
+            Some(speaker) => Some(speaker.as_str()?.to_string()),
             None => None,
         };
 
-        let total_length: usize = seq_text.iter().fold(0, |sum, s| sum + s.str_len());
+        let mut scenario_text = ScenarioText::from_sources(
+            sources,
+            font,
+            id,
+            next_id,
+            background_texture_id,
+            tachie_data,
+            speaker,
+        );
+        scenario_text.speaker_position = tachie_position_from_toml(toml_scripts)?;
+        scenario_text.tachie_commands = tachie_commands_from_toml(toml_scripts)?;
+        scenario_text.sound_commands = sound_commands_from_toml(toml_scripts)?;
 
-        let tachie_data = if let Some(tachie_table) = toml_scripts.get("tachie-data") {
-            TachieData {
-                right: if let Some(tid) = tachie_table.get("right") {
-                    Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                } else {
-                    None
-                },
-                inner_right: if let Some(tid) = tachie_table.get("inner-right") {
-                    Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                } else {
-                    None
-                },
-                left: if let Some(tid) = tachie_table.get("left") {
-                    Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                } else {
-                    None
-                },
-            }
-        } else {
-            TachieData {
-                inner_right: None,
-                right: None,
-                left: None,
-            }
-        };
+        Ok(scenario_text)
+    }
 
-        ScenarioText {
-            seq_text: seq_text,
+    pub fn from_sources(
+        sources: Vec<ScenarioTextSource>,
+        font: ggraphics::Font,
+        scenario_id: ScenarioElementID,
+        next_scenario_id: ScenarioElementID,
+        background_texture_id: Option<TextureID>,
+        tachie_data: TachieData,
+        speaker: Option<String>,
+    ) -> Self {
+        let mut scenario_text = ScenarioText {
+            sources: sources,
+            font: font,
+            seq_text: Vec::new(),
+            pauses: Vec::new(),
+            pause_index: 0,
+            pause_remain: 0,
+            voices: Vec::new(),
+            voice_index: 0,
+            variables_expanded: false,
+            commands_applied: false,
             iterator: 0.0,
             current_segment_index: 0,
-            total_length: total_length,
-            scenario_id: id,
-            next_scenario_id: next_id,
+            total_length: 0,
+            scenario_id: scenario_id,
+            next_scenario_id: next_scenario_id,
             background_texture_id: background_texture_id,
             tachie_data: tachie_data,
+            speaker: speaker,
+            speaker_position: None,
+            tachie_commands: Vec::new(),
+            sound_commands: Vec::new(),
+        };
+
+        // 変数はまだ置き換えず, {name}のまま表示できる状態にしておく
+        scenario_text.build_segments(&|_: &str| None);
+        scenario_text
+    }
+
+    ///
+    /// インライン装飾を展開し, 表示に使うセグメントと停止位置を作り直す
+    ///
+    fn build_segments<F>(&mut self, lookup: &F)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut seq_text = Vec::new();
+        let mut pauses = Vec::new();
+        let mut voices = Vec::new();
+        let mut total_length = 0;
+
+        for source in self.sources.iter() {
+            if let Some(voice) = source.voice.as_ref() {
+                voices.push((total_length, voice.to_string()));
+            }
+
+            let runs = source.markup.build(source.style, lookup);
+
+            for (index, run) in runs.into_iter().enumerate() {
+                if run.pause > 0 {
+                    pauses.push((total_length, run.pause));
+                }
+
+                // テーブルの先頭は新しい行から, 装飾で分かれた部分は同じ行に続けて表示する
+                let segment = ScenarioTextSegment::from_markup_run(run, self.font, index > 0);
+                total_length += segment.str_len();
+                seq_text.push(segment);
+            }
         }
+
+        self.seq_text = seq_text;
+        self.pauses = pauses;
+        self.voices = voices;
+        self.total_length = total_length;
+    }
+
+    pub fn is_variables_expanded(&self) -> bool {
+        self.variables_expanded
+    }
+
+    ///
+    /// 音と立ち絵への命令を実行済みか. resetするまでは再度実行しない
+    ///
+    pub fn is_commands_applied(&self) -> bool {
+        self.commands_applied
+    }
+
+    pub fn mark_commands_applied(&mut self) {
+        self.commands_applied = true;
+    }
+
+    ///
+    /// テキスト中の{name}をlookupの結果で置き換える
+    /// 表示を始める前に呼び出す. resetするまでは再度置き換えない
+    ///
+    pub fn expand_variables<F>(&mut self, lookup: &F)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        self.build_segments(lookup);
+        self.variables_expanded = true;
     }
 
     fn current_iterator(&self) -> usize {
@@ -399,14 +1212,47 @@ impl ScenarioText {
 
     // 表示する文字数を更新する
     pub fn update_iterator(&mut self) {
+        // [wait=N]で止まっている間は文字数を進めない
+        if self.pause_remain > 0 {
+            self.pause_remain -= 1;
+            return;
+        }
+
         let current_segment = self.seq_text.get(self.current_segment_index).unwrap();
         self.iterator += current_segment.get_fpc();
 
+        if let Some((position, frames)) = self.pauses.get(self.pause_index) {
+            if self.iterator as usize >= *position {
+                self.iterator = *position as f32;
+                self.pause_remain = *frames;
+                self.pause_index += 1;
+            }
+        }
+
         if self.iterator as usize >= self.total_length {
             self.iterator = self.total_length as f32;
         }
     }
 
+    ///
+    /// 表示し始めたテキストのボイスを返す. 1つのボイスは一度だけ返す
+    /// 一度に複数のテキストを通り過ぎた場合は, 最後のものだけを返す
+    ///
+    pub fn take_started_voice(&mut self) -> Option<String> {
+        let mut started = None;
+
+        while let Some((position, voice)) = self.voices.get(self.voice_index) {
+            if self.iterator as usize >= *position {
+                started = Some(voice.to_string());
+                self.voice_index += 1;
+            } else {
+                break;
+            }
+        }
+
+        started
+    }
+
     pub fn reset_segment(&mut self) {
         self.current_segment_index = 0;
     }
@@ -419,6 +1265,17 @@ impl ScenarioText {
         self.iterator as usize == self.total_length
     }
 
+    ///
+    /// すべての文字を表示した状態にする
+    ///
+    pub fn finish_iterator(&mut self) {
+        self.iterator = self.total_length as f32;
+        self.current_segment_index = self.seq_text.len().saturating_sub(1);
+        self.pause_index = self.pauses.len();
+        self.pause_remain = 0;
+        self.voice_index = self.voices.len();
+    }
+
     pub fn seq_text_iter(&self) -> std::slice::Iter<ScenarioTextSegment> {
         self.seq_text.iter()
     }
@@ -427,9 +1284,18 @@ impl ScenarioText {
         self.scenario_id
     }
 
+    pub fn get_next_scenario_id(&self) -> ScenarioElementID {
+        self.next_scenario_id
+    }
+
     pub fn reset(&mut self) {
         self.iterator = 0.0;
         self.current_segment_index = 0;
+        self.pause_index = 0;
+        self.pause_remain = 0;
+        self.voice_index = 0;
+        self.variables_expanded = false;
+        self.commands_applied = false;
     }
 
     pub fn get_background_texture_id(&self) -> Option<TextureID> {
@@ -439,27 +1305,109 @@ impl ScenarioText {
     pub fn get_tachie_data(&self) -> TachieData {
         self.tachie_data.clone()
     }
+
+    pub fn get_speaker(&self) -> Option<&str> {
+        self.speaker.as_ref().map(|s| s.as_str())
+    }
+
+    ///
+    /// speaker-tachieで明示された話者の立ち絵の位置
+    ///
+    pub fn get_speaker_position(&self) -> Option<TachiePosition> {
+        self.speaker_position
+    }
+
+    pub fn get_tachie_commands(&self) -> &Vec<TachieCommand> {
+        &self.tachie_commands
+    }
+
+    pub fn get_sound_commands(&self) -> &Vec<ScenarioSoundCommand> {
+        &self.sound_commands
+    }
+
+    ///
+    /// すべてのテキストセグメントをつなげた文字列を返す
+    /// テキストボックスで新しい行から表示されるセグメントの前には改行を入れる
+    ///
+    pub fn get_full_text(&self) -> String {
+        let mut full_text = String::new();
+
+        for segment in self.seq_text.iter() {
+            if !segment.is_continued_line() && !full_text.is_empty() && !full_text.ends_with('\n')
+            {
+                full_text.push('\n');
+            }
+            full_text.push_str(segment.get_text());
+            if let Some(ruby) = segment.get_ruby() {
+                full_text.push_str(&format!("({})", ruby));
+            }
+        }
+
+        full_text
+    }
+}
+
+///
+/// switchで使用できるopecode
+/// conditionを使わない古い形式のswitchで使用する
+///
+pub const SWITCH_OPECODES: &[&str] = &["cleared"];
+
+///
+/// 条件式の文字列をStoryExprに変換する. 構文エラーの位置もエラーに含める
+///
+fn story_expr_from_toml(node: &TomlNode) -> Result<StoryExpr, SuzuError> {
+    let src = node.as_str()?;
+    StoryExpr::parse(src).map_err(|e| node.invalid_value(&format!("{} ({})", src, e)))
 }
 
 pub struct ScenarioSwitch {
-    opecode: String,
+    condition_src: String,
+    condition: StoryExpr,
     self_id: ScenarioElementID,
     yes_branch: ScenarioElementID,
     no_branch: ScenarioElementID,
 }
 
 impl ScenarioSwitch {
-    pub fn from_toml_object(toml_scripts: &toml::value::Value) -> Self {
-	ScenarioSwitch {
-	    self_id: toml_scripts["id"].as_integer().unwrap() as i32,
-	    opecode: toml_scripts["opecode"].as_str().unwrap().to_string(),
-	    yes_branch: toml_scripts["yes"].as_integer().unwrap() as i32,
-	    no_branch: toml_scripts["no"].as_integer().unwrap() as i32,
-	}
+    pub fn from_toml_object(toml_scripts: &TomlNode) -> Result<Self, SuzuError> {
+	let (condition_src, condition) = match toml_scripts.get_opt("condition") {
+	    Some(condition) => (
+		condition.as_str()?.to_string(),
+		story_expr_from_toml(&condition)?,
+	    ),
+	    None => {
+		let opecode = toml_scripts.get("opecode")?;
+		let opecode_str = opecode.as_str()?;
+		if !SWITCH_OPECODES.contains(&opecode_str) {
+		    return Err(opecode.invalid_value(opecode_str));
+		}
+		(opecode_str.to_string(), StoryExpr::Name(opecode_str.to_string()))
+	    }
+	};
+
+	Ok(ScenarioSwitch {
+	    self_id: toml_scripts.get("id")?.as_integer()? as i32,
+	    condition_src: condition_src,
+	    condition: condition,
+	    yes_branch: toml_scripts.get("yes")?.as_integer()? as i32,
+	    no_branch: toml_scripts.get("no")?.as_integer()? as i32,
+	})
     }
 
-    pub fn get_opecode(&self) -> &str {
-	self.opecode.as_str()
+    pub fn get_condition(&self) -> &str {
+	self.condition_src.as_str()
+    }
+
+    ///
+    /// 条件式を評価し, 次に進むScenarioElementIDを返す
+    ///
+    pub fn select_branch(&self, save_data: &SavableData) -> ScenarioElementID {
+	if self.condition.eval_bool(&|name| save_data.story_value(name)) {
+	    self.yes_branch
+	} else {
+	    self.no_branch
+	}
     }
 
     pub fn get_yes_branch(&self) -> ScenarioElementID {
@@ -475,9 +1423,390 @@ impl ScenarioSwitch {
     }
 }
 
+///
+/// # シナリオ変数を書き換えるScenarioElement
+///
+/// flagsはフラグの設定, variablesは変数への代入, incrementsは変数への加算を行う
+/// textsはテキストに埋め込む文字列の設定を行う
+///
+pub struct ScenarioSetVariables {
+    scenario_id: ScenarioElementID,
+    next_id: ScenarioElementID,
+    flags: Vec<(String, bool)>,
+    variables: Vec<(String, i64)>,
+    increments: Vec<(String, i64)>,
+    texts: Vec<(String, String)>,
+}
+
+impl ScenarioSetVariables {
+    fn table_entries<T, F>(
+        toml_scripts: &TomlNode,
+        key: &str,
+        convert: F,
+    ) -> Result<Vec<(String, T)>, SuzuError>
+    where
+        F: Fn(&TomlNode) -> Result<T, SuzuError>,
+    {
+        let table = match toml_scripts.get_opt(key) {
+            Some(table) => table,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut entries = Vec::new();
+        for name in table.as_table()?.keys() {
+            entries.push((name.to_string(), convert(&table.get(name)?)?));
+        }
+
+        Ok(entries)
+    }
+
+    pub fn from_toml_object(toml_scripts: &TomlNode) -> Result<Self, SuzuError> {
+        Ok(ScenarioSetVariables {
+            scenario_id: toml_scripts.get("id")?.as_integer()? as i32,
+            next_id: toml_scripts.get("next-id")?.as_integer()? as i32,
+            flags: Self::table_entries(toml_scripts, "flags", |node| node.as_bool())?,
+            variables: Self::table_entries(toml_scripts, "variables", |node| node.as_integer())?,
+            increments: Self::table_entries(toml_scripts, "increments", |node| {
+                node.as_integer()
+            })?,
+            texts: Self::table_entries(toml_scripts, "texts", |node| {
+                node.as_str().map(|s| s.to_string())
+            })?,
+        })
+    }
+
+    pub fn apply(&self, story_variables: &mut StoryVariables) {
+        for (name, value) in self.flags.iter() {
+            story_variables.set_flag(name, *value);
+        }
+
+        for (name, value) in self.variables.iter() {
+            story_variables.set_variable(name, *value);
+        }
+
+        for (name, diff) in self.increments.iter() {
+            story_variables.add_variable(name, *diff);
+        }
+
+        for (name, text) in self.texts.iter() {
+            story_variables.set_text(name, text);
+        }
+    }
+
+    ///
+    /// "met_akyu = true, affinity += 1" のような変更内容の要約を返す
+    ///
+    pub fn summary(&self) -> String {
+        self.flags
+            .iter()
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .chain(
+                self.variables
+                    .iter()
+                    .map(|(name, value)| format!("{} = {}", name, value)),
+            )
+            .chain(
+                self.increments
+                    .iter()
+                    .map(|(name, diff)| format!("{} += {}", name, diff)),
+            )
+            .chain(
+                self.texts
+                    .iter()
+                    .map(|(name, text)| format!("{} = \"{}\"", name, text)),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn get_scenario_id(&self) -> ScenarioElementID {
+        self.scenario_id
+    }
+
+    pub fn get_next_id(&self) -> ScenarioElementID {
+        self.next_id
+    }
+}
+
+///
+/// # 画面効果の種類
+///
+/// FadeOutとTileCloseは画面を覆ったまま残り, 次のFadeInとTileOpenで元に戻る
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScreenEffectKind {
+    Shake(f32),
+    Flash(u32),
+    FadeOut(u32),
+    FadeIn,
+    TileClose(TileBatchTextureID),
+    TileOpen(TileBatchTextureID),
+    CutIn(TextureID, Clock),
+}
+
+///
+/// # 画面効果を実行するScenarioElement
+///
+/// durationは効果にかけるフレーム数
+/// waitがtrueのときは, 効果が終わるまで次のScenarioElementへ進まない
+///
+/// [[scenario-group]]
+/// type = "effect"
+/// id = 10
+/// next-id = 11
+/// effect = "shake" | "flash" | "fade-out" | "fade-in" | "tile-close" | "tile-open" | "cut-in"
+/// duration = 30
+/// amplitude = 16.0      # shake
+/// color = 0xffffff      # flash, fade-out. 省略するとflashは白, fade-outは黒
+/// tile = "Shoji"        # tile-close, tile-open
+/// texture = "Paper1"    # cut-in
+/// keep = 60             # cut-in. 表示したままにするフレーム数
+/// wait = true           # 省略するとtrue
+///
+pub struct ScenarioScreenEffect {
+    scenario_id: ScenarioElementID,
+    next_id: ScenarioElementID,
+    kind: ScreenEffectKind,
+    duration: Clock,
+    wait: bool,
+}
+
+impl ScenarioScreenEffect {
+    pub fn from_toml_object(toml_scripts: &TomlNode) -> Result<Self, SuzuError> {
+        let color = |default: u32| -> Result<u32, SuzuError> {
+            match toml_scripts.get_opt("color") {
+                Some(color) => Ok(color.as_integer()? as u32),
+                None => Ok(default),
+            }
+        };
+
+        let effect = toml_scripts.get("effect")?;
+        let kind = match effect.as_str()? {
+            "shake" => ScreenEffectKind::Shake(match toml_scripts.get_opt("amplitude") {
+                Some(amplitude) => amplitude.as_float()? as f32,
+                None => 16.0,
+            }),
+            "flash" => ScreenEffectKind::Flash(color(0xffffff)?),
+            "fade-out" => ScreenEffectKind::FadeOut(color(0x000000)?),
+            "fade-in" => ScreenEffectKind::FadeIn,
+            "tile-close" => {
+                ScreenEffectKind::TileClose(enum_from_toml(&toml_scripts.get("tile")?)?)
+            }
+            "tile-open" => ScreenEffectKind::TileOpen(enum_from_toml(&toml_scripts.get("tile")?)?),
+            "cut-in" => ScreenEffectKind::CutIn(
+                texture_id_from_toml(&toml_scripts.get("texture")?)?,
+                match toml_scripts.get_opt("keep") {
+                    Some(keep) => keep.as_integer()? as Clock,
+                    None => 60,
+                },
+            ),
+            s => return Err(effect.invalid_value(s)),
+        };
+
+        let wait = match toml_scripts.get_opt("wait") {
+            Some(wait) => wait.as_bool()?,
+            None => true,
+        };
+
+        Ok(ScenarioScreenEffect {
+            scenario_id: toml_scripts.get("id")?.as_integer()? as i32,
+            next_id: toml_scripts.get("next-id")?.as_integer()? as i32,
+            kind: kind,
+            duration: (toml_scripts.get("duration")?.as_integer()? as Clock).max(1),
+            wait: wait,
+        })
+    }
+
+    pub fn get_kind(&self) -> ScreenEffectKind {
+        self.kind
+    }
+
+    pub fn get_duration(&self) -> Clock {
+        self.duration
+    }
+
+    pub fn get_scenario_id(&self) -> ScenarioElementID {
+        self.scenario_id
+    }
+
+    pub fn get_next_id(&self) -> ScenarioElementID {
+        self.next_id
+    }
+}
+
+///
+/// # ScenarioEventの上に重ねて表示する画面効果
+///
+/// 揺れはScenarioEventのcanvasごと動かし, それ以外はcanvasの一番上に描画する
+///
+struct ScreenEffectLayer {
+    shake: Option<(Clock, Clock, f32)>,
+    color_panel: Option<DarkEffectPanel>,
+    color_panel_end: Option<Clock>,
+    tile_effect: Option<effect_object::ScreenTileEffect>,
+    tile_effect_end: Option<Clock>,
+    cut_in: Option<(BlackOutTexture, Clock)>,
+    animation_end: Clock,
+}
+
+impl ScreenEffectLayer {
+    fn new() -> Self {
+        ScreenEffectLayer {
+            shake: None,
+            color_panel: None,
+            color_panel_end: None,
+            tile_effect: None,
+            tile_effect_end: None,
+            cut_in: None,
+            animation_end: 0,
+        }
+    }
+
+    ///
+    /// 画面効果を開始し, 効果が終わる時刻を返す
+    ///
+    fn start<'a>(
+        &mut self,
+        ctx: &mut SuzuContext<'a>,
+        effect: &ScenarioScreenEffect,
+        rect: numeric::Rect,
+        t: Clock,
+    ) -> Clock {
+        let duration = effect.get_duration();
+        let mut end = t + duration;
+
+        match effect.get_kind() {
+            ScreenEffectKind::Shake(amplitude) => {
+                self.shake = Some((t, duration, amplitude));
+            }
+            ScreenEffectKind::Flash(rgb) => {
+                // 半分の時間で色に染め, 残りの時間で元に戻す
+                let mut panel = DarkEffectPanel::with_color(ctx.context, rect, rgb, t);
+                let half = (duration / 2).max(1);
+                panel.new_effect(half, t, 0, 255);
+                panel.new_effect(duration - half, t + half, 255, 0);
+                self.color_panel = Some(panel);
+                self.color_panel_end = Some(end);
+            }
+            ScreenEffectKind::FadeOut(rgb) => {
+                let mut panel = DarkEffectPanel::with_color(ctx.context, rect, rgb, t);
+                panel.new_effect(duration, t, 0, 255);
+                self.color_panel = Some(panel);
+                self.color_panel_end = None;
+            }
+            ScreenEffectKind::FadeIn => {
+                if let Some(panel) = self.color_panel.as_mut() {
+                    panel.new_effect(duration, t, 255, 0);
+                    self.color_panel_end = Some(end);
+                }
+            }
+            ScreenEffectKind::TileClose(tile) | ScreenEffectKind::TileOpen(tile) => {
+                let (effect_type, tile_effect_end) = match effect.get_kind() {
+                    ScreenEffectKind::TileClose(_) => {
+                        (effect_object::SceneTransitionEffectType::Close, None)
+                    }
+                    _ => (effect_object::SceneTransitionEffectType::Open, Some(end)),
+                };
+                self.tile_effect = Some(effect_object::ScreenTileEffect::new(
+                    ctx,
+                    tile,
+                    numeric::Rect::new(0.0, 0.0, rect.w, rect.h),
+                    duration,
+                    effect_type,
+                    effect_object::TilingEffectType::WholeTile,
+                    0,
+                    t,
+                ));
+                self.tile_effect_end = tile_effect_end;
+            }
+            ScreenEffectKind::CutIn(texture_id, keep) => {
+                let mut texture =
+                    BlackOutTexture::new(ctx, texture_id, numeric::Point2f::new(0.0, 0.0), 0, t);
+                texture.set_alpha(0.0);
+                texture.run_black_out(BlackOutParam::new(duration, keep, duration), t);
+                end = t + duration + keep + duration;
+                self.cut_in = Some((texture, end));
+            }
+        }
+
+        self.animation_end = self.animation_end.max(end);
+        end
+    }
+
+    ///
+    /// 画面効果を進める. 効果が動いている間はtrueを返す
+    ///
+    fn update<'a>(&mut self, ctx: &mut SuzuContext<'a>, t: Clock) -> bool {
+        if let Some(panel) = self.color_panel.as_mut() {
+            panel.run_effect(ctx, t);
+        }
+        if self.color_panel_end.map_or(false, |end| end < t) {
+            self.color_panel = None;
+            self.color_panel_end = None;
+        }
+
+        if let Some(tile_effect) = self.tile_effect.as_mut() {
+            tile_effect.effect(ctx.context, t);
+        }
+        if self.tile_effect_end.map_or(false, |end| end < t) {
+            self.tile_effect = None;
+            self.tile_effect_end = None;
+        }
+
+        if let Some((texture, end)) = self.cut_in.as_mut() {
+            texture.effect(ctx.context, t);
+            if *end < t {
+                self.cut_in = None;
+            }
+        }
+
+        if self.shake.map_or(false, |(start, duration, _)| start + duration <= t) {
+            self.shake = None;
+        }
+
+        t <= self.animation_end
+    }
+
+    ///
+    /// 揺れによるcanvasのずれ
+    ///
+    fn shake_offset(&self, t: Clock) -> numeric::Vector2f {
+        match self.shake {
+            Some((start, duration, amplitude)) if start <= t => {
+                let elapsed = (t - start) as f32;
+                let power = amplitude * (1.0 - (elapsed / duration as f32)).max(0.0);
+                numeric::Vector2f::new(power * (elapsed * 1.7).sin(), power * (elapsed * 2.3).cos())
+            }
+            _ => numeric::Vector2f::new(0.0, 0.0),
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        if let Some((texture, _)) = self.cut_in.as_mut() {
+            texture.draw(ctx)?;
+        }
+
+        if let Some(tile_effect) = self.tile_effect.as_mut() {
+            tile_effect.draw(ctx)?;
+        }
+
+        if let Some(panel) = self.color_panel.as_mut() {
+            panel.draw(ctx)?;
+        }
+
+        Ok(())
+    }
+}
+
 ///
 /// 選択肢のデータを保持する構造体
 ///
+/// time-limitを指定すると, そのフレーム数が経過した時点でtimeout-jump-idへ進む
+/// 時間切れはchoice-patternの数と同じインデックスの選択肢として記録する
+///
+/// time-limit = 300
+/// timeout-jump-id = 20
+///
 pub struct ChoicePatternData {
     header_text: String,
     text: Vec<String>,
@@ -485,67 +1814,65 @@ pub struct ChoicePatternData {
     scenario_id: ScenarioElementID,
     background_texture_id: Option<TextureID>,
     tachie_data: TachieData,
+    time_limit: Option<(Clock, ScenarioElementID)>,
 }
 
+///
+/// 時間切れになったときにバックログへ残す文字列
+///
+pub const CHOICE_TIMEOUT_PATTERN: &str = "(時間切れ)";
+
 impl ChoicePatternData {
-    pub fn from_toml_object(toml_scripts: &toml::value::Value, _: &GameResource) -> Self {
-        let id = toml_scripts.get("id").unwrap().as_integer().unwrap() as i32;
+    pub fn from_toml_object(toml_scripts: &TomlNode) -> Result<Self, SuzuError> {
+        let id = toml_scripts.get("id")?.as_integer()? as i32;
 
         let mut choice_pattern_array = Vec::new();
         let mut jump_scenario_array = Vec::new();
 
-        for elem in toml_scripts
-            .get("choice-pattern")
-            .unwrap()
-            .as_array()
-            .unwrap()
-        {
-            choice_pattern_array.push(elem.get("pattern").unwrap().as_str().unwrap().to_string());
-            jump_scenario_array
-                .push(elem.get("jump-id").unwrap().as_integer().unwrap() as ScenarioElementID);
+        for elem in toml_scripts.get("choice-pattern")?.as_array()? {
+            choice_pattern_array.push(elem.get("pattern")?.as_str()?.to_string());
+            jump_scenario_array.push(elem.get("jump-id")?.as_integer()? as ScenarioElementID);
         }
 
-        let background_texture_id = if let Some(background_tid_str) = toml_scripts.get("background")
-        {
-            Some(TextureID::from_str(background_tid_str.as_str().unwrap()).unwrap())
-        } else {
-            None
-        };
+        let background_texture_id = background_from_toml(toml_scripts)?;
+        let tachie_data = TachieData::from_scenario_element(toml_scripts)?;
 
-        let tachie_data = if let Some(tachie_table) = toml_scripts.get("tachie-data") {
-            TachieData {
-                right: if let Some(tid) = tachie_table.get("right") {
-                    Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                } else {
-                    None
-                },
-                inner_right: if let Some(tid) = tachie_table.get("inner-right") {
-                    Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                } else {
-                    None
-                },
-                left: if let Some(tid) = tachie_table.get("left") {
-                    Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                } else {
-                    None
-                },
-            }
-        } else {
-            TachieData::new_empty()
+        let time_limit = match toml_scripts.get_opt("time-limit") {
+            Some(limit) => Some((
+                limit.as_integer()?.max(1) as Clock,
+                toml_scripts.get("timeout-jump-id")?.as_integer()? as ScenarioElementID,
+            )),
+            None => None,
         };
 
-        ChoicePatternData {
-            header_text: toml_scripts
-                .get("header_text")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string(),
+        Ok(ChoicePatternData {
+            header_text: toml_scripts.get("header_text")?.as_str()?.to_string(),
             text: choice_pattern_array,
             jump_scenario_id: jump_scenario_array,
             scenario_id: id,
             background_texture_id: background_texture_id,
             tachie_data: tachie_data,
+            time_limit: time_limit,
+        })
+    }
+
+    ///
+    /// 制限時間と, 時間切れのときのジャンプ先
+    ///
+    pub fn get_time_limit(&self) -> Option<(Clock, ScenarioElementID)> {
+        self.time_limit
+    }
+
+    ///
+    /// select_indexの選択肢のジャンプ先. 選択肢の数と同じインデックスは時間切れとして扱う
+    ///
+    pub fn get_jump_id(&self, select_index: usize) -> Option<ScenarioElementID> {
+        match self.jump_scenario_id.get(select_index) {
+            Some(jump_id) => Some(*jump_id),
+            None if select_index == self.text.len() => {
+                self.time_limit.map(|(_, timeout_id)| timeout_id)
+            }
+            None => None,
         }
     }
 
@@ -553,12 +1880,66 @@ impl ChoicePatternData {
         self.scenario_id
     }
 
-    pub fn get_background_texture_id(&self) -> Option<TextureID> {
-        self.background_texture_id
+    pub fn get_background_texture_id(&self) -> Option<TextureID> {
+        self.background_texture_id
+    }
+
+    pub fn get_tachie_data(&self) -> TachieData {
+        self.tachie_data.clone()
+    }
+
+    pub fn get_header_text(&self) -> &str {
+        self.header_text.as_str()
+    }
+
+    ///
+    /// 選択肢の文字列と, 選択したときのジャンプ先のペアを返す
+    ///
+    pub fn get_choice_patterns(&self) -> Vec<(&str, ScenarioElementID)> {
+        self.text
+            .iter()
+            .map(|text| text.as_str())
+            .zip(self.jump_scenario_id.iter().copied())
+            .collect()
+    }
+}
+
+///
+/// # 選択肢の制限時間
+///
+/// 経過フレーム数は選択肢を更新したときだけ進める. 一時停止中やバックログを開いている間は進まない
+///
+#[derive(Debug, Clone, Copy)]
+pub struct ChoiceCountdown {
+    elapsed: Clock,
+    limit: Clock,
+}
+
+impl ChoiceCountdown {
+    pub fn new(limit: Clock) -> Self {
+        ChoiceCountdown {
+            elapsed: 0,
+            limit: limit,
+        }
+    }
+
+    ///
+    /// 1フレーム進める. 時間切れになった場合はtrueを返す
+    ///
+    pub fn tick(&mut self) -> bool {
+        if self.elapsed < self.limit {
+            self.elapsed += 1;
+        }
+
+        self.is_timeout()
+    }
+
+    pub fn remain(&self) -> Clock {
+        self.limit - self.elapsed
     }
 
-    pub fn get_tachie_data(&self) -> TachieData {
-        self.tachie_data.clone()
+    pub fn is_timeout(&self) -> bool {
+        self.elapsed >= self.limit
     }
 }
 
@@ -567,6 +1948,7 @@ pub struct ChoiceBox {
     choice_text: Vec<String>,
     panels: Vec<FramedButton>,
     selecting: Option<usize>,
+    countdown: Option<(Meter, ChoiceCountdown)>,
     canvas: SubScreen,
 }
 
@@ -615,6 +1997,7 @@ impl ChoiceBox {
             panels: panels,
             choice_text: choice_text,
             selecting: None,
+            countdown: None,
             canvas: SubScreen::new(
                 ctx.context,
                 numeric::Rect::new(pos_rect.x, pos_rect.y, width, pos_rect.h),
@@ -624,6 +2007,42 @@ impl ChoiceBox {
         }
     }
 
+    ///
+    /// 制限時間を設定し, 残り時間を表すバーを選択肢の下に表示する
+    ///
+    pub fn set_time_limit<'a>(&mut self, ctx: &mut SuzuContext<'a>, limit: Clock) {
+        let size = self.canvas.get_drawing_size(ctx.context);
+        let meter = Meter::new(
+            numeric::Point2f::new(10.0, size.y - 16.0),
+            numeric::Rect::new(0.0, 0.0, size.x - 20.0, 10.0),
+            ggraphics::Color::from_rgba_u32(0x3d2b1fff),
+            numeric::Rect::new(2.0, 2.0, size.x - 24.0, 6.0),
+            ggraphics::Color::from_rgba_u32(0x5a4a3aff),
+            ggraphics::Color::from_rgba_u32(0xd8554bff),
+            limit as f32,
+            limit as f32,
+        );
+        self.countdown = Some((meter, ChoiceCountdown::new(limit)));
+    }
+
+    ///
+    /// 残り時間を1フレーム減らし, バーを更新する. 時間切れになった場合はtrueを返す
+    ///
+    pub fn update_countdown(&mut self) -> bool {
+        match self.countdown.as_mut() {
+            Some((meter, countdown)) => {
+                let timeout = countdown.tick();
+                meter.add(countdown.remain() as f32 - meter.get_value());
+                timeout
+            }
+            None => false,
+        }
+    }
+
+    pub fn has_time_limit(&self) -> bool {
+        self.countdown.is_some()
+    }
+
     pub fn get_selecting_index(&self) -> Option<usize> {
         self.selecting.clone()
     }
@@ -663,6 +2082,10 @@ impl DrawableComponent for ChoiceBox {
                 panel.draw(ctx)?;
             }
 
+            if let Some((meter, _, _)) = self.countdown.as_mut() {
+                meter.draw(ctx)?;
+            }
+
             sub_screen::pop_screen(ctx);
             self.canvas.draw(ctx).unwrap();
         }
@@ -698,6 +2121,22 @@ impl TextureObject for ChoiceBox {
     impl_texture_object_for_wrapped! {canvas}
 }
 
+///
+/// waitで使用できるopecode
+/// ScenarioSceneのschedule_checkで処理される
+///
+pub const FINISH_AND_WAIT_OPECODES: &[&str] = &[
+    "ScheduleCheck",
+    "ShowSchedule",
+    "DisableTutorial",
+    "ShowAd",
+    "ShowMain",
+    "ShowAdAgency",
+    "NextDay",
+    "GoToEndRoll",
+    "ShowStatusScreen",
+];
+
 ///
 /// 選択肢のデータを保持する構造体
 ///
@@ -710,52 +2149,26 @@ pub struct ScenarioFinishAndWaitData {
 }
 
 impl ScenarioFinishAndWaitData {
-    pub fn from_toml_object(toml_scripts: &toml::value::Value, _: &GameResource) -> Self {
-        let id = toml_scripts.get("id").unwrap().as_integer().unwrap() as i32;
-        let next_id = toml_scripts.get("next-id").unwrap().as_integer().unwrap() as i32;
-        let opecode = toml_scripts
-            .get("opecode")
-            .unwrap()
-            .as_str()
-            .unwrap()
-            .to_string();
-
-        let background_texture_id = if let Some(background_tid_str) = toml_scripts.get("background")
-        {
-            Some(TextureID::from_str(background_tid_str.as_str().unwrap()).unwrap())
-        } else {
-            None
-        };
+    pub fn from_toml_object(toml_scripts: &TomlNode) -> Result<Self, SuzuError> {
+        let id = toml_scripts.get("id")?.as_integer()? as i32;
+        let next_id = toml_scripts.get("next-id")?.as_integer()? as i32;
+        let opecode_node = toml_scripts.get("opecode")?;
+        let opecode = opecode_node.as_str()?;
+        if !FINISH_AND_WAIT_OPECODES.contains(&opecode) {
+            return Err(opecode_node.invalid_value(opecode));
+        }
+        let opecode = opecode.to_string();
 
-        let tachie_data = if let Some(tachie_table) = toml_scripts.get("tachie-data") {
-            TachieData {
-                right: if let Some(tid) = tachie_table.get("right") {
-                    Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                } else {
-                    None
-                },
-                inner_right: if let Some(tid) = tachie_table.get("inner-right") {
-                    Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                } else {
-                    None
-                },
-                left: if let Some(tid) = tachie_table.get("left") {
-                    Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                } else {
-                    None
-                },
-            }
-        } else {
-            TachieData::new_empty()
-        };
+        let background_texture_id = background_from_toml(toml_scripts)?;
+        let tachie_data = TachieData::from_scenario_element(toml_scripts)?;
 
-        ScenarioFinishAndWaitData {
+        Ok(ScenarioFinishAndWaitData {
             scenario_id: id,
             next_id: next_id,
             background_texture_id: background_texture_id,
             tachie_data: tachie_data,
             opecode: opecode,
-        }
+        })
     }
 
     pub fn get_scenario_id(&self) -> ScenarioElementID {
@@ -796,51 +2209,16 @@ pub enum ScenarioBuiltinCommand {
 }
 
 impl ScenarioBuiltinCommand {
-    pub fn from_toml_object(toml_scripts: &toml::value::Value) -> Self {
-        match toml_scripts
-            .get("opecode")
-            .unwrap()
-            .as_str()
-            .expect("invalid ScenarioBuiltinCommand opecode format")
-        {
-            "StartSchedule" => {
-                let id = toml_scripts.get("id").unwrap().as_integer().unwrap() as i32;
-                let background_texture_id =
-                    if let Some(background_tid_str) = toml_scripts.get("background") {
-                        Some(TextureID::from_str(background_tid_str.as_str().unwrap()).unwrap())
-                    } else {
-                        None
-                    };
-
-                let tachie_data = if let Some(tachie_table) = toml_scripts.get("tachie-data") {
-                    TachieData {
-                        right: if let Some(tid) = tachie_table.get("right") {
-                            Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                        } else {
-                            None
-                        },
-                        inner_right: if let Some(tid) = tachie_table.get("inner-right") {
-                            Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                        } else {
-                            None
-                        },
-                        left: if let Some(tid) = tachie_table.get("left") {
-                            Some(TextureID::from_str(tid.as_str().unwrap()).unwrap())
-                        } else {
-                            None
-                        },
-                    }
-                } else {
-                    TachieData::new_empty()
-                };
-
-                Self::ScheduleStart(ScheduleStartEssential {
-                    scenario_id: id,
-                    background_texture_id: background_texture_id,
-                    tachie_data: tachie_data,
-                })
-            }
-            _ => panic!("Invalid ScenarioBuiltinCommand opecode"),
+    pub fn from_toml_object(toml_scripts: &TomlNode) -> Result<Self, SuzuError> {
+        let opecode = toml_scripts.get("opecode")?;
+
+        match opecode.as_str()? {
+            "StartSchedule" => Ok(Self::ScheduleStart(ScheduleStartEssential {
+                scenario_id: toml_scripts.get("id")?.as_integer()? as i32,
+                background_texture_id: background_from_toml(toml_scripts)?,
+                tachie_data: TachieData::from_scenario_element(toml_scripts)?,
+            })),
+            s => Err(opecode.invalid_value(s)),
         }
     }
 
@@ -871,6 +2249,16 @@ impl ScenarioBuiltinCommand {
 #[derive(Clone, Copy)]
 pub struct ScenarioTransitionData(SceneID, SceneTransition, ScenarioElementID);
 
+impl ScenarioTransitionData {
+    pub fn get_scene_id(&self) -> SceneID {
+        self.0
+    }
+
+    pub fn get_scenario_id(&self) -> ScenarioElementID {
+        self.2
+    }
+}
+
 pub enum ScenarioElement {
     Text(ScenarioText),
     ChoiceSwitch(ChoicePatternData),
@@ -878,9 +2266,69 @@ pub enum ScenarioElement {
     FinishAndWait(ScenarioFinishAndWaitData),
     BuiltinCommand(ScenarioBuiltinCommand),
     Switch(ScenarioSwitch),
+    SetVariables(ScenarioSetVariables),
+    ScreenEffect(ScenarioScreenEffect),
 }
 
 impl ScenarioElement {
+    ///
+    /// scenario-groupの要素を一つ読み込む
+    ///
+    pub fn from_toml(elem: &TomlNode, font: ggraphics::Font) -> Result<Self, SuzuError> {
+        let type_info = elem.get("type")?;
+        match type_info.as_str()? {
+            "scenario" => Ok(ScenarioElement::Text(ScenarioText::new(elem, font)?)),
+            "choice" => Ok(ScenarioElement::ChoiceSwitch(
+                ChoicePatternData::from_toml_object(elem)?,
+            )),
+            "wait" => Ok(ScenarioElement::FinishAndWait(
+                ScenarioFinishAndWaitData::from_toml_object(elem)?,
+            )),
+            "builtin" => Ok(ScenarioElement::BuiltinCommand(
+                ScenarioBuiltinCommand::from_toml_object(elem)?,
+            )),
+	    "switch" => Ok(ScenarioElement::Switch(ScenarioSwitch::from_toml_object(elem)?)),
+            "set" => Ok(ScenarioElement::SetVariables(
+                ScenarioSetVariables::from_toml_object(elem)?,
+            )),
+            "effect" => Ok(ScenarioElement::ScreenEffect(
+                ScenarioScreenEffect::from_toml_object(elem)?,
+            )),
+            s => Err(type_info.invalid_value(s)),
+        }
+    }
+
+    ///
+    /// この要素から進む先のIDと, それを指定している要素内のキーの組
+    ///
+    pub fn jump_targets(&self) -> Vec<(String, ScenarioElementID)> {
+        match self {
+            Self::Text(text) => vec![("next-id".to_string(), text.get_next_scenario_id())],
+            Self::ChoiceSwitch(choice) => {
+                let mut targets = choice
+                    .get_choice_patterns()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (_, jump_id))| {
+                        (format!("choice-pattern[{}].jump-id", index), *jump_id)
+                    })
+                    .collect::<Vec<_>>();
+                if let Some((_, timeout_id)) = choice.get_time_limit() {
+                    targets.push(("timeout-jump-id".to_string(), timeout_id));
+                }
+                targets
+            }
+            Self::FinishAndWait(data) => vec![("next-id".to_string(), data.get_next_id())],
+	    Self::Switch(switch) => vec![
+		("yes".to_string(), switch.get_yes_branch()),
+		("no".to_string(), switch.get_no_branch()),
+	    ],
+            Self::SetVariables(command) => vec![("next-id".to_string(), command.get_next_id())],
+            Self::ScreenEffect(effect) => vec![("next-id".to_string(), effect.get_next_id())],
+            Self::SceneTransition(_) | Self::BuiltinCommand(_) => Vec::new(),
+        }
+    }
+
     pub fn get_scenario_id(&self) -> ScenarioElementID {
         match self {
             Self::Text(text) => text.get_scenario_id(),
@@ -889,6 +2337,8 @@ impl ScenarioElement {
             Self::FinishAndWait(data) => data.get_scenario_id(),
             Self::BuiltinCommand(command) => command.get_scenario_id(),
 	    Self::Switch(switch) => switch.get_self_scenario_id(),
+            Self::SetVariables(command) => command.get_scenario_id(),
+            Self::ScreenEffect(effect) => effect.get_scenario_id(),
         }
     }
 
@@ -900,6 +2350,8 @@ impl ScenarioElement {
             Self::FinishAndWait(data) => data.get_background_texture_id(),
             Self::BuiltinCommand(command) => command.get_background_texture_id(),
 	    Self::Switch(_) => None,
+            Self::SetVariables(_) => None,
+            Self::ScreenEffect(_) => None,
         }
     }
 
@@ -911,6 +2363,8 @@ impl ScenarioElement {
             Self::FinishAndWait(data) => data.get_tachie_data(),
             Self::BuiltinCommand(command) => command.get_tachie_info(),
 	    Self::Switch(_) => TachieData::new_empty(),
+            Self::SetVariables(_) => TachieData::new_empty(),
+            Self::ScreenEffect(_) => TachieData::new_empty(),
         }
     }
 }
@@ -924,10 +2378,45 @@ impl ScenarioElementPool {
         ScenarioElementPool { pool: Vec::new() }
     }
 
+    ///
+    /// シナリオファイルのscenario-groupとscene-transitionからScenarioElementPoolを生成する
+    ///
+    pub fn from_toml(root: &TomlNode, font: ggraphics::Font) -> Result<Self, SuzuError> {
+        let mut scenario = ScenarioElementPool::new_empty();
+
+        for elem in root.get("scenario-group")?.as_array()? {
+            scenario.add(ScenarioElement::from_toml(&elem, font)?);
+        }
+
+        // シーン切り替えのScenarioElementをロード
+        let scene_transition = root.get("scene-transition")?;
+        scenario.add(ScenarioElement::SceneTransition(ScenarioTransitionData(
+            SceneID::Scenario,
+            SceneTransition::SwapTransition,
+            scene_transition.get("scenario")?.as_integer()? as i32,
+        )));
+        scenario.add(ScenarioElement::SceneTransition(ScenarioTransitionData(
+            SceneID::SuzunaShop,
+            SceneTransition::SwapTransition,
+            scene_transition.get("dream")?.as_integer()? as i32,
+        )));
+        scenario.add(ScenarioElement::SceneTransition(ScenarioTransitionData(
+            SceneID::Save,
+            SceneTransition::StackingTransition,
+            scene_transition.get("save")?.as_integer()? as i32,
+        )));
+
+        Ok(scenario)
+    }
+
     pub fn add(&mut self, elem: ScenarioElement) {
         self.pool.push(elem);
     }
 
+    pub fn iter(&self) -> std::slice::Iter<ScenarioElement> {
+        self.pool.iter()
+    }
+
     ///
     /// 次のScenarioElementIDから、ScenarioElementのインデックスを得るメソッド
     ///
@@ -955,6 +2444,7 @@ impl ScenarioElementPool {
 }
 
 pub struct Scenario {
+    file_path: String,
     scenario: ScenarioElementPool,
     element_id_stack: Vec<ScenarioElementID>,
     current_page: usize,
@@ -965,105 +2455,74 @@ impl Scenario {
 	ctx: &mut SuzuContext<'a>,
 	file_path: &str,
 	save_data: Option<&ScenarioSceneSaveData>,
-    ) -> Self {
-        let game_data = &ctx.resource;
-
-        let mut scenario = ScenarioElementPool::new_empty();
-
-        let root = if file_path == "/scenario/day_7_23.toml" {
-	    first_day_scenario
-		.parse::<toml::Value>()
-		.expect("Failed to parse toml file")
-	} else if file_path == "/scenario/time_attack_first.toml" {
-	    time_attack_first_day_scenario
-		.parse::<toml::Value>()
-		.expect("Failed to parse toml file")
-	} else if file_path == "/scenario/time_attack_default.toml" {
-	    time_attack_default_day_scenario
-		.parse::<toml::Value>()
-		.expect("Failed to parse toml file")
-	} else if file_path == "/scenario/time_attack_week_first.toml" {
-	    time_attack_week_first_scenario
-		.parse::<toml::Value>()
-		.expect("Failed to parse toml file")
-	} else if file_path ==  "/scenario/no_enough_hp.toml" {
-	    no_enough_hp_scenario
-		.parse::<toml::Value>()
-		.expect("Failed to parse toml file")	    
-	} else {
-	    parse_toml_file!(ctx.context, file_path)
-	};
+    ) -> Result<Self, SuzuError> {
+        let content =
+            crate::core::util::try_read_from_resources_as_string(ctx.context, file_path)?;
+        let mut root = parse_scenario_source(file_path, &content)?;
+        ctx.resource.translate(file_path, &mut root);
+        let root = TomlNode::root(file_path, &root);
 
         let first_scenario_id = 
 	    if let Some(save_data) = save_data {
 		println!("first id -> {}", save_data.scenario_id);
 		save_data.scenario_id as i64
 	    } else {
-		root["first-scenario-id"].as_integer().unwrap()
+		root.get("first-scenario-id")?.as_integer()?
 	    };
 
-        let array = root["scenario-group"].as_array().unwrap();
+        let scenario =
+            ScenarioElementPool::from_toml(&root, ctx.resource.get_font(FontID::Cinema))?;
 
-        for elem in array {
-            if let Some(type_info) = elem.get("type") {
-                match type_info.as_str().unwrap() {
-                    "scenario" => {
-                        scenario.add(ScenarioElement::Text(ScenarioText::new(elem, game_data)));
-                    }
-                    "choice" => {
-                        scenario.add(ScenarioElement::ChoiceSwitch(
-                            ChoicePatternData::from_toml_object(elem, game_data),
-                        ));
-                    }
-                    "wait" => {
-                        scenario.add(ScenarioElement::FinishAndWait(
-                            ScenarioFinishAndWaitData::from_toml_object(elem, game_data),
-                        ));
-                    }
-                    "builtin" => {
-                        scenario.add(ScenarioElement::BuiltinCommand(
-                            ScenarioBuiltinCommand::from_toml_object(elem),
-                        ));
-                    }
-		    "switch" => {
-			scenario.add(ScenarioElement::Switch(ScenarioSwitch::from_toml_object(elem)));
-		    }
-                    _ => eprintln!("Error"),
-                }
-            } else {
-                eprintln!("Error");
-            }
-        }
+        let mut scenario = Scenario {
+            file_path: file_path.to_string(),
+            scenario: scenario,
+            element_id_stack: Vec::new(),
+            current_page: 0,
+        };
 
-        // シーン切り替えのScenarioElementをロード
-        let scene_transition = root["scene-transition"].as_table().unwrap();
-        scenario.add(ScenarioElement::SceneTransition(ScenarioTransitionData(
-            SceneID::Scenario,
-            SceneTransition::SwapTransition,
-            scene_transition
-                .get("scenario")
-                .unwrap()
-                .as_integer()
-                .unwrap() as i32,
+        scenario.update_current_page_index(first_scenario_id as ScenarioElementID);
+        Ok(scenario)
+    }
+
+    ///
+    /// シナリオの読み込みに失敗したときに, エラーの内容を表示してタイトルに戻るシナリオ
+    ///
+    pub fn new_error_notice<'a>(ctx: &mut SuzuContext<'a>, error: &SuzuError) -> Self {
+        let mut scenario = ScenarioElementPool::new_empty();
+
+        // エラーの内容に[や{が含まれていても, そのまま表示する
+        let text = ScenarioTextSource::new(
+            MarkupText::plain(&format!("シナリオの読み込みに失敗しました\n{}", error)),
+            MarkupStyle {
+                fpc: 2.0,
+                font_scale: 28.0,
+                color: 0x000000ff,
+            },
+        );
+
+        scenario.add(ScenarioElement::Text(ScenarioText::from_sources(
+            vec![text],
+            ctx.resource.get_font(FontID::Cinema),
+            1,
+            2,
+            None,
+            TachieData::new_empty(),
+            None,
         )));
         scenario.add(ScenarioElement::SceneTransition(ScenarioTransitionData(
-            SceneID::SuzunaShop,
+            SceneID::Title,
             SceneTransition::SwapTransition,
-            scene_transition.get("dream").unwrap().as_integer().unwrap() as i32,
-        )));
-        scenario.add(ScenarioElement::SceneTransition(ScenarioTransitionData(
-            SceneID::Save,
-            SceneTransition::StackingTransition,
-            scene_transition.get("save").unwrap().as_integer().unwrap() as i32,
+            2,
         )));
 
         let mut scenario = Scenario {
+            file_path: String::new(),
             scenario: scenario,
             element_id_stack: Vec::new(),
             current_page: 0,
         };
 
-        scenario.update_current_page_index(first_scenario_id as ScenarioElementID);
+        scenario.update_current_page_index(1);
         scenario
     }
 
@@ -1156,7 +2615,7 @@ impl Scenario {
         let next_id = match self.ref_current_element_mut() {
             ScenarioElement::ChoiceSwitch(obj) => {
                 // 選択中の選択肢のジャンプ先を取得する
-                *obj.jump_scenario_id.get(select_index).unwrap()
+                obj.get_jump_id(select_index).unwrap()
             }
             _ => {
                 panic!("Error: go_next_scenario_from_text_scenario");
@@ -1187,6 +2646,10 @@ impl Scenario {
         }
     }
 
+    pub fn get_file_path(&self) -> &str {
+        self.file_path.as_str()
+    }
+
     pub fn ref_current_element(&self) -> &ScenarioElement {
         self.scenario.seq_access(self.current_page).unwrap()
     }
@@ -1214,11 +2677,24 @@ pub enum TextBoxStatus {
     FixedText,
 }
 
+///
+/// テキストボックスの行を構成する文字列. rubyは文字列の上に表示する読み仮名
+///
+struct TextBoxPiece {
+    text: SimpleText,
+    ruby: Option<SimpleText>,
+}
+
+///
+/// テキストボックスの1行. 装飾ごとに分かれたTextBoxPieceを左から並べて表示する
+///
+type TextBoxLine = Vec<TextBoxPiece>;
+
 pub struct TextBox {
     box_lines: usize,
-    buffered_text: VecDeque<SimpleText>,
+    buffered_text: VecDeque<TextBoxLine>,
     head_line_number: u32,
-    text: VecDeque<SimpleText>,
+    text: VecDeque<TextBoxLine>,
     line_arrow: UniTexture,
     text_box_status: TextBoxStatus,
     appearance_frame: TileBatchFrame,
@@ -1294,29 +2770,106 @@ impl TextBox {
         sub_screen::pop_screen(ctx.context);
     }
 
-    // ScenarioTextSegmentを改行で分割しVec<SimpleText>に変換する
-    pub fn text_from_segment(segment: &ScenarioTextSegment, length: usize) -> Vec<SimpleText> {
-        let mut text_lines = Vec::new();
-
-        for line in segment.slice(length).lines() {
-            text_lines.push(SimpleText::new(
-                tobj::MovableText::new(
-                    Box::new(tobj::UniText::new(
-                        line.to_string(),
-                        numeric::Point2f::new(0.0, 0.0),
-                        numeric::Vector2f::new(1.0, 1.0),
-                        0.0,
-                        0,
-                        segment.attribute.font_info,
-                    )),
-                    None,
+    fn new_simple_text(text: String, font_info: FontInformation) -> SimpleText {
+        SimpleText::new(
+            tobj::MovableText::new(
+                Box::new(tobj::UniText::new(
+                    text,
+                    numeric::Point2f::new(0.0, 0.0),
+                    numeric::Vector2f::new(1.0, 1.0),
+                    0.0,
                     0,
-                ),
-                Vec::new(),
-            ));
+                    font_info,
+                )),
+                None,
+                0,
+            ),
+            Vec::new(),
+        )
+    }
+
+    // ScenarioTextSegmentを改行で分割し, 行ごとのSimpleTextに変換してlinesに追加する
+    // 前のセグメントに続けて表示するセグメントは, 最初の行を最後の行の右側に追加する
+    // line_openは, 最後の行が改行で終わっておらず, 続けて追加できるかを表す
+    fn push_segment_lines(
+        lines: &mut VecDeque<TextBoxLine>,
+        line_open: &mut bool,
+        segment: &ScenarioTextSegment,
+        length: usize,
+    ) {
+        let sliced = segment.slice(length);
+        let pieces = sliced.split('\n').collect::<Vec<&str>>();
+        let last_index = pieces.len() - 1;
+
+        *line_open = true;
+
+        for (index, piece) in pieces.into_iter().enumerate() {
+            let piece = piece.trim_end_matches('\r');
+
+            // 改行で終わっている場合は, 次のセグメントを新しい行から表示する
+            if index > 0 && index == last_index && piece.is_empty() {
+                *line_open = false;
+                break;
+            }
+
+            let text = TextBoxPiece {
+                text: Self::new_simple_text(piece.to_string(), segment.attribute.font_info),
+                ruby: Self::ruby_from_segment(segment, piece),
+            };
+
+            match lines.back_mut() {
+                Some(line) if index == 0 && segment.is_continued_line() => line.push(text),
+                _ => lines.push_back(vec![text]),
+            }
+        }
+    }
+
+    // 読み仮名は, 表示した本文の文字数に合わせて少しずつ表示する
+    // 読み仮名のあるセグメントは改行を含まないので, shownはそのままセグメントの一部になる
+    fn ruby_from_segment(segment: &ScenarioTextSegment, shown: &str) -> Option<SimpleText> {
+        let ruby = segment.get_ruby()?;
+        let font_info = segment.get_ruby_font_info()?;
+
+        let base_len = segment.str_len().max(1);
+        let ruby_len = ruby.chars().count();
+        let shown_len = (ruby_len * shown.chars().count() + base_len - 1) / base_len;
+
+        Some(Self::new_simple_text(
+            ruby.chars().take(shown_len).collect(),
+            font_info,
+        ))
+    }
+
+    // 行の中の文字列を左から並べ, 下端を揃える. 読み仮名は本文の上に中央揃えで置く
+    // ruby_heightは読み仮名のために行の上に空ける高さ. 空けた分も含めた行の高さを返す
+    fn layout_line(
+        ctx: &mut ggez::Context,
+        line: &mut TextBoxLine,
+        pos: numeric::Point2f,
+        ruby_height: f32,
+    ) -> f32 {
+        let height = line.iter().fold(0.0, |height: f32, piece| {
+            height.max(piece.text.get_font_scale().y)
+        });
+
+        let mut x = pos.x;
+        for piece in line.iter_mut() {
+            let y = pos.y + ruby_height + height - piece.text.get_font_scale().y;
+            piece.text.set_position(numeric::Point2f::new(x, y));
+            let width = piece.text.get_drawing_area(ctx).w;
+
+            if let Some(ruby) = piece.ruby.as_mut() {
+                let ruby_width = ruby.get_drawing_area(ctx).w;
+                ruby.set_position(numeric::Point2f::new(
+                    x + (width - ruby_width) / 2.0,
+                    y - ruby.get_font_scale().y,
+                ));
+            }
+
+            x += width;
         }
 
-        text_lines
+        height + ruby_height
     }
 
     pub fn update_scenario_text<'a>(
@@ -1336,6 +2889,7 @@ impl TextBox {
         let mut segs: Vec<(i32, &ScenarioTextSegment)> = Vec::new();
 
         // buffered または textにpushされるテキストを保持するTextSegmentを取得
+        // まだ1文字も表示しないセグメントも, 表示速度を決めるために含める
         for seg in scenario.seq_text_iter() {
             // このテキストセグメントの文字数を取得
             let seg_str_len = seg.str_len() as i32;
@@ -1356,10 +2910,12 @@ impl TextBox {
         }
 
         let mut text_lines = VecDeque::new();
-        for (slice_len, seg) in segs.iter() {
-            for line in Self::text_from_segment(seg, *slice_len as usize) {
-                text_lines.push_back(line);
+        let mut line_open = false;
+        for (index, (slice_len, seg)) in segs.iter().enumerate() {
+            if index > 0 && *slice_len == 0 {
+                continue;
             }
+            Self::push_segment_lines(&mut text_lines, &mut line_open, seg, *slice_len as usize);
         }
 
         if !self.complete_and_wait_current_line {
@@ -1380,16 +2936,27 @@ impl TextBox {
 
         self.buffered_text = text_lines;
 
+        // 読み仮名を含むテキストは, 表示の途中で行の位置が変わらないよう, すべての行の上に読み仮名の高さを空ける
+        let ruby_height = scenario
+            .seq_text_iter()
+            .filter_map(|seg| seg.get_ruby_font_info())
+            .fold(0.0, |height: f32, font_info| height.max(font_info.scale.y));
+
         // ボックスに入ったSimpleTextの位置を設定
         let mut pos = numeric::Point2f::new(60.0, 60.0);
         for line in &mut self.text {
-            line.set_position(pos);
-            pos.y += line.get_font_scale().y;
+            pos.y += Self::layout_line(ctx.context, line, pos, ruby_height);
         }
 
         if self.text_box_status == TextBoxStatus::WaitNextLineKey || scenario.iterator_finish() {
             self.line_arrow.appear();
-            let last_text_drawing_area = self.text.back().unwrap().get_drawing_area(ctx.context);
+            let last_text_drawing_area = self
+                .text
+                .back()
+                .and_then(|line| line.last())
+                .unwrap()
+                .text
+                .get_drawing_area(ctx.context);
             let pos = numeric::Point2f::new(
                 last_text_drawing_area.x + last_text_drawing_area.w,
                 last_text_drawing_area.y,
@@ -1402,51 +2969,131 @@ impl TextBox {
 
     pub fn set_fixed_text(&mut self, text: String, font_info: FontInformation) {
         self.text.clear();
-        self.text.push_back(SimpleText::new(
-            tobj::MovableText::new(
-                Box::new(tobj::UniText::new(
-                    text,
-                    numeric::Point2f::new(60.0, 60.0),
-                    numeric::Vector2f::new(1.0, 1.0),
-                    0.0,
-                    0,
-                    font_info,
-                )),
-                None,
-                0,
-            ),
-            Vec::new(),
-        ));
+        let mut text = Self::new_simple_text(text, font_info);
+        text.set_position(numeric::Point2f::new(60.0, 60.0));
+        self.text.push_back(vec![TextBoxPiece {
+            text: text,
+            ruby: None,
+        }]);
 
         self.set_text_box_status(TextBoxStatus::FixedText);
     }
 
-    pub fn next_button_handler(&mut self) {
-        self.head_line_number += 1;
-        self.set_text_box_status(TextBoxStatus::UpdatingText);
-    }
+    pub fn next_button_handler(&mut self) {
+        self.head_line_number += 1;
+        self.set_text_box_status(TextBoxStatus::UpdatingText);
+    }
+
+    pub fn reset_head_line(&mut self) {
+        self.head_line_number = 0;
+    }
+
+    pub fn set_text_box_status(&mut self, status: TextBoxStatus) {
+        self.text_box_status = status;
+    }
+
+    ///
+    /// 改行待ちを解除し, テキストを更新する状態に戻す
+    ///
+    pub fn reset_line_waiting(&mut self) {
+        self.complete_and_wait_current_line = false;
+        self.line_arrow.hide();
+        self.set_text_box_status(TextBoxStatus::UpdatingText);
+    }
+}
+
+impl DrawableComponent for TextBox {
+    fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        if self.is_visible() {
+            sub_screen::stack_screen(ctx, &self.canvas);
+
+            self.const_canvas.draw(ctx)?;
+            for line in &mut self.text {
+                for piece in line.iter_mut() {
+                    piece.text.draw(ctx)?;
+                    if let Some(ruby) = piece.ruby.as_mut() {
+                        ruby.draw(ctx)?;
+                    }
+                }
+            }
+
+            self.line_arrow.draw(ctx)?;
+
+            sub_screen::pop_screen(ctx);
+            self.canvas.draw(ctx).unwrap();
+        }
+        Ok(())
+    }
+
+    fn hide(&mut self) {
+        self.canvas.hide()
+    }
+
+    fn appear(&mut self) {
+        self.canvas.appear()
+    }
+
+    fn is_visible(&self) -> bool {
+        self.canvas.is_visible()
+    }
+
+    fn set_drawing_depth(&mut self, depth: i8) {
+        self.canvas.set_drawing_depth(depth)
+    }
+
+    fn get_drawing_depth(&self) -> i8 {
+        self.canvas.get_drawing_depth()
+    }
+}
+
+///
+/// # テキストボックスの左上に表示する話者の名前
+///
+pub struct SpeakerNamePlate {
+    name: String,
+    text: UniText,
+    canvas: SubScreen,
+}
+
+impl SpeakerNamePlate {
+    pub fn new<'a>(ctx: &mut SuzuContext<'a>, name: &str, pos: numeric::Point2f) -> Self {
+        let mut text = UniText::new(
+            name.to_string(),
+            numeric::Point2f::new(20.0, 8.0),
+            numeric::Vector2f::new(1.0, 1.0),
+            0.0,
+            0,
+            FontInformation::new(
+                ctx.resource.get_font(FontID::Cinema),
+                numeric::Vector2f::new(28.0, 28.0),
+                ggraphics::Color::from_rgba_u32(0xffffffff),
+            ),
+        );
+        let text_area = text.get_drawing_area(ctx.context);
+        text.set_position(numeric::Point2f::new(20.0, (44.0 - text_area.h) / 2.0));
 
-    pub fn reset_head_line(&mut self) {
-        self.head_line_number = 0;
+        SpeakerNamePlate {
+            name: name.to_string(),
+            text: text,
+            canvas: SubScreen::new(
+                ctx.context,
+                numeric::Rect::new(pos.x, pos.y, text_area.w + 40.0, 44.0),
+                0,
+                ggraphics::Color::from_rgba_u32(0x4a3424e6),
+            ),
+        }
     }
 
-    pub fn set_text_box_status(&mut self, status: TextBoxStatus) {
-        self.text_box_status = status;
+    pub fn get_name(&self) -> &str {
+        &self.name
     }
 }
 
-impl DrawableComponent for TextBox {
+impl DrawableComponent for SpeakerNamePlate {
     fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
         if self.is_visible() {
             sub_screen::stack_screen(ctx, &self.canvas);
-
-            self.const_canvas.draw(ctx)?;
-            for d in &mut self.text {
-                d.draw(ctx)?;
-            }
-
-            self.line_arrow.draw(ctx)?;
-
+            self.text.draw(ctx)?;
             sub_screen::pop_screen(ctx);
             self.canvas.draw(ctx).unwrap();
         }
@@ -1477,6 +3124,8 @@ impl DrawableComponent for TextBox {
 pub struct ScenarioBox {
     pub text_box: TextBox,
     pub choice_box: Option<ChoiceBox>,
+    name_plate: Option<SpeakerNamePlate>,
+    name_plate_position: numeric::Point2f,
     drwob_essential: DrawableObjectEssential,
 }
 
@@ -1506,6 +3155,8 @@ impl ScenarioBox {
                 t,
             ),
             choice_box: None,
+            name_plate: None,
+            name_plate_position: numeric::Point2f::new(rect.x + 30.0, rect.y - 30.0),
             drwob_essential: DrawableObjectEssential::new(true, 0),
         }
     }
@@ -1514,6 +3165,20 @@ impl ScenarioBox {
         self.text_box.canvas.contains(point)
     }
 
+    ///
+    /// 話者の名前を表示する. Noneの場合は名前を消す. 表示が変わった場合はtrueを返す
+    ///
+    pub fn set_speaker<'a>(&mut self, ctx: &mut SuzuContext<'a>, speaker: Option<&str>) -> bool {
+        let current = self.name_plate.as_ref().map(|plate| plate.get_name());
+        if current == speaker {
+            return false;
+        }
+
+        let pos = self.name_plate_position;
+        self.name_plate = speaker.map(|name| SpeakerNamePlate::new(ctx, name, pos));
+        true
+    }
+
     // pub fn new_choice<'a>(
     //     ctx: &mut SuzuContext<'a>,
     //     rect: numeric::Rect,
@@ -1615,6 +3280,10 @@ impl DrawableComponent for ScenarioBox {
         if self.is_visible() {
             self.text_box.draw(ctx)?;
 
+            if let Some(name_plate) = self.name_plate.as_mut() {
+                name_plate.draw(ctx)?;
+            }
+
             if let Some(choice) = self.choice_box.as_mut() {
                 choice.draw(ctx)?;
             }
@@ -1653,6 +3322,19 @@ pub enum ScenarioEventStatus {
     BuiltinSwitch,
 }
 
+///
+/// # テキストの送り方
+///
+/// Autoは表示し終えてから一定時間後に, Skipは既読のテキストを即座に送る
+/// どちらも選択肢とwaitに到達するとManualに戻る
+///
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ScenarioAdvanceMode {
+    Manual,
+    Auto,
+    Skip,
+}
+
 pub struct ScenarioEvent {
     scenario: Scenario,
     scenario_box: ScenarioBox,
@@ -1665,6 +3347,19 @@ pub struct ScenarioEvent {
     appearance_frame: TileBatchFrame,
     redraw_request: DrawRequest,
     se_handlers: [Option<SoundHandler>; 1],
+    background_id: Option<TextureID>,
+    tachie_data: TachieData,
+    backlog: ScenarioBacklog,
+    backlog_window: Option<BacklogWindow>,
+    advance_mode: ScenarioAdvanceMode,
+    auto_wait_begin: Option<Clock>,
+    advance_mode_text: UniText,
+    event_clock: Clock,
+    bgm_fade_out: Option<BgmFadeOut>,
+    bgm_ducked: bool,
+    screen_effect: ScreenEffectLayer,
+    screen_effect_wait: Option<Clock>,
+    canvas_position: numeric::Point2f,
 }
 
 impl ScenarioEvent {
@@ -1676,7 +3371,13 @@ impl ScenarioEvent {
         hide_shadow: bool,
         t: Clock,
     ) -> Self {
-        let scenario = Scenario::new(ctx, file_path, save_data);
+        let scenario = match Scenario::new(ctx, file_path, save_data) {
+            Ok(scenario) => scenario,
+            Err(e) => {
+                eprintln!("{}", e);
+                Scenario::new_error_notice(ctx, &e)
+            }
+        };
 
         let event_background = if let Some(mut texture) =
             Self::update_event_background_sub(ctx, scenario.ref_current_element())
@@ -1688,6 +3389,8 @@ impl ScenarioEvent {
         };
 
         let event_tachie = Self::update_event_tachie_sub(ctx, scenario.ref_current_element(), t);
+        let background_id = scenario.ref_current_element().get_background_texture();
+        let tachie_data = scenario.ref_current_element().get_tachie_info();
 
         let mut appr_frame = TileBatchFrame::new(
             ctx.resource,
@@ -1700,6 +3403,20 @@ impl ScenarioEvent {
             appr_frame.hide();
         }
 
+        let mut advance_mode_text = UniText::new(
+            String::new(),
+            numeric::Point2f::new(rect.w - 200.0, 24.0),
+            numeric::Vector2f::new(1.0, 1.0),
+            0.0,
+            0,
+            FontInformation::new(
+                ctx.resource.get_font(FontID::Cinema),
+                numeric::Vector2f::new(28.0, 28.0),
+                ggraphics::Color::from_rgba_u32(0xffffffff),
+            ),
+        );
+        advance_mode_text.hide();
+
         ScenarioEvent {
             scenario: scenario,
             scenario_box: ScenarioBox::new(
@@ -1716,6 +3433,19 @@ impl ScenarioEvent {
             tachie: event_tachie,
             redraw_request: DrawRequest::InitDraw,
             se_handlers: [None],
+            background_id: background_id,
+            tachie_data: tachie_data,
+            backlog: ScenarioBacklog::new(),
+            backlog_window: None,
+            advance_mode: ScenarioAdvanceMode::Manual,
+            auto_wait_begin: None,
+            advance_mode_text: advance_mode_text,
+            event_clock: 0,
+            bgm_fade_out: None,
+            bgm_ducked: false,
+            screen_effect: ScreenEffectLayer::new(),
+            screen_effect_wait: None,
+            canvas_position: numeric::Point2f::new(rect.x, rect.y),
         }
     }
 
@@ -1727,7 +3457,13 @@ impl ScenarioEvent {
         t: Clock,
     ) {
 	let save_data = ctx.take_save_data_mut().get_scenario_save_data();
-        self.scenario = Scenario::new(ctx, scenario_path, save_data.as_ref());
+        self.scenario = match Scenario::new(ctx, scenario_path, save_data.as_ref()) {
+            Ok(scenario) => scenario,
+            Err(e) => {
+                eprintln!("{}", e);
+                Scenario::new_error_notice(ctx, &e)
+            }
+        };
         self.status = ScenarioEventStatus::Scenario;
         self.key_down_action1(ctx, None, t);
         self.update_text(ctx, Some(scno_ctx));
@@ -1763,6 +3499,7 @@ impl ScenarioEvent {
             let canvas_size = self.canvas.get_drawing_size(ctx.context);
             texture.fit_scale(ctx.context, canvas_size);
             self.background = Some(texture);
+            self.background_id = self.scenario.ref_current_element().get_background_texture();
             self.redraw_request = DrawRequest::Draw;
         }
     }
@@ -1786,14 +3523,153 @@ impl ScenarioEvent {
     pub fn update_event_tachie<'a>(&mut self, ctx: &mut SuzuContext<'a>, t: Clock) {
         // 現在のScenarioElementに立ち絵がある場合、立ち絵データを取り込み
         // そうでない場合は、何もしない
-        let scenario_tachie =
-            Self::update_event_tachie_sub(ctx, self.scenario.ref_current_element(), t);
-        if scenario_tachie.is_some() {
-            self.tachie = scenario_tachie;
+        let tachie_data = self.scenario.ref_current_element().get_tachie_info();
+        if tachie_data.is_none() {
+            return;
+        }
+
+        // 表示中の立ち絵は作り直さずに, 変わった位置だけを差し替える
+        match self.tachie.as_mut() {
+            Some(tachie) => tachie.merge_data(ctx, &tachie_data, t),
+            None => self.tachie = Some(ScenarioTachie::new(ctx, tachie_data, t)),
+        }
+
+        if let Some(tachie) = self.tachie.as_ref() {
+            self.tachie_data = tachie.get_tachie_data();
+        }
+        self.redraw_request = DrawRequest::Draw;
+    }
+
+    ///
+    /// 立ち絵, 音, 画面効果といった演出を進める. 毎フレーム, update_textの前に呼び出す
+    ///
+    pub fn update_staging<'a>(&mut self, ctx: &mut SuzuContext<'a>, t: Clock) {
+        self.update_tachie(ctx, t);
+        self.update_sound(ctx, t);
+        self.update_screen_effect(ctx, t);
+    }
+
+    fn update_screen_effect<'a>(&mut self, ctx: &mut SuzuContext<'a>, t: Clock) {
+        if self.screen_effect.update(ctx, t) {
+            let offset = self.screen_effect.shake_offset(t);
+            self.canvas.set_position(self.canvas_position + offset);
+
+            ctx.process_utility.redraw();
             self.redraw_request = DrawRequest::Draw;
         }
     }
 
+    ///
+    /// 立ち絵の登場, 退場, 動きを進める
+    ///
+    fn update_tachie<'a>(&mut self, ctx: &mut SuzuContext<'a>, t: Clock) {
+        self.event_clock = t;
+
+        if let Some(tachie) = self.tachie.as_mut() {
+            if tachie.update(ctx, t) {
+                ctx.process_utility.redraw();
+                self.redraw_request = DrawRequest::Draw;
+            }
+        }
+    }
+
+    ///
+    /// BGMのフェードアウトを進める
+    ///
+    fn update_sound<'a>(&mut self, ctx: &mut SuzuContext<'a>, t: Clock) {
+        self.event_clock = t;
+
+        if let Some(fade_out) = self.bgm_fade_out.as_ref() {
+            if !fade_out.update(ctx, t) {
+                self.bgm_fade_out = None;
+            }
+            return;
+        }
+
+        // ボイスを再生している間はBGMの音量を下げる
+        let voice_playing = ctx.is_voice_playing();
+        if voice_playing != self.bgm_ducked {
+            let ratio = if voice_playing { BGM_DUCKING_RATIO } else { 1.0 };
+            let volume = ratio * ctx.config.get_bgm_volume();
+            for sound_id in ctx.resource.playing_bgm_list() {
+                ctx.resource.set_bgm_volume(&sound_id, volume);
+            }
+            self.bgm_ducked = voice_playing;
+        }
+    }
+
+    fn apply_sound_command<'a>(
+        ctx: &mut SuzuContext<'a>,
+        bgm_fade_out: &mut Option<BgmFadeOut>,
+        command: &ScenarioSoundCommand,
+        t: Clock,
+    ) {
+        // 前のフェードアウトが残っていれば, 先に止めてしまう
+        if !matches!(command, ScenarioSoundCommand::PlaySe(_)) {
+            if let Some(fade_out) = bgm_fade_out.take() {
+                fade_out.finish(ctx);
+            }
+        }
+
+        match command {
+            ScenarioSoundCommand::PlayBgm(sound_id) => {
+                if ctx.resource.playing_bgm_list().contains(sound_id) {
+                    return;
+                }
+                ctx.resource.stop_all_bgm(ctx.context);
+                let volume = ctx.config.get_bgm_volume();
+                ctx.play_sound_as_bgm(
+                    sound_id.clone(),
+                    Some(SoundPlayFlags::new(10000, 1.0, true, volume)),
+                );
+            }
+            ScenarioSoundCommand::StopBgm(fade) => {
+                if *fade == 0 {
+                    ctx.resource.stop_all_bgm(ctx.context);
+                } else {
+                    *bgm_fade_out = Some(BgmFadeOut {
+                        from: ctx.resource.playing_bgm_list(),
+                        start: t,
+                        required_time: *fade,
+                    });
+                }
+            }
+            ScenarioSoundCommand::CrossfadeBgm(sound_id, fade) => {
+                let from = ctx
+                    .resource
+                    .playing_bgm_list()
+                    .into_iter()
+                    .filter(|playing| playing != sound_id)
+                    .collect();
+
+                // SoundPlayFlagsのフェードインはミリ秒で指定する
+                let volume = ctx.config.get_bgm_volume();
+                ctx.play_sound_as_bgm(
+                    sound_id.clone(),
+                    Some(SoundPlayFlags::new(
+                        ((*fade * 1000 / 60).max(1)) as _,
+                        1.0,
+                        true,
+                        volume,
+                    )),
+                );
+
+                *bgm_fade_out = Some(BgmFadeOut {
+                    from: from,
+                    start: t,
+                    required_time: (*fade).max(1),
+                });
+            }
+            ScenarioSoundCommand::PlaySe(sound_id) => {
+                let volume = ctx.config.get_se_volume();
+                ctx.play_sound_as_se(
+                    sound_id.clone(),
+                    Some(SoundPlayFlags::new(10, 1.0, false, volume)),
+                );
+            }
+        }
+    }
+
     pub fn scenario_control_mut(&mut self) -> &mut Scenario {
         self.redraw_request = DrawRequest::Draw;
         &mut self.scenario
@@ -1809,6 +3685,59 @@ impl ScenarioEvent {
     ) {
         match self.scenario.ref_current_element_mut() {
             ScenarioElement::Text(scenario_text) => {
+                // テキストを表示し始めるときに, 音と立ち絵への命令を一度だけ実行する
+                if !scenario_text.is_commands_applied() {
+                    for command in scenario_text.get_sound_commands().iter() {
+                        Self::apply_sound_command(
+                            ctx,
+                            &mut self.bgm_fade_out,
+                            command,
+                            self.event_clock,
+                        );
+                        // 新しく再生したBGMにも, ボイスによる音量の抑制をかけ直す
+                        self.bgm_ducked = false;
+                    }
+
+                    if !scenario_text.get_tachie_commands().is_empty() {
+                        let event_clock = self.event_clock;
+                        let tachie = self.tachie.get_or_insert_with(|| {
+                            ScenarioTachie::new(ctx, TachieData::new_empty(), event_clock)
+                        });
+                        for command in scenario_text.get_tachie_commands().iter() {
+                            tachie.apply_command(ctx, command, event_clock);
+                        }
+                        self.tachie_data = tachie.get_tachie_data();
+
+                        ctx.process_utility.redraw();
+                        self.redraw_request = DrawRequest::Draw;
+                    }
+
+                    scenario_text.mark_commands_applied();
+                }
+
+                // 話者の名前と, 話者に合わせた立ち絵の強調を更新する
+                let speaker = scenario_text.get_speaker();
+                let mut speaker_changed = self.scenario_box.set_speaker(ctx, speaker);
+                if let Some(tachie) = self.tachie.as_mut() {
+                    let highlight = self.tachie_data.highlight_for_speaker(
+                        ctx.resource,
+                        speaker,
+                        scenario_text.get_speaker_position(),
+                    );
+                    speaker_changed |= tachie.set_highlight(highlight);
+                }
+                if speaker_changed {
+                    ctx.process_utility.redraw();
+                    self.redraw_request = DrawRequest::Draw;
+                }
+
+                // 表示を始める前に, テキスト中の変数をセーブデータの値で置き換える
+                if !scenario_text.is_variables_expanded() {
+                    let save_data = ctx.savable_data.as_ref();
+                    scenario_text
+                        .expand_variables(&|name: &str| save_data.and_then(|s| s.story_text(name)));
+                }
+
                 if self.scenario_box.get_text_box_status() == TextBoxStatus::UpdatingText {
                     // 表示する文字数を更新
                     scenario_text.update_iterator();
@@ -1824,10 +3753,24 @@ impl ScenarioEvent {
                     ctx.process_utility.redraw();
                     self.redraw_request = DrawRequest::Draw;
                 }
+
+                // 表示し始めたテキストにボイスがあれば再生する. スキップ中は再生しない
+                if let Some(voice) = scenario_text.take_started_voice() {
+                    if self.advance_mode != ScenarioAdvanceMode::Skip {
+                        if let Err(e) = ctx.play_voice(&voice) {
+                            eprintln!("{}", e);
+                        }
+                    }
+                }
             }
             ScenarioElement::ChoiceSwitch(choice_pattern) => {
                 // ChoiceBoxが表示されていない場合、新しくオブジェクトを生成する
                 if !self.scenario_box.is_enable_choice_box() {
+                    self.scenario_box.set_speaker(ctx, None);
+                    if let Some(tachie) = self.tachie.as_mut() {
+                        tachie.set_highlight(TachieHighlight::All);
+                    }
+
                     let mut choice_box = ChoiceBox::new(
                         ctx,
                         numeric::Rect::new(400.0, 100.0, 1200.0, 150.0),
@@ -1840,6 +3783,9 @@ impl ScenarioEvent {
                         ctx.context,
                         numeric::Point2f::new(1326.0 / 2.0, scenario_box_p.y + 180.0),
                     );
+                    if let Some((limit, _)) = choice_pattern.get_time_limit() {
+                        choice_box.set_time_limit(ctx, limit);
+                    }
                     self.scenario_box.insert_choice_box(Some(choice_box));
 
                     // テキストボックスに選択肢の文字列を表示する
@@ -1855,6 +3801,20 @@ impl ScenarioEvent {
                     // 再描画要求
                     ctx.process_utility.redraw();
                     self.redraw_request = DrawRequest::Draw;
+                } else if let Some(choice_box) = self.scenario_box.choice_box.as_mut() {
+                    // 制限時間付きの選択肢は, 残り時間のバーを減らしていく
+                    // バックログを読んでいる間は選択肢を操作できないので, 時間を止めておく
+                    if choice_box.has_time_limit() && self.backlog_window.is_none() {
+                        let timeout = choice_box.update_countdown();
+                        let timeout_index = choice_pattern.text.len();
+
+                        ctx.process_utility.redraw();
+                        self.redraw_request = DrawRequest::Draw;
+
+                        if timeout {
+                            self.finish_choice(ctx, timeout_index);
+                        }
+                    }
                 }
             }
             ScenarioElement::SceneTransition(transition_data) => {
@@ -1879,17 +3839,7 @@ impl ScenarioEvent {
                 }
             },
 	    ScenarioElement::Switch(switch) => {
-		let next_id = match switch.get_opecode() {
-		    "cleared" => {
-			if ctx.take_save_data().game_cleared() {
-			    switch.get_yes_branch()
-			} else {
-			    switch.get_no_branch()
-			}
-		    },
-		    _ => panic!("Scenario Script BUG"),
-		};
-
+		let next_id = switch.select_branch(ctx.take_save_data());
 		self.scenario.update_current_page_index(next_id);
 		
 		// 次がシナリオなら初期化する
@@ -1901,7 +3851,105 @@ impl ScenarioEvent {
 		}
 		self.update_event_tachie(ctx, 0);
 	    }
+            ScenarioElement::SetVariables(command) => {
+                command.apply(&mut ctx.take_save_data_mut().story_variables);
+                let next_id = command.get_next_id();
+                self.scenario.update_current_page_index(next_id);
+
+                // 次がシナリオなら初期化する
+                match self.scenario.ref_current_element_mut() {
+                    ScenarioElement::Text(obj) => {
+                        obj.reset();
+                    }
+                    _ => (),
+                }
+                self.update_event_tachie(ctx, 0);
+            }
+            ScenarioElement::ScreenEffect(effect) => {
+                let t = self.event_clock;
+                match self.screen_effect_wait {
+                    None => {
+                        let rect = numeric::Rect::new(
+                            0.0,
+                            0.0,
+                            self.canvas.get_drawing_size(ctx.context).x,
+                            self.canvas.get_drawing_size(ctx.context).y,
+                        );
+                        let end = self.screen_effect.start(ctx, effect, rect, t);
+                        self.screen_effect_wait = Some(if effect.wait { end } else { t });
+                    }
+                    Some(end) if end <= t => {
+                        self.screen_effect_wait = None;
+                        let next_id = effect.get_next_id();
+                        self.scenario.update_current_page_index(next_id);
+
+                        // 次がシナリオなら初期化する
+                        match self.scenario.ref_current_element_mut() {
+                            ScenarioElement::Text(obj) => {
+                                obj.reset();
+                            }
+                            _ => (),
+                        }
+                        self.update_event_background(ctx);
+                        self.update_event_tachie(ctx, 0);
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    ///
+    /// 選んだ選択肢をシナリオファイルとScenarioElementIDごとにセーブデータへ記録する
+    /// バックログにも選択肢と選んだ結果を残す
+    ///
+    fn record_choice<'a>(&mut self, ctx: &mut SuzuContext<'a>, select_index: usize) {
+        let choice = match self.scenario.ref_current_element() {
+            ScenarioElement::ChoiceSwitch(choice) => choice,
+            _ => return,
+        };
+        let pattern = choice
+            .text
+            .get(select_index)
+            .map(|s| s.as_str())
+            .unwrap_or(CHOICE_TIMEOUT_PATTERN);
+
+        if let Some(save_data) = ctx.savable_data.as_mut() {
+            save_data.story_variables.record_choice(
+                self.scenario.get_file_path(),
+                choice.get_scenario_id(),
+                select_index,
+                pattern,
+            );
         }
+
+        self.backlog.push(BacklogEntry {
+            element_id: choice.get_scenario_id(),
+            speaker: None,
+            text: format!("{}\n→ {}", choice.get_header_text(), pattern),
+            background: self.background_id,
+            tachie: self.tachie_data.clone(),
+            highlight: TachieHighlight::All,
+        });
+    }
+
+    ///
+    /// 選択肢を選び終えて, 選んだ選択肢のジャンプ先へ進む
+    ///
+    fn finish_choice<'a>(&mut self, ctx: &mut SuzuContext<'a>, select_index: usize) {
+        self.record_choice(ctx, select_index);
+        self.scenario
+            .go_next_scenario_from_choice_scenario(select_index);
+        self.update_event_background(ctx);
+        self.update_event_tachie(ctx, 0);
+
+        self.scenario_box
+            .text_box
+            .set_text_box_status(TextBoxStatus::UpdatingText);
+
+        // choice_boxは消す
+        self.scenario_box.insert_choice_box(None);
+        self.redraw_request = DrawRequest::Draw;
     }
 
     pub fn contains_scenario_text_box(&self, point: numeric::Point2f) -> bool {
@@ -1971,6 +4019,22 @@ impl ScenarioEvent {
 
                 // 最後まで到達していた場合、新しいScenarioElementに遷移し、テキストボックスをリセット
                 if scenario_text.iterator_finish() {
+                    let element_id = scenario_text.get_scenario_id();
+                    self.backlog.push(BacklogEntry {
+                        element_id: element_id,
+                        speaker: scenario_text.get_speaker().map(|s| s.to_string()),
+                        text: scenario_text.get_full_text(),
+                        background: self.background_id,
+                        tachie: self.tachie_data.clone(),
+                        highlight: self.tachie_data.highlight_for_speaker(
+                            ctx.resource,
+                            scenario_text.get_speaker(),
+                            scenario_text.get_speaker_position(),
+                        ),
+                    });
+                    ctx.permanent_save_data
+                        .mark_scenario_read(self.scenario.get_file_path(), element_id);
+                    ctx.stop_voice();
                     self.scenario.go_next_scenario_from_text_scenario();
                     self.update_event_background(ctx);
                     self.update_event_tachie(ctx, 0);
@@ -2011,23 +4075,14 @@ impl ScenarioEvent {
                     return;
                 }
 
-                self.scenario
-                    .go_next_scenario_from_choice_scenario(maybe_index.unwrap());
-                self.update_event_background(ctx);
-                self.update_event_tachie(ctx, 0);
-
-                self.scenario_box
-                    .text_box
-                    .set_text_box_status(TextBoxStatus::UpdatingText);
-
-                // choice_boxは消す
-                self.scenario_box.insert_choice_box(None);
-                self.redraw_request = DrawRequest::Draw;
+                self.finish_choice(ctx, maybe_index.unwrap());
             }
             ScenarioElement::SceneTransition(_) => (),
             ScenarioElement::FinishAndWait(_) => (),
             ScenarioElement::BuiltinCommand(_) => (),
 	    ScenarioElement::Switch(_) => (),
+            ScenarioElement::SetVariables(_) => (),
+            ScenarioElement::ScreenEffect(_) => (),
         }
     }
 
@@ -2053,6 +4108,161 @@ impl ScenarioEvent {
         }
     }
 
+    pub fn is_backlog_opened(&self) -> bool {
+        self.backlog_window.is_some()
+    }
+
+    ///
+    /// バックログを開く. まだ履歴がない場合は何もしない
+    ///
+    pub fn open_backlog<'a>(&mut self, ctx: &mut SuzuContext<'a>, t: Clock) {
+        if self.backlog.is_empty() || self.backlog_window.is_some() {
+            return;
+        }
+
+        let size = self.canvas.get_drawing_size(ctx.context);
+        self.backlog_window = Some(BacklogWindow::new(
+            ctx,
+            numeric::Rect::new(0.0, 0.0, size.x, size.y),
+            &self.backlog,
+            t,
+        ));
+        self.redraw_request = DrawRequest::Draw;
+        ctx.process_utility.redraw();
+    }
+
+    pub fn close_backlog<'a>(&mut self, ctx: &mut SuzuContext<'a>) {
+        if self.backlog_window.take().is_some() {
+            self.redraw_request = DrawRequest::Draw;
+            ctx.process_utility.redraw();
+        }
+    }
+
+    ///
+    /// ホイールを上に回すとバックログを開き, 古い履歴へスクロールする
+    /// 最新の履歴より下へスクロールするとバックログを閉じる
+    ///
+    pub fn mouse_wheel_handler<'a>(&mut self, ctx: &mut SuzuContext<'a>, y: f32, t: Clock) {
+        if y == 0.0 {
+            return;
+        }
+
+        let diff = if y > 0.0 { -1 } else { 1 };
+
+        let scrolled = match self.backlog_window.as_mut() {
+            Some(window) => window.scroll(ctx, &self.backlog, diff, t),
+            None => {
+                if diff < 0 {
+                    self.open_backlog(ctx, t);
+                }
+                return;
+            }
+        };
+
+        if scrolled {
+            self.redraw_request = DrawRequest::Draw;
+            ctx.process_utility.redraw();
+        } else {
+            self.close_backlog(ctx);
+        }
+    }
+
+    pub fn get_advance_mode(&self) -> ScenarioAdvanceMode {
+        self.advance_mode
+    }
+
+    pub fn set_advance_mode<'a>(&mut self, ctx: &mut SuzuContext<'a>, mode: ScenarioAdvanceMode) {
+        self.advance_mode = mode;
+        self.auto_wait_begin = None;
+
+        match mode {
+            ScenarioAdvanceMode::Manual => self.advance_mode_text.hide(),
+            ScenarioAdvanceMode::Auto => {
+                self.advance_mode_text.replace_text("オート".to_string());
+                self.advance_mode_text.appear();
+            }
+            ScenarioAdvanceMode::Skip => {
+                self.advance_mode_text.replace_text("スキップ".to_string());
+                self.advance_mode_text.appear();
+            }
+        }
+
+        self.redraw_request = DrawRequest::Draw;
+        ctx.process_utility.redraw();
+    }
+
+    ///
+    /// 指定したモードとManualを切り替える
+    ///
+    pub fn toggle_advance_mode<'a>(&mut self, ctx: &mut SuzuContext<'a>, mode: ScenarioAdvanceMode) {
+        if self.advance_mode == mode {
+            self.set_advance_mode(ctx, ScenarioAdvanceMode::Manual);
+        } else {
+            self.set_advance_mode(ctx, mode);
+        }
+    }
+
+    ///
+    /// オートモードとスキップモードでテキストを送るメソッド
+    /// 毎フレーム, update_textの後に呼び出す
+    ///
+    pub fn update_advance_mode<'a>(&mut self, ctx: &mut SuzuContext<'a>, t: Clock) {
+        if self.advance_mode == ScenarioAdvanceMode::Manual || self.backlog_window.is_some() {
+            return;
+        }
+
+        let (element_id, waiting) = match self.scenario.ref_current_element() {
+            ScenarioElement::Text(scenario_text) => (
+                scenario_text.get_scenario_id(),
+                scenario_text.iterator_finish()
+                    || self.scenario_box.get_text_box_status() == TextBoxStatus::WaitNextLineKey,
+            ),
+            ScenarioElement::ChoiceSwitch(_) | ScenarioElement::FinishAndWait(_) => {
+                self.set_advance_mode(ctx, ScenarioAdvanceMode::Manual);
+                return;
+            }
+            _ => return,
+        };
+
+        match self.advance_mode {
+            ScenarioAdvanceMode::Skip => {
+                // 未読のテキストに到達したらスキップをやめる
+                if !ctx
+                    .permanent_save_data
+                    .is_scenario_read(self.scenario.get_file_path(), element_id)
+                {
+                    self.set_advance_mode(ctx, ScenarioAdvanceMode::Manual);
+                    return;
+                }
+
+                if let ScenarioElement::Text(scenario_text) = self.scenario.ref_current_element_mut()
+                {
+                    scenario_text.finish_iterator();
+                }
+                self.scenario_box.text_box.reset_line_waiting();
+                self.key_down_action1(ctx, None, t);
+            }
+            ScenarioAdvanceMode::Auto => {
+                // ボイスを再生している間は, 待ち時間を数え始めない
+                if !waiting || ctx.is_voice_playing() {
+                    self.auto_wait_begin = None;
+                    return;
+                }
+
+                match self.auto_wait_begin {
+                    None => self.auto_wait_begin = Some(t),
+                    Some(begin) => {
+                        if t - begin >= ctx.config.get_auto_advance_wait() {
+                            self.auto_wait_begin = None;
+                            self.key_down_action1(ctx, None, t);
+                        }
+                    }
+                }
+            }
+            ScenarioAdvanceMode::Manual => (),
+        }
+    }
+
     pub fn get_scenario_id_for_saving(&self) -> i32 {
 	let len = self.scenario.element_id_stack.len();
 	self.scenario.element_id_stack[len - 2 as usize]
@@ -2074,10 +4284,18 @@ impl DrawableComponent for ScenarioEvent {
                     tachie.draw(ctx)?;
                 }
 
-                self.scenario_box.draw(ctx)?;
+                if let Some(backlog_window) = self.backlog_window.as_mut() {
+                    backlog_window.draw(ctx)?;
+                } else {
+                    self.scenario_box.draw(ctx)?;
+                }
+
+                self.advance_mode_text.draw(ctx)?;
 
                 self.appearance_frame.draw(ctx)?;
 
+                self.screen_effect.draw(ctx)?;
+
                 sub_screen::pop_screen(ctx);
             }
             self.canvas.draw(ctx).unwrap();
@@ -2105,717 +4323,3 @@ impl DrawableComponent for ScenarioEvent {
         self.canvas.get_drawing_depth()
     }
 }
-
-const first_day_scenario: &str = "first-scenario-id = 6
-
-[scene-transition]
-scenario = 1
-dream = 2
-save = 4
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"エピソードとチュートリアルを短縮しますか？\"
-background = \"SightBackground1\"	
-id = 6
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"はい\"
-   jump-id = 7
-   [[scenario-group.choice-pattern]]
-   pattern = \"いいえ\"
-   jump-id = 8
-
-[[scenario-group]]
-type = \"wait\"
-id = 7
-next-id = 9
-opecode = \"DisableTutorial\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"scenario\"
-id = 8
-next-id = 201
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-   [[scenario-group.text]]
-   text = \"さてと、昨日の続きでも読もうかな。\"
-
-[[scenario-group]]
-type = \"scenario\"
-id = 201
-next-id = 202
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-left = \"NitoriTachieSunGlass\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-   [[scenario-group.text]]
-   text_src_path = \"./resources/scenario/7_23/1.txt\"
-
-[[scenario-group]]
-type = \"scenario\"
-id = 202
-next-id = 203
-background = \"TownBackground\"
-
-[scenario-group.tachie-data]
-left = \"NitoriTachieDefault\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-   [[scenario-group.text]]
-   text_src_path = \"./resources/scenario/7_23/2.txt\"
-
-[[scenario-group]]
-type = \"scenario\"
-id = 203
-next-id = 101
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-   [[scenario-group.text]]
-   text_src_path = \"./resources/scenario/7_23/3.txt\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 9
-next-id = 20
-opecode = \"ShowStatusScreen\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"scenario\"
-id = 10
-next-id = 101
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-   [[scenario-group.text]]
-   text_src_path = \"./resources/scenario/7_23/1.txt\"
-
-[[scenario-group]]
-type = \"scenario\"
-id = 101
-next-id = 11
-background = \"SightBackground1\"
-
-[[scenario-group.text]]
-text = \"\"\"
-
-ちょっと何張り切ってんのよ。
-
-あ、阿求じゃない。ちょっとカクカクシカジカで・・・。
-
-・・・
-
-ふーん。大金を稼がないといけないことは分かったけど大丈夫なの？
-
-まあ何回かお店の手伝いはしたことはあるから。
-
-どうも心配ネ。ここは私が稗田家に伝わる商売術を伝授するわよ。
-
-稗田家に商売術なんて伝わってるのかしら。
-
-\"\"\"
-
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-left = \"AkyuTachieDefault\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-[[scenario-group]]
-type = \"wait\"
-id = 11
-next-id = 12
-opecode = \"ShowStatusScreen\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 12
-next-id = 13
-opecode = \"ShowAd\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"scenario\"
-id = 13
-next-id = 14
-background = \"SightBackground1\"
-
-[[scenario-group.text]]
-text = \"\"\"
-
-まずこれネ。お店の評判は重要よ。お客さんがたくさん来てくれるかもしれないわ。
-もし悪評が広まれば商売上がったりネ。\"\"\"
-
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-inner-right = \"AkyuTachieDefault\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-[[scenario-group]]
-type = \"wait\"
-id = 14
-next-id = 15
-opecode = \"ShowAdAgency\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"scenario\"
-id = 15
-next-id = 16
-background = \"SightBackground1\"
-
-[[scenario-group.text]]
-text = \"\"\"
-
-次にこれ。人間の里で商売してるのはあなただけじゃない。
-みんな自分の店の宣伝をしようとしているわ。
-その手段として他のお店に宣伝をお願いすることもあるのよ。
-鈴奈庵も例外じゃないわ。
-鈴奈庵の評判が良ければ大きなお店から宣伝をお願いされるかも。
-宣伝をすることでもられるお金も立派なお給料よ。
-\"\"\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-inner-right = \"AkyuTachieDefault\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-[[scenario-group]]
-type = \"wait\"
-id = 16
-next-id = 17
-opecode = \"ShowSchedule\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"scenario\"
-id = 17
-next-id = 18
-background = \"SightBackground1\"
-
-[[scenario-group.text]]
-text = \"\"\"
-
-次はこれを見て。まだ今週の予定は決めてないみたいネ。
-週の始まりにはその週の予定を決めましょう。\"\"\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-inner-right = \"AkyuTachieDefault\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-[[scenario-group]]
-type = \"wait\"
-id = 18
-next-id = 19
-opecode = \"ShowMain\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"scenario\"
-id = 19
-next-id = 20
-background = \"SightBackground1\"
-
-[[scenario-group.text]]
-text = \"\"\"
-
-最後にこれ。今の所持金だったりお店の状態が分かるわ。
-これを参考にしながら計画的にお仕事を頑張りなさい。
-
-分かったような分からないような。
-
-習うより慣れろよ。それじゃ、また今度鈴奈庵に来るわネ。
-
-一体何しに来たんだ。\"\"\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-inner-right = \"AkyuTachieDefault\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-[[scenario-group]]
-type = \"wait\"
-id = 20
-next-id = 21
-opecode = \"ScheduleCheck\"
-background = \"SightBackground1\"
-
-	   [scenario-group.tachie-data]
-	   right = \"KosuzuTachie1\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 21
-next-id = 22
-opecode = \"ShowAd\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"宣伝の依頼もやらないとネ\"
-id = 22
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"完了\"
-   jump-id = 23
-
-[[scenario-group]]
-type = \"wait\"
-id = 23
-next-id = 24
-opecode = \"ShowAdAgency\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"宣伝の受注もしないとネ\"
-id = 24
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"完了\"
-   jump-id = 25
-
-[[scenario-group]]
-type = \"wait\"
-id = 25
-next-id = 26
-opecode = \"ShowMain\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"choice\"
-background = \"SightBackground1\"
-header_text = \"サァ準備ができたわよ\"
-id = 26
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"行動開始\"
-   jump-id = 27
-   [[scenario-group.choice-pattern]]
-   pattern = \"保存\"
-   jump-id = 4
-
-
-[[scenario-group]]
-type = \"builtin\"
-opecode = \"StartSchedule\"
-id = 27
-background = \"SightBackground1\"
-";
-
-const time_attack_first_day_scenario: &str = "first-scenario-id = 10
-
-[scene-transition]
-scenario = 1
-dream = 2
-save = 4
-
-[[scenario-group]]
-type = \"scenario\"
-id = 10
-next-id = 12
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-   [[scenario-group.text]]
-   text = \"\"\"*助言*
-熟練モードへようこそ。
-熟練モードでは通常モードとは異なり、60日間
-働くことになります。特に目標金額は無く、この期限の間
-にいくら稼げるかがテーマになります。
-かなり長時間かかると思われますので保存を忘れずに
-まったりお楽しみください。\"\"\"
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"サァ準備ができたわよ\"
-id = 11
-background = \"SightBackground1\"
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"行動開始\"
-   jump-id = 13
-   [[scenario-group.choice-pattern]]
-   pattern = \"保存\"
-   jump-id = 4
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"宣伝の依頼もやらないとネ\"
-id = 14
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"完了\"
-   jump-id = 18
-
-[[scenario-group]]
-type = \"wait\"
-id = 18
-next-id = 17
-opecode = \"ShowAdAgency\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"宣伝の受注もしないとネ\"
-id = 17
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"完了\"
-   jump-id = 16
-
-[[scenario-group]]
-type = \"wait\"
-id = 16
-next-id = 11
-opecode = \"ShowMain\"
-background = \"SightBackground1\"
-
-
-[[scenario-group]]
-type = \"builtin\"
-opecode = \"StartSchedule\"
-id = 13
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 12
-next-id = 20
-opecode = \"ShowStatusScreen\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 20
-next-id = 15
-opecode = \"ScheduleCheck\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 15
-next-id = 14
-opecode = \"ShowAd\"
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-";
-
-const time_attack_default_day_scenario: &str = "first-scenario-id = 10
-
-[scene-transition]
-scenario = 1
-dream = 2
-save = 4
-
-[[scenario-group]]
-type = \"scenario\"
-id = 10
-next-id = 12
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-   [[scenario-group.text]]
-   text = \"サァ、今日も働くわヨ\"
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"サァ準備ができたわよ\"
-id = 11
-background = \"SightBackground1\"
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"行動開始\"
-   jump-id = 13
-   [[scenario-group.choice-pattern]]
-   pattern = \"保存\"
-   jump-id = 4
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"宣伝の依頼もやらないとネ\"
-id = 14
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"完了\"
-   jump-id = 18
-
-[[scenario-group]]
-type = \"wait\"
-id = 18
-next-id = 17
-opecode = \"ShowAdAgency\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"宣伝の受注もしないとネ\"
-id = 17
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"完了\"
-   jump-id = 16
-
-[[scenario-group]]
-type = \"wait\"
-id = 16
-next-id = 11
-opecode = \"ShowMain\"
-background = \"SightBackground1\"
-
-
-[[scenario-group]]
-type = \"builtin\"
-opecode = \"StartSchedule\"
-id = 13
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 12
-next-id = 20
-opecode = \"ShowStatusScreen\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 20
-next-id = 14
-opecode = \"ShowAd\"
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-";
-
-const time_attack_week_first_scenario: &str = "first-scenario-id = 10
-
-[scene-transition]
-scenario = 1
-dream = 2
-save = 4
-
-[[scenario-group]]
-type = \"scenario\"
-id = 10
-next-id = 12
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-   [[scenario-group.text]]
-   text = \"サァ、今日も働くわヨ\"
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"サァ準備ができたわよ\"
-id = 11
-background = \"SightBackground1\"
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"行動開始\"
-   jump-id = 13
-   [[scenario-group.choice-pattern]]
-   pattern = \"保存\"
-   jump-id = 4
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"宣伝の依頼もやらないとネ\"
-id = 14
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"完了\"
-   jump-id = 18
-
-[[scenario-group]]
-type = \"wait\"
-id = 18
-next-id = 17
-opecode = \"ShowAdAgency\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"choice\"
-header_text = \"宣伝の受注もしないとネ\"
-id = 17
-   [scenario-group.tachie-data]
-   right = \"KosuzuTachie1\"
-
-   [[scenario-group.choice-pattern]]
-   pattern = \"完了\"
-   jump-id = 16
-
-[[scenario-group]]
-type = \"wait\"
-id = 16
-next-id = 11
-opecode = \"ShowMain\"
-background = \"SightBackground1\"
-
-
-[[scenario-group]]
-type = \"builtin\"
-opecode = \"StartSchedule\"
-id = 13
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 12
-next-id = 20
-opecode = \"ShowStatusScreen\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 20
-next-id = 15
-opecode = \"ScheduleCheck\"
-background = \"SightBackground1\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 15
-next-id = 14
-opecode = \"ShowAd\"
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-";
-
-const no_enough_hp_scenario: &str = "first-scenario-id = 10
-
-[scene-transition]
-scenario = 1
-dream = 2
-save = 4
-
-[[scenario-group]]
-type = \"scenario\"
-id = 10
-next-id = 11
-background = \"SightBackground1\"
-
-[scenario-group.tachie-data]
-right = \"KosuzuTachie1\"
-left = \"KosuzuTachie1\"
-
-   [scenario-group.default-text-attribute]
-   fpc = 2.0
-   font_scale = 32.0
-   color = 0x000000ff
-
-   [[scenario-group.text]]
-   text = \"なんか疲れちゃったカモ。今日はお休みにしましょ。\"
-
-[[scenario-group]]
-type = \"wait\"
-id = 11
-next-id = 12
-opecode = \"NextDay\"
-background = \"SightBackground1\"";
@@ -0,0 +1,279 @@
+use std::collections::VecDeque;
+
+use ggez::graphics as ggraphics;
+
+use torifune::core::Clock;
+use torifune::graphics::drawable::*;
+use torifune::graphics::object::sub_screen;
+use torifune::graphics::object::sub_screen::SubScreen;
+use torifune::graphics::object::*;
+use torifune::numeric;
+
+use crate::core::{FontID, SuzuContext, TextureID};
+
+use super::scenario::{ScenarioElementID, ScenarioTachie, TachieData, TachieHighlight};
+
+///
+/// # バックログに記録される1つのテキスト
+///
+/// 表示したときの背景と立ち絵, 話者に合わせた立ち絵の強調も一緒に記録する
+///
+#[derive(Clone)]
+pub struct BacklogEntry {
+    pub element_id: ScenarioElementID,
+    pub speaker: Option<String>,
+    pub text: String,
+    pub background: Option<TextureID>,
+    pub tachie: TachieData,
+    pub highlight: TachieHighlight,
+}
+
+const BACKLOG_CAPACITY: usize = 200;
+
+///
+/// # 現在のセッションで表示したテキストの履歴
+///
+/// 古いものから順に保持し, BACKLOG_CAPACITYを超えたら古いものから捨てる
+///
+pub struct ScenarioBacklog {
+    entries: VecDeque<BacklogEntry>,
+}
+
+impl ScenarioBacklog {
+    pub fn new() -> Self {
+        ScenarioBacklog {
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, entry: BacklogEntry) {
+        if self.entries.len() >= BACKLOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&BacklogEntry> {
+        self.entries.get(index)
+    }
+}
+
+const BACKLOG_PADDING: f32 = 60.0;
+const BACKLOG_LINE_HEIGHT: f32 = 36.0;
+const BACKLOG_ENTRY_MARGIN: f32 = 18.0;
+
+///
+/// # バックログを表示するウィンドウ
+///
+/// focusは画面の一番下に表示される履歴のインデックス
+/// focusの履歴を表示したときの背景と立ち絵を, ウィンドウの後ろに表示する
+///
+pub struct BacklogWindow {
+    focus: usize,
+    texts: Vec<UniText>,
+    preview_background: Option<UniTexture>,
+    preview_tachie: Option<ScenarioTachie>,
+    canvas: SubScreen,
+}
+
+impl BacklogWindow {
+    pub fn new<'a>(
+        ctx: &mut SuzuContext<'a>,
+        rect: numeric::Rect,
+        backlog: &ScenarioBacklog,
+        t: Clock,
+    ) -> Self {
+        let mut window = BacklogWindow {
+            focus: backlog.len().saturating_sub(1),
+            texts: Vec::new(),
+            preview_background: None,
+            preview_tachie: None,
+            canvas: SubScreen::new(
+                ctx.context,
+                rect,
+                0,
+                ggraphics::Color::from_rgba_u32(0x000000c8),
+            ),
+        };
+
+        window.update_contents(ctx, backlog, t);
+        window
+    }
+
+    ///
+    /// 履歴をスクロールする. 負の値で古い方へ, 正の値で新しい方へ移動する
+    /// 最新の履歴より先へスクロールした場合はfalseを返す
+    ///
+    pub fn scroll<'a>(
+        &mut self,
+        ctx: &mut SuzuContext<'a>,
+        backlog: &ScenarioBacklog,
+        diff: i32,
+        t: Clock,
+    ) -> bool {
+        let next_focus = self.focus as i32 + diff;
+
+        if next_focus >= backlog.len() as i32 {
+            return false;
+        }
+
+        let next_focus = next_focus.max(0) as usize;
+        if next_focus != self.focus {
+            self.focus = next_focus;
+            self.update_contents(ctx, backlog, t);
+        }
+
+        true
+    }
+
+    fn new_line_text(text: &str, pos: numeric::Point2f, font_info: FontInformation) -> UniText {
+        UniText::new(
+            text.to_string(),
+            pos,
+            numeric::Vector2f::new(1.0, 1.0),
+            0.0,
+            0,
+            font_info,
+        )
+    }
+
+    fn update_contents<'a>(
+        &mut self,
+        ctx: &mut SuzuContext<'a>,
+        backlog: &ScenarioBacklog,
+        t: Clock,
+    ) {
+        let font = ctx.resource.get_font(FontID::Cinema);
+        let speaker_font_info = FontInformation::new(
+            font,
+            numeric::Vector2f::new(26.0, 26.0),
+            ggraphics::Color::from_rgba_u32(0xffd27fff),
+        );
+        let text_font_info = FontInformation::new(
+            font,
+            numeric::Vector2f::new(28.0, 28.0),
+            ggraphics::Color::from_rgba_u32(0xffffffff),
+        );
+
+        let size = self.canvas.get_drawing_size(ctx.context);
+        let mut bottom = size.y - BACKLOG_PADDING;
+
+        self.texts.clear();
+        self.texts.push(Self::new_line_text(
+            "バックログ",
+            numeric::Point2f::new(BACKLOG_PADDING, 20.0),
+            speaker_font_info,
+        ));
+
+        // focusから古い方へ, 画面に収まるところまで下から詰めていく
+        for index in (0..=self.focus).rev() {
+            let entry = match backlog.get(index) {
+                Some(entry) => entry,
+                None => break,
+            };
+
+            let lines = entry.text.lines().collect::<Vec<&str>>();
+            let line_count = lines.len() + entry.speaker.iter().count();
+            let top = bottom - (line_count as f32 * BACKLOG_LINE_HEIGHT);
+
+            if top < BACKLOG_PADDING && index != self.focus {
+                break;
+            }
+
+            let mut pos = numeric::Point2f::new(BACKLOG_PADDING, top);
+            if let Some(speaker) = entry.speaker.as_ref() {
+                self.texts
+                    .push(Self::new_line_text(speaker, pos, speaker_font_info));
+                pos.y += BACKLOG_LINE_HEIGHT;
+            }
+
+            for line in lines {
+                self.texts.push(Self::new_line_text(
+                    line,
+                    numeric::Point2f::new(pos.x + 20.0, pos.y),
+                    text_font_info,
+                ));
+                pos.y += BACKLOG_LINE_HEIGHT;
+            }
+
+            bottom = top - BACKLOG_ENTRY_MARGIN;
+        }
+
+        let (background, tachie, highlight) = match backlog.get(self.focus) {
+            Some(entry) => (entry.background, entry.tachie.clone(), entry.highlight),
+            None => (None, TachieData::new_empty(), TachieHighlight::All),
+        };
+
+        self.preview_background = background.map(|texture_id| {
+            let mut texture = UniTexture::new(
+                ctx.ref_texture(texture_id),
+                numeric::Point2f::new(0.0, 0.0),
+                numeric::Vector2f::new(1.0, 1.0),
+                0.0,
+                0,
+            );
+            texture.fit_scale(ctx.context, size);
+            texture
+        });
+
+        self.preview_tachie = if tachie.is_none() {
+            None
+        } else {
+            let mut preview_tachie = ScenarioTachie::new(ctx, tachie, t);
+            preview_tachie.set_highlight(highlight);
+            Some(preview_tachie)
+        };
+    }
+}
+
+impl DrawableComponent for BacklogWindow {
+    fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult<()> {
+        if self.is_visible() {
+            if let Some(background) = self.preview_background.as_mut() {
+                background.draw(ctx)?;
+            }
+
+            if let Some(tachie) = self.preview_tachie.as_mut() {
+                tachie.draw(ctx)?;
+            }
+
+            sub_screen::stack_screen(ctx, &self.canvas);
+
+            for text in &mut self.texts {
+                text.draw(ctx)?;
+            }
+
+            sub_screen::pop_screen(ctx);
+            self.canvas.draw(ctx).unwrap();
+        }
+        Ok(())
+    }
+
+    fn hide(&mut self) {
+        self.canvas.hide()
+    }
+
+    fn appear(&mut self) {
+        self.canvas.appear()
+    }
+
+    fn is_visible(&self) -> bool {
+        self.canvas.is_visible()
+    }
+
+    fn set_drawing_depth(&mut self, depth: i8) {
+        self.canvas.set_drawing_depth(depth)
+    }
+
+    fn get_drawing_depth(&self) -> i8 {
+        self.canvas.get_drawing_depth()
+    }
+}
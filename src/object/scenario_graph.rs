@@ -0,0 +1,295 @@
+use super::scenario::{
+    ScenarioBuiltinCommand, ScenarioElement, ScenarioElementID, ScenarioElementPool,
+    CHOICE_TIMEOUT_PATTERN,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScenarioGraphFormat {
+    Dot,
+    Mermaid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NodeShape {
+    Text,
+    Choice,
+    Wait,
+    Switch,
+    Builtin,
+    SetVariables,
+    ScreenEffect,
+    Transition,
+}
+
+struct GraphNode {
+    id: ScenarioElementID,
+    label: String,
+    shape: NodeShape,
+}
+
+struct GraphEdge {
+    from: ScenarioElementID,
+    to: ScenarioElementID,
+    label: Option<String>,
+}
+
+///
+/// # シナリオの分岐構造を表すグラフ
+///
+/// ScenarioElementPoolから生成し, DOTまたはMermaid形式で出力する
+/// 選択肢の文字列とswitchの条件式は辺のラベルになる
+///
+pub struct ScenarioGraph {
+    first_id: Option<ScenarioElementID>,
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+const TEXT_SUMMARY_LENGTH: usize = 16;
+
+fn summarize_text(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or("");
+    let mut summary = line.chars().take(TEXT_SUMMARY_LENGTH).collect::<String>();
+
+    if line.chars().count() > TEXT_SUMMARY_LENGTH || text.trim().lines().count() > 1 {
+        summary.push('…');
+    }
+
+    summary
+}
+
+fn escape_label(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "'")
+        .replace('\n', " ")
+}
+
+fn negate_condition(condition: &str) -> String {
+    if condition
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+    {
+        format!("!{}", condition)
+    } else {
+        format!("!({})", condition)
+    }
+}
+
+impl ScenarioGraph {
+    pub fn from_pool(pool: &ScenarioElementPool, first_id: Option<ScenarioElementID>) -> Self {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+
+        for elem in pool.iter() {
+            let id = elem.get_scenario_id();
+
+            match elem {
+                ScenarioElement::Text(text) => {
+                    let summary = summarize_text(&text.get_full_text());
+                    nodes.push(GraphNode {
+                        id: id,
+                        label: format!("{}: {}", id, summary),
+                        shape: NodeShape::Text,
+                    });
+                    edges.push(GraphEdge {
+                        from: id,
+                        to: text.get_next_scenario_id(),
+                        label: None,
+                    });
+                }
+                ScenarioElement::ChoiceSwitch(choice) => {
+                    nodes.push(GraphNode {
+                        id: id,
+                        label: format!("{}: {}", id, choice.get_header_text()),
+                        shape: NodeShape::Choice,
+                    });
+                    for (pattern, jump_id) in choice.get_choice_patterns() {
+                        edges.push(GraphEdge {
+                            from: id,
+                            to: jump_id,
+                            label: Some(pattern.to_string()),
+                        });
+                    }
+                    if let Some((_, timeout_id)) = choice.get_time_limit() {
+                        edges.push(GraphEdge {
+                            from: id,
+                            to: timeout_id,
+                            label: Some(CHOICE_TIMEOUT_PATTERN.to_string()),
+                        });
+                    }
+                }
+                ScenarioElement::FinishAndWait(data) => {
+                    nodes.push(GraphNode {
+                        id: id,
+                        label: format!("{}: wait {}", id, data.get_opecode()),
+                        shape: NodeShape::Wait,
+                    });
+                    edges.push(GraphEdge {
+                        from: id,
+                        to: data.get_next_id(),
+                        label: None,
+                    });
+                }
+                ScenarioElement::Switch(switch) => {
+                    nodes.push(GraphNode {
+                        id: id,
+                        label: format!("{}: switch", id),
+                        shape: NodeShape::Switch,
+                    });
+                    edges.push(GraphEdge {
+                        from: id,
+                        to: switch.get_yes_branch(),
+                        label: Some(switch.get_condition().to_string()),
+                    });
+                    edges.push(GraphEdge {
+                        from: id,
+                        to: switch.get_no_branch(),
+                        label: Some(negate_condition(switch.get_condition())),
+                    });
+                }
+                ScenarioElement::BuiltinCommand(command) => {
+                    let name = match command {
+                        ScenarioBuiltinCommand::ScheduleStart(_) => "StartSchedule",
+                    };
+                    nodes.push(GraphNode {
+                        id: id,
+                        label: format!("{}: {}", id, name),
+                        shape: NodeShape::Builtin,
+                    });
+                }
+                ScenarioElement::SetVariables(command) => {
+                    nodes.push(GraphNode {
+                        id: id,
+                        label: format!("{}: set {}", id, command.summary()),
+                        shape: NodeShape::SetVariables,
+                    });
+                    edges.push(GraphEdge {
+                        from: id,
+                        to: command.get_next_id(),
+                        label: None,
+                    });
+                }
+                ScenarioElement::ScreenEffect(effect) => {
+                    nodes.push(GraphNode {
+                        id: id,
+                        label: format!(
+                            "{}: effect {:?} {}f",
+                            id,
+                            effect.get_kind(),
+                            effect.get_duration()
+                        ),
+                        shape: NodeShape::ScreenEffect,
+                    });
+                    edges.push(GraphEdge {
+                        from: id,
+                        to: effect.get_next_id(),
+                        label: None,
+                    });
+                }
+                ScenarioElement::SceneTransition(transition) => {
+                    nodes.push(GraphNode {
+                        id: id,
+                        label: format!("{}: -> {:?}", id, transition.get_scene_id()),
+                        shape: NodeShape::Transition,
+                    });
+                }
+            }
+        }
+
+        ScenarioGraph {
+            first_id: first_id,
+            nodes: nodes,
+            edges: edges,
+        }
+    }
+
+    pub fn export(&self, format: ScenarioGraphFormat) -> String {
+        match format {
+            ScenarioGraphFormat::Dot => self.to_dot(),
+            ScenarioGraphFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph scenario {\n    node [fontname=\"sans-serif\"];\n");
+
+        if let Some(first_id) = self.first_id {
+            out.push_str(&format!(
+                "    start [shape=point];\n    start -> n{};\n",
+                first_id
+            ));
+        }
+
+        for node in self.nodes.iter() {
+            let shape = match node.shape {
+                NodeShape::Text => "box",
+                NodeShape::Choice => "diamond",
+                NodeShape::Wait => "parallelogram",
+                NodeShape::Switch => "hexagon",
+                NodeShape::Builtin => "component",
+                NodeShape::SetVariables => "note",
+                NodeShape::ScreenEffect => "octagon",
+                NodeShape::Transition => "doubleoctagon",
+            };
+            out.push_str(&format!(
+                "    n{} [shape={}, label=\"{}\"];\n",
+                node.id,
+                shape,
+                escape_label(&node.label)
+            ));
+        }
+
+        for edge in self.edges.iter() {
+            match edge.label.as_ref() {
+                Some(label) => out.push_str(&format!(
+                    "    n{} -> n{} [label=\"{}\"];\n",
+                    edge.from,
+                    edge.to,
+                    escape_label(label)
+                )),
+                None => out.push_str(&format!("    n{} -> n{};\n", edge.from, edge.to)),
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart TD\n");
+
+        if let Some(first_id) = self.first_id {
+            out.push_str(&format!("    start((start)) --> n{}\n", first_id));
+        }
+
+        for node in self.nodes.iter() {
+            let label = escape_label(&node.label);
+            let node_str = match node.shape {
+                NodeShape::Text => format!("n{}[\"{}\"]", node.id, label),
+                NodeShape::Choice => format!("n{}{{\"{}\"}}", node.id, label),
+                NodeShape::Wait => format!("n{}[/\"{}\"/]", node.id, label),
+                NodeShape::Switch => format!("n{}{{{{\"{}\"}}}}", node.id, label),
+                NodeShape::Builtin => format!("n{}[[\"{}\"]]", node.id, label),
+                NodeShape::SetVariables => format!("n{}[(\"{}\")]", node.id, label),
+                NodeShape::ScreenEffect => format!("n{}>\"{}\"]", node.id, label),
+                NodeShape::Transition => format!("n{}([\"{}\"])", node.id, label),
+            };
+            out.push_str(&format!("    {}\n", node_str));
+        }
+
+        for edge in self.edges.iter() {
+            match edge.label.as_ref() {
+                Some(label) => out.push_str(&format!(
+                    "    n{} -->|\"{}\"| n{}\n",
+                    edge.from,
+                    escape_label(label),
+                    edge.to
+                )),
+                None => out.push_str(&format!("    n{} --> n{}\n", edge.from, edge.to)),
+            }
+        }
+
+        out
+    }
+}
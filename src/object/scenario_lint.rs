@@ -0,0 +1,305 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::Path;
+
+use ggez::graphics as ggraphics;
+
+use crate::core::embedded::{embedded_resource, embedded_resource_paths};
+use crate::core::error::{parse_toml_str, TomlNode};
+use crate::core::texture_registry::register_textures_from_game_data;
+use crate::core::util::set_resource_directory;
+use crate::core::SuzuError;
+
+use super::scenario::{ScenarioElement, ScenarioElementID};
+use super::scenario_script::{is_scenario_script_path, parse_scenario_source};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintSeverity {
+    Error,
+    Warning,
+}
+
+///
+/// # シナリオの検査結果
+///
+/// messageはファイル名とキーの位置から始まる
+///
+#[derive(Debug, Clone)]
+pub struct LintDiagnostic {
+    pub severity: LintSeverity,
+    pub message: String,
+}
+
+impl fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.severity {
+            LintSeverity::Error => write!(f, "error: {}", self.message),
+            LintSeverity::Warning => write!(f, "warning: {}", self.message),
+        }
+    }
+}
+
+struct ScenarioLinter<'a> {
+    path: &'a str,
+    diagnostics: Vec<LintDiagnostic>,
+    defined_ids: HashMap<ScenarioElementID, String>,
+    transition_ids: HashSet<ScenarioElementID>,
+    edges: Vec<(ScenarioElementID, ScenarioElementID, String)>,
+}
+
+impl<'a> ScenarioLinter<'a> {
+    fn new(path: &'a str) -> Self {
+        ScenarioLinter {
+            path: path,
+            diagnostics: Vec::new(),
+            defined_ids: HashMap::new(),
+            transition_ids: HashSet::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    fn error(&mut self, message: String) {
+        self.diagnostics.push(LintDiagnostic {
+            severity: LintSeverity::Error,
+            message: message,
+        });
+    }
+
+    fn warning(&mut self, message: String) {
+        self.diagnostics.push(LintDiagnostic {
+            severity: LintSeverity::Warning,
+            message: message,
+        });
+    }
+
+    fn check<T>(&mut self, result: Result<T, SuzuError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.error(e.to_string());
+                None
+            }
+        }
+    }
+
+    fn define_id(&mut self, node: &TomlNode, id: ScenarioElementID) {
+        if let Some(first) = self.defined_ids.get(&id) {
+            let message = format!(
+                "{}: {} duplicates id {} (first defined at {})",
+                self.path,
+                node.key_path(),
+                id,
+                first
+            );
+            self.error(message);
+        } else {
+            self.defined_ids.insert(id, node.key_path().to_string());
+        }
+    }
+
+    ///
+    /// ゲーム本体と同じパーサで要素を読み込み, 進む先のIDを辺として記録する
+    ///
+    fn check_element(&mut self, elem: &TomlNode) {
+        match ScenarioElement::from_toml(elem, ggraphics::Font::default()) {
+            Ok(element) => {
+                let id = element.get_scenario_id();
+                self.define_id(elem, id);
+                for (key, target) in element.jump_targets() {
+                    self.edges
+                        .push((id, target, format!("{}.{}", elem.key_path(), key)));
+                }
+            }
+            Err(e) => {
+                self.error(e.to_string());
+                // 読み込めなかった要素へのジャンプを, 未定義のIDとして報告しないようにする
+                if let Some(id) = elem.get_opt("id").and_then(|node| node.as_integer().ok()) {
+                    self.define_id(elem, id as ScenarioElementID);
+                }
+            }
+        }
+    }
+
+    fn check_links(&mut self, first_id: Option<ScenarioElementID>) {
+        for (_, target, key) in self.edges.clone() {
+            if !self.defined_ids.contains_key(&target) {
+                let message = format!("{}: {} points to undefined id {}", self.path, key, target);
+                self.error(message);
+            }
+        }
+
+        let first_id = match first_id {
+            Some(first_id) => first_id,
+            None => return,
+        };
+
+        if !self.defined_ids.contains_key(&first_id) {
+            let message = format!(
+                "{}: first-scenario-id points to undefined id {}",
+                self.path, first_id
+            );
+            self.error(message);
+            return;
+        }
+
+        let mut reached = HashSet::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(first_id);
+
+        while let Some(id) = queue.pop_front() {
+            if !reached.insert(id) {
+                continue;
+            }
+
+            for (from, target, _) in self.edges.iter() {
+                if *from == id {
+                    queue.push_back(*target);
+                }
+            }
+        }
+
+        // シーン切り替えは, そのファイルが使わなくても必ず宣言されるので対象外
+        let mut unreached = self
+            .defined_ids
+            .iter()
+            .filter(|(id, _)| !reached.contains(*id) && !self.transition_ids.contains(*id))
+            .map(|(id, key)| (*id, key.clone()))
+            .collect::<Vec<_>>();
+        unreached.sort();
+
+        for (id, key) in unreached {
+            let message = format!(
+                "{}: {} (id {}) is unreachable from first-scenario-id",
+                self.path, key, id
+            );
+            self.warning(message);
+        }
+    }
+}
+
+///
+/// シナリオファイル1つ分の内容を検査する
+///
+pub fn lint_scenario_source(path: &str, content: &str) -> Vec<LintDiagnostic> {
+    let mut linter = ScenarioLinter::new(path);
+
+    let root = match linter.check(parse_scenario_source(path, content)) {
+        Some(root) => root,
+        None => return linter.diagnostics,
+    };
+    let root = TomlNode::root(path, &root);
+
+    let first_id = linter
+        .check(
+            root.get("first-scenario-id")
+                .and_then(|node| node.as_integer()),
+        )
+        .map(|id| id as ScenarioElementID);
+
+    if let Some(scene_transition) = linter.check(root.get("scene-transition")) {
+        for key in &["scenario", "dream", "save"] {
+            if let Some(node) = linter.check(scene_transition.get(key)) {
+                if let Some(id) = linter.check(node.as_integer()) {
+                    linter.define_id(&node, id as ScenarioElementID);
+                    linter.transition_ids.insert(id as ScenarioElementID);
+                }
+            }
+        }
+    }
+
+    let elements = linter.check(root.get("scenario-group").and_then(|node| node.as_array()));
+    for elem in elements.unwrap_or_default() {
+        linter.check_element(&elem);
+    }
+
+    linter.check_links(first_id);
+    linter.diagnostics
+}
+
+///
+/// scenario_table.tomlに記載されているシナリオと, scenarioディレクトリ以下の
+/// すべての.tomlファイルを検査する
+///
+pub fn lint_scenario_corpus(resource_dir: &Path) -> Vec<LintDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut paths = BTreeSet::new();
+
+    // text_src_pathなどのリソースのパスは, 実行時と同じく検査するディレクトリから解決する
+    set_resource_directory(resource_dir.to_path_buf());
+
+    // game_data.tomlだけで宣言されているテクスチャの名前も解決できるようにする
+    let game_data_path = resource_dir.join("game_data.toml");
+    if let Ok(content) = std::fs::read_to_string(&game_data_path) {
+        match parse_toml_str(&game_data_path.to_string_lossy(), &content) {
+            Ok(root) => register_textures_from_game_data(&root),
+            Err(e) => diagnostics.push(LintDiagnostic {
+                severity: LintSeverity::Error,
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    let table_path = resource_dir.join("scenario_table.toml");
+    let table_path_str = table_path.to_string_lossy().to_string();
+    match std::fs::read_to_string(&table_path) {
+        Ok(content) => match parse_toml_str(&table_path_str, &content) {
+            Ok(root) => {
+                for table_key in &["scenario-table", "general-scenario-table"] {
+                    if let Some(entries) = root.get(table_key).and_then(|v| v.as_array()) {
+                        for entry in entries {
+                            if let Some(path) = entry.get("path").and_then(|v| v.as_str()) {
+                                paths.insert(path.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => diagnostics.push(LintDiagnostic {
+                severity: LintSeverity::Error,
+                message: e.to_string(),
+            }),
+        },
+        Err(e) => diagnostics.push(LintDiagnostic {
+            severity: LintSeverity::Error,
+            message: SuzuError::io(&table_path_str, e).to_string(),
+        }),
+    }
+
+    if let Ok(entries) = std::fs::read_dir(resource_dir.join("scenario")) {
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if file_name.ends_with(".toml") || is_scenario_script_path(&file_name) {
+                paths.insert(format!("/scenario/{}", file_name));
+            }
+        }
+    }
+
+    paths.extend(
+        embedded_resource_paths()
+            .filter(|path| path.starts_with("/scenario/"))
+            .map(|path| path.to_string()),
+    );
+
+    for path in paths.iter() {
+        let content = match embedded_resource(path) {
+            Some(source) => source.to_string(),
+            None => {
+                let file_path = resource_dir.join(path.trim_start_matches('/'));
+                match std::fs::read_to_string(&file_path) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        diagnostics.push(LintDiagnostic {
+                            severity: LintSeverity::Error,
+                            message: SuzuError::io(path, e).to_string(),
+                        });
+                        continue;
+                    }
+                }
+            }
+        };
+
+        diagnostics.extend(lint_scenario_source(path, &content));
+    }
+
+    diagnostics
+}
@@ -0,0 +1,477 @@
+use std::collections::HashMap;
+
+use crate::core::error::parse_toml_str;
+use crate::core::SuzuError;
+
+///
+/// 簡易スクリプト形式のシナリオファイルの拡張子
+///
+pub const SCENARIO_SCRIPT_EXTENSION: &str = ".suzu";
+
+///
+/// シーン切り替えに割り当てるID. 既存のシナリオファイルと同じ値を使う
+///
+const SCENE_TRANSITION_LABELS: [(&str, i64); 3] = [("scenario", 1), ("dream", 2), ("save", 4)];
+
+///
+/// 自動で割り当てるIDの開始値
+///
+const FIRST_AUTO_ID: i64 = 100;
+
+const TACHIE_POSITION_KEYS: [&str; 3] = ["right", "inner-right", "left"];
+
+pub fn is_scenario_script_path(path: &str) -> bool {
+    path.ends_with(SCENARIO_SCRIPT_EXTENSION)
+}
+
+///
+/// パスの拡張子を見て, TOML形式かスクリプト形式かを切り替えて読み込む
+///
+/// どちらの場合もScenarioElementPool::from_tomlが読めるTOMLの値を返す
+///
+pub fn parse_scenario_source(path: &str, content: &str) -> Result<toml::Value, SuzuError> {
+    if is_scenario_script_path(path) {
+        compile_scenario_script(path, content)
+    } else {
+        parse_toml_str(path, content)
+    }
+}
+
+enum ScriptElementKind {
+    Text {
+        speaker: Option<String>,
+        lines: Vec<String>,
+        jump: Option<(String, usize)>,
+    },
+    Choice {
+        header: String,
+        patterns: Vec<(String, String, usize)>,
+    },
+}
+
+struct ScriptElement {
+    id: i64,
+    line: usize,
+    background: Option<String>,
+    tachie: Vec<(String, String)>,
+    kind: ScriptElementKind,
+}
+
+impl ScriptElement {
+    fn is_text(&self) -> bool {
+        match self.kind {
+            ScriptElementKind::Text { .. } => true,
+            ScriptElementKind::Choice { .. } => false,
+        }
+    }
+}
+
+struct ScriptCompiler<'a> {
+    path: &'a str,
+    elements: Vec<ScriptElement>,
+    current: Option<ScriptElement>,
+    labels: HashMap<String, i64>,
+    pending_labels: Vec<(String, usize)>,
+    background: Option<String>,
+    tachie: Vec<(String, String)>,
+    speaker: Option<String>,
+    next_id: i64,
+}
+
+impl<'a> ScriptCompiler<'a> {
+    fn new(path: &'a str) -> Self {
+        ScriptCompiler {
+            path: path,
+            elements: Vec::new(),
+            current: None,
+            labels: SCENE_TRANSITION_LABELS
+                .iter()
+                .map(|(label, id)| (label.to_string(), *id))
+                .collect(),
+            pending_labels: Vec::new(),
+            background: None,
+            tachie: Vec::new(),
+            speaker: None,
+            next_id: FIRST_AUTO_ID,
+        }
+    }
+
+    fn error<E: std::fmt::Display>(&self, line: usize, detail: E) -> SuzuError {
+        SuzuError::script_parse(self.path, line, detail)
+    }
+
+    fn flush(&mut self) {
+        if let Some(elem) = self.current.take() {
+            self.elements.push(elem);
+        }
+    }
+
+    ///
+    /// 新しい要素を開始する. 直前に書かれたラベルはこの要素を指す
+    ///
+    fn open(&mut self, line: usize, kind: ScriptElementKind) -> Result<(), SuzuError> {
+        self.flush();
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        for (label, label_line) in self.pending_labels.drain(..) {
+            if self.labels.insert(label.clone(), id).is_some() {
+                return Err(SuzuError::script_parse(
+                    self.path,
+                    label_line,
+                    format!("label *{} is defined twice", label),
+                ));
+            }
+        }
+
+        self.current = Some(ScriptElement {
+            id: id,
+            line: line,
+            background: self.background.clone(),
+            tachie: self.tachie.clone(),
+            kind: kind,
+        });
+        Ok(())
+    }
+
+    fn compile_line(&mut self, line: usize, src: &str) -> Result<(), SuzuError> {
+        let src = src.trim();
+
+        if src.is_empty() {
+            self.flush();
+        } else if src.starts_with('#') {
+            // コメント
+        } else if let Some(label) = src.strip_prefix('*') {
+            self.flush();
+            let label = label.trim();
+            if label.is_empty() {
+                return Err(self.error(line, "empty label"));
+            }
+            self.pending_labels.push((label.to_string(), line));
+        } else if let Some(speaker) = src.strip_prefix('@') {
+            self.flush();
+            let speaker = speaker.trim();
+            self.speaker = if speaker.is_empty() {
+                None
+            } else {
+                Some(speaker.to_string())
+            };
+        } else if let Some(command) = src.strip_prefix("[bg ").and_then(|s| s.strip_suffix(']')) {
+            self.flush();
+            let background = command.trim();
+            self.background = if background == "none" {
+                None
+            } else {
+                Some(background.to_string())
+            };
+        } else if let Some(command) = src
+            .strip_prefix("[tachie ")
+            .and_then(|s| s.strip_suffix(']'))
+        {
+            self.flush();
+            self.compile_tachie(line, command)?;
+        } else if let Some(header) = src.strip_prefix('?') {
+            self.open(
+                line,
+                ScriptElementKind::Choice {
+                    header: header.trim().to_string(),
+                    patterns: Vec::new(),
+                },
+            )?;
+        } else if let Some(choice) = src.strip_prefix('>') {
+            self.compile_choice(line, choice)?;
+        } else if let Some(label) = src.strip_prefix("->") {
+            self.compile_jump(line, label.trim())?;
+        } else {
+            match self.current.as_mut() {
+                Some(ScriptElement {
+                    kind:
+                        ScriptElementKind::Text {
+                            lines, jump: None, ..
+                        },
+                    ..
+                }) => lines.push(src.to_string()),
+                _ => {
+                    let speaker = self.speaker.clone();
+                    self.open(
+                        line,
+                        ScriptElementKind::Text {
+                            speaker: speaker,
+                            lines: vec![src.to_string()],
+                            jump: None,
+                        },
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_tachie(&mut self, line: usize, command: &str) -> Result<(), SuzuError> {
+        for assign in command.split_whitespace() {
+            let (position, texture) = match assign.find('=') {
+                Some(index) => (&assign[..index], &assign[index + 1..]),
+                None => {
+                    return Err(self.error(
+                        line,
+                        format!("expected position=texture, found \"{}\"", assign),
+                    ))
+                }
+            };
+
+            if !TACHIE_POSITION_KEYS.contains(&position) {
+                return Err(self.error(line, format!("unknown tachie position \"{}\"", position)));
+            }
+
+            self.tachie.retain(|(key, _)| key != position);
+            if texture != "none" {
+                self.tachie
+                    .push((position.to_string(), texture.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile_choice(&mut self, line: usize, choice: &str) -> Result<(), SuzuError> {
+        let (pattern, label) = match choice.find("->") {
+            Some(index) => (choice[..index].trim(), choice[index + 2..].trim()),
+            None => {
+                return Err(self.error(line, "choice must be written as \"> pattern -> label\""))
+            }
+        };
+
+        if label.is_empty() {
+            return Err(self.error(line, "choice has no jump label"));
+        }
+
+        if self.current.as_ref().map_or(true, |elem| elem.is_text()) {
+            self.open(
+                line,
+                ScriptElementKind::Choice {
+                    header: String::new(),
+                    patterns: Vec::new(),
+                },
+            )?;
+        }
+
+        if let Some(ScriptElement {
+            kind: ScriptElementKind::Choice { patterns, .. },
+            ..
+        }) = self.current.as_mut()
+        {
+            patterns.push((pattern.to_string(), label.to_string(), line));
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// "-> label"は直前のテキストの飛び先を指定する
+    ///
+    fn compile_jump(&mut self, line: usize, label: &str) -> Result<(), SuzuError> {
+        if label.is_empty() {
+            return Err(self.error(line, "jump has no label"));
+        }
+
+        let target = match self.current.as_mut() {
+            Some(elem) => Some(elem),
+            None if self.pending_labels.is_empty() => self.elements.last_mut(),
+            None => None,
+        };
+
+        match target {
+            Some(ScriptElement {
+                kind:
+                    ScriptElementKind::Text {
+                        jump: jump @ None, ..
+                    },
+                ..
+            }) => {
+                *jump = Some((label.to_string(), line));
+            }
+            _ => return Err(self.error(line, "jump must follow a text")),
+        }
+
+        self.flush();
+        Ok(())
+    }
+
+    fn resolve(&self, label: &str, line: usize) -> Result<i64, SuzuError> {
+        self.labels
+            .get(label)
+            .copied()
+            .ok_or_else(|| self.error(line, format!("undefined label *{}", label)))
+    }
+
+    fn finish(mut self) -> Result<toml::Value, SuzuError> {
+        self.flush();
+
+        if let Some((label, line)) = self.pending_labels.first() {
+            return Err(self.error(
+                *line,
+                format!("label *{} does not point to any element", label),
+            ));
+        }
+
+        let first_id = match self.elements.first() {
+            Some(elem) => elem.id,
+            None => return Err(self.error(1, "script has no element")),
+        };
+
+        let mut group = Vec::new();
+        for (index, elem) in self.elements.iter().enumerate() {
+            let mut table = toml::value::Table::new();
+            table.insert("id".to_string(), toml::Value::Integer(elem.id));
+
+            if let Some(background) = elem.background.as_ref() {
+                table.insert(
+                    "background".to_string(),
+                    toml::Value::String(background.clone()),
+                );
+            }
+
+            if !elem.tachie.is_empty() {
+                let tachie = elem
+                    .tachie
+                    .iter()
+                    .map(|(position, texture)| {
+                        (position.clone(), toml::Value::String(texture.clone()))
+                    })
+                    .collect::<toml::value::Table>();
+                table.insert("tachie-data".to_string(), toml::Value::Table(tachie));
+            }
+
+            match &elem.kind {
+                ScriptElementKind::Text {
+                    speaker,
+                    lines,
+                    jump,
+                } => {
+                    let next_id = match jump {
+                        Some((label, line)) => self.resolve(label, *line)?,
+                        None => match self.elements.get(index + 1) {
+                            Some(next) => next.id,
+                            None => {
+                                return Err(
+                                    self.error(elem.line, "last text must end with \"-> label\"")
+                                )
+                            }
+                        },
+                    };
+
+                    table.insert(
+                        "type".to_string(),
+                        toml::Value::String("scenario".to_string()),
+                    );
+                    table.insert("next-id".to_string(), toml::Value::Integer(next_id));
+
+                    if let Some(speaker) = speaker {
+                        table.insert("speaker".to_string(), toml::Value::String(speaker.clone()));
+                    }
+
+                    let mut attribute = toml::value::Table::new();
+                    attribute.insert("fpc".to_string(), toml::Value::Float(2.0));
+                    attribute.insert("font_scale".to_string(), toml::Value::Float(32.0));
+                    attribute.insert("color".to_string(), toml::Value::Integer(0x000000ff));
+                    table.insert(
+                        "default-text-attribute".to_string(),
+                        toml::Value::Table(attribute),
+                    );
+
+                    let mut text = toml::value::Table::new();
+                    text.insert("text".to_string(), toml::Value::String(lines.join("\n")));
+                    table.insert(
+                        "text".to_string(),
+                        toml::Value::Array(vec![toml::Value::Table(text)]),
+                    );
+                }
+                ScriptElementKind::Choice { header, patterns } => {
+                    if patterns.is_empty() {
+                        return Err(self.error(elem.line, "choice has no \"> pattern -> label\""));
+                    }
+
+                    table.insert(
+                        "type".to_string(),
+                        toml::Value::String("choice".to_string()),
+                    );
+                    table.insert(
+                        "header_text".to_string(),
+                        toml::Value::String(header.clone()),
+                    );
+
+                    let mut choice_pattern = Vec::new();
+                    for (pattern, label, line) in patterns.iter() {
+                        let mut choice = toml::value::Table::new();
+                        choice.insert("pattern".to_string(), toml::Value::String(pattern.clone()));
+                        choice.insert(
+                            "jump-id".to_string(),
+                            toml::Value::Integer(self.resolve(label, *line)?),
+                        );
+                        choice_pattern.push(toml::Value::Table(choice));
+                    }
+                    table.insert(
+                        "choice-pattern".to_string(),
+                        toml::Value::Array(choice_pattern),
+                    );
+                }
+            }
+
+            group.push(toml::Value::Table(table));
+        }
+
+        let scene_transition = SCENE_TRANSITION_LABELS
+            .iter()
+            .map(|(label, id)| (label.to_string(), toml::Value::Integer(*id)))
+            .collect::<toml::value::Table>();
+
+        let mut root = toml::value::Table::new();
+        root.insert(
+            "first-scenario-id".to_string(),
+            toml::Value::Integer(first_id),
+        );
+        root.insert(
+            "scene-transition".to_string(),
+            toml::Value::Table(scene_transition),
+        );
+        root.insert("scenario-group".to_string(), toml::Value::Array(group));
+
+        Ok(toml::Value::Table(root))
+    }
+}
+
+///
+/// # 簡易スクリプト形式のシナリオをTOMLの値に変換する
+///
+/// 空行でテキストが区切られ, 各要素には100から順にIDが割り当てられる
+///
+/// ```text
+/// # コメント
+/// *start
+/// [bg SightBackground1]
+/// [tachie right=KosuzuTachie1 left=none]
+/// @小鈴
+/// さてと、昨日の続きでも読もうかな。
+///
+/// ? どうする?
+/// > 店番をする -> shop
+/// > 夢を見る -> dream
+///
+/// *shop
+/// いらっしゃいませ。
+/// -> scenario
+/// ```
+///
+/// "scenario", "dream", "save"はシーン切り替えのラベルとして予約されている
+///
+pub fn compile_scenario_script(path: &str, content: &str) -> Result<toml::Value, SuzuError> {
+    let mut compiler = ScriptCompiler::new(path);
+
+    for (index, line) in content.lines().enumerate() {
+        compiler.compile_line(index + 1, line)?;
+    }
+
+    compiler.finish()
+}
@@ -1,5 +1,3 @@
-use std::str::FromStr;
-
 use ggez::graphics as ggraphics;
 use ggez::input::mouse::MouseButton;
 
@@ -24,8 +22,10 @@ use crate::{core::*, scene::DrawRequest};
 use crate::flush_delay_event;
 use crate::flush_delay_event_and_redraw_check;
 use crate::parse_toml_file;
+use crate::try_parse_toml_file;
 
 use number_to_jk::number_to_jk;
+use serde::Deserialize;
 
 pub struct SelectBookWindowContents {
     table_frame: TableFrame,
@@ -2624,6 +2624,51 @@ impl std::fmt::Display for ShopClock {
     }
 }
 
+pub const SHOP_CLOCK_CONFIG_PATH: &str = "/other_config/shop_clock.toml";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfigVector {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl ConfigVector {
+    pub fn to_point(&self) -> numeric::Point2f {
+        numeric::Point2f::new(self.x, self.y)
+    }
+
+    pub fn to_vector(&self) -> numeric::Vector2f {
+        numeric::Vector2f::new(self.x, self.y)
+    }
+}
+
+///
+/// # 店番中の時計の配置
+///
+/// other_config/shop_clock.tomlの内容
+///
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ShopClockConfig {
+    pub background_texture: TextureID,
+    pub short_needle_texture: TextureID,
+    pub long_needle_texture: TextureID,
+    pub clock_texture_position: ConfigVector,
+    pub clock_origin: ConfigVector,
+    pub clock_texture_scale: ConfigVector,
+    pub short_needle_texture_scale: ConfigVector,
+    pub long_needle_texture_scale: ConfigVector,
+    pub short_needle_offset: ConfigVector,
+    pub long_needle_offset: ConfigVector,
+}
+
+impl ShopClockConfig {
+    pub fn load(ctx: &mut ggez::Context, path: &str) -> Result<Self, SuzuError> {
+        let root = try_parse_toml_file!(ctx, path)?;
+        schema::deserialize(path, root)
+    }
+}
+
 pub struct DrawableShopClock {
     background: UniTexture,
     long_needle: UniTexture,
@@ -2634,71 +2679,23 @@ pub struct DrawableShopClock {
 }
 
 impl DrawableShopClock {
-    pub fn from_toml<'a>(ctx: &mut SuzuContext<'a>, path: &str, time: ShopClock) -> Self {
-        let root = parse_toml_file!(ctx.context, path);
-
-        let background_texture_id = root["background-texture"].as_str().unwrap();
-        let short_needle_texture_id = root["short-needle-texture"].as_str().unwrap();
-        let long_needle_texture_id = root["long-needle-texture"].as_str().unwrap();
-
-        let background_pos = {
-            let clock_texture_position = root["clock-texture-position"].as_table().unwrap();
-            numeric::Point2f::new(
-                clock_texture_position["x"].as_float().unwrap() as f32,
-                clock_texture_position["y"].as_float().unwrap() as f32,
-            )
-        };
-
-        let background_origin = {
-            let clock_texture_position = root["clock-origin"].as_table().unwrap();
-            numeric::Vector2f::new(
-                clock_texture_position["x"].as_float().unwrap() as f32,
-                clock_texture_position["y"].as_float().unwrap() as f32,
-            )
-        };
-
-        let background_scale = {
-            let clock_texture_position = root["clock-texture-scale"].as_table().unwrap();
-            numeric::Vector2f::new(
-                clock_texture_position["x"].as_float().unwrap() as f32,
-                clock_texture_position["y"].as_float().unwrap() as f32,
-            )
-        };
-
-        let short_needle_scale = {
-            let clock_texture_position = root["short-needle-texture-scale"].as_table().unwrap();
-            numeric::Vector2f::new(
-                clock_texture_position["x"].as_float().unwrap() as f32,
-                clock_texture_position["y"].as_float().unwrap() as f32,
-            )
-        };
-
-        let long_needle_scale = {
-            let clock_texture_position = root["long-needle-texture-scale"].as_table().unwrap();
-            numeric::Vector2f::new(
-                clock_texture_position["x"].as_float().unwrap() as f32,
-                clock_texture_position["y"].as_float().unwrap() as f32,
-            )
-        };
-
-        let short_needle_offset = {
-            let clock_texture_position = root["short-needle-offset"].as_table().unwrap();
-            numeric::Vector2f::new(
-                clock_texture_position["x"].as_float().unwrap() as f32,
-                clock_texture_position["y"].as_float().unwrap() as f32,
-            )
-        };
-
-        let long_needle_offset = {
-            let clock_texture_position = root["long-needle-offset"].as_table().unwrap();
-            numeric::Vector2f::new(
-                clock_texture_position["x"].as_float().unwrap() as f32,
-                clock_texture_position["y"].as_float().unwrap() as f32,
-            )
-        };
+    pub fn from_toml<'a>(
+        ctx: &mut SuzuContext<'a>,
+        path: &str,
+        time: ShopClock,
+    ) -> Result<Self, SuzuError> {
+        let config = ShopClockConfig::load(ctx.context, path)?;
+
+        let background_pos = config.clock_texture_position.to_point();
+        let background_origin = config.clock_origin.to_vector();
+        let background_scale = config.clock_texture_scale.to_vector();
+        let short_needle_scale = config.short_needle_texture_scale.to_vector();
+        let long_needle_scale = config.long_needle_texture_scale.to_vector();
+        let short_needle_offset = config.short_needle_offset.to_vector();
+        let long_needle_offset = config.long_needle_offset.to_vector();
 
         let background_texture = UniTexture::new(
-            ctx.ref_texture(TextureID::from_str(background_texture_id).expect("Invalid TextureID")),
+            ctx.ref_texture(config.background_texture),
             background_pos,
             background_scale,
             0.0,
@@ -2709,9 +2706,7 @@ impl DrawableShopClock {
             util::clock_needle_angle_inverse(time.hour, time.minute);
 
         let mut long_needle_texture = UniTexture::new(
-            ctx.ref_texture(
-                TextureID::from_str(long_needle_texture_id).expect("Invalid TextureID"),
-            ),
+            ctx.ref_texture(config.long_needle_texture),
             background_pos + background_origin + long_needle_offset,
             long_needle_scale,
             long_needle_angle,
@@ -2719,9 +2714,7 @@ impl DrawableShopClock {
         );
 
         let mut short_needle_texture = UniTexture::new(
-            ctx.ref_texture(
-                TextureID::from_str(short_needle_texture_id).expect("Invalid TextureID"),
-            ),
+            ctx.ref_texture(config.short_needle_texture),
             background_pos + background_origin + short_needle_offset,
             short_needle_scale,
             short_needle_angle,
@@ -2731,14 +2724,14 @@ impl DrawableShopClock {
         long_needle_texture.set_transform_offset(numeric::Point2f::new(0.5, 0.0));
         short_needle_texture.set_transform_offset(numeric::Point2f::new(0.5, 0.0));
 
-        DrawableShopClock {
+        Ok(DrawableShopClock {
             background: background_texture,
             long_needle: long_needle_texture,
             short_needle: short_needle_texture,
             time: time,
             center_position: background_origin,
             drwob_essential: DrawableObjectEssential::new(true, 0),
-        }
+        })
     }
 
     pub fn update_needle_angle(&mut self) {
@@ -424,9 +424,6 @@ impl DrawableTaskResult {
             numeric::Vector2u::new(2, 1)
         );
         effect_text.push_back(done_work_num_text);
-        ctx.take_save_data_mut()
-            .award_data
-            .add_customer_count(done_work_num as u16);
 
         let mut money_desc_text = VerticalText::new(
             format!("収入"),
@@ -527,8 +524,7 @@ impl DrawableTaskResult {
             1,
         );
 
-	let eval = result_report.generate_eval_result();
-	ctx.take_save_data_mut().suzunaan_status.add_reputation(eval);
+	ctx.simulator().settle_shop_work_result(&result_report, done_work_num);
 	let goal = ctx.take_save_data().suzunaan_status.get_current_reputation();
         let goal = if goal >= 0.0
         {
@@ -0,0 +1,355 @@
+use std::fmt;
+
+use torifune::core::Clock;
+
+///
+/// # テキストの装飾
+///
+/// fpcは1フレームに表示する文字数, colorはRGBAの32bit値
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarkupStyle {
+    pub fpc: f32,
+    pub font_scale: f32,
+    pub color: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MarkupTagKind {
+    Color,
+    Size,
+    Speed,
+    Ruby,
+}
+
+impl MarkupTagKind {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "color" => Some(MarkupTagKind::Color),
+            "size" => Some(MarkupTagKind::Size),
+            "speed" => Some(MarkupTagKind::Speed),
+            "ruby" => Some(MarkupTagKind::Ruby),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            MarkupTagKind::Color => "color",
+            MarkupTagKind::Size => "size",
+            MarkupTagKind::Speed => "speed",
+            MarkupTagKind::Ruby => "ruby",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum MarkupToken {
+    Text(String),
+    Variable(String),
+    Pause(Clock),
+    Open(MarkupTagKind, f32, u32),
+    RubyOpen(String),
+    Close(MarkupTagKind),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkupError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.position + 1)
+    }
+}
+
+///
+/// # 装飾が同じ文字列のまとまり
+///
+/// pauseは, このまとまりを表示し始める前に待つフレーム数
+/// rubyは, textの上に小さく表示する読み仮名
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkupRun {
+    pub text: String,
+    pub style: MarkupStyle,
+    pub pause: Clock,
+    pub ruby: Option<String>,
+}
+
+impl MarkupRun {
+    fn new(style: MarkupStyle) -> Self {
+        MarkupRun {
+            text: String::new(),
+            style: style,
+            pause: 0,
+            ruby: None,
+        }
+    }
+}
+
+///
+/// # インライン装飾付きのテキスト
+///
+/// 使用できる記法
+/// [color=ff0000ff]...[/color] 文字色. 6桁の場合は不透明として扱う
+/// [size=36]...[/size]         文字の大きさ
+/// [speed=0.5]...[/speed]      1フレームに表示する文字数
+/// [wait=30]                   指定したフレーム数だけ表示を止める
+/// [ruby=ようかい]妖怪[/ruby]  妖怪の上に読み仮名を表示する
+/// {money}                     セーブデータの値で置き換える
+/// [[, ]], {{, }}はそれぞれ[, ], {, }そのものになる
+///
+/// rubyの中では改行と他の記法は使えない. ただし{money}は使える
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkupText {
+    tokens: Vec<MarkupToken>,
+}
+
+fn parse_color(value: &str) -> Option<u32> {
+    let hex = value.trim_start_matches('#').trim_start_matches("0x");
+    let color = u32::from_str_radix(hex, 16).ok()?;
+
+    match hex.len() {
+        6 => Some((color << 8) | 0xff),
+        8 => Some(color),
+        _ => None,
+    }
+}
+
+impl MarkupText {
+    ///
+    /// 記法を解釈せず, 文字列をそのまま表示するMarkupTextを作る
+    ///
+    pub fn plain(text: &str) -> Self {
+        MarkupText {
+            tokens: vec![MarkupToken::Text(text.to_string())],
+        }
+    }
+
+    pub fn parse(src: &str) -> Result<Self, MarkupError> {
+        let chars = src.chars().collect::<Vec<char>>();
+        let mut tokens = Vec::new();
+        let mut text = String::new();
+        let mut opened = Vec::new();
+        let mut pos = 0;
+
+        while pos < chars.len() {
+            let c = chars[pos];
+
+            if (c == '[' || c == ']' || c == '{' || c == '}') && chars.get(pos + 1) == Some(&c) {
+                text.push(c);
+                pos += 2;
+                continue;
+            }
+
+            if c != '[' && c != '{' {
+                if c == '\n' && opened.contains(&MarkupTagKind::Ruby) {
+                    return Err(MarkupError {
+                        position: pos,
+                        message: "line break inside ruby".to_string(),
+                    });
+                }
+                text.push(c);
+                pos += 1;
+                continue;
+            }
+
+            let close = if c == '[' { ']' } else { '}' };
+            let end = match chars[pos..].iter().position(|ch| *ch == close) {
+                Some(offset) => pos + offset,
+                None => {
+                    return Err(MarkupError {
+                        position: pos,
+                        message: format!("unclosed '{}'", c),
+                    })
+                }
+            };
+            let body = chars[pos + 1..end].iter().collect::<String>();
+            let body = body.trim();
+
+            if !text.is_empty() {
+                tokens.push(MarkupToken::Text(std::mem::take(&mut text)));
+            }
+
+            if c == '{' {
+                if body.is_empty() {
+                    return Err(MarkupError {
+                        position: pos,
+                        message: "empty variable name".to_string(),
+                    });
+                }
+                tokens.push(MarkupToken::Variable(body.to_string()));
+            } else {
+                tokens.push(Self::parse_tag(body, pos, &mut opened)?);
+            }
+
+            pos = end + 1;
+        }
+
+        if let Some(kind) = opened.last() {
+            return Err(MarkupError {
+                position: chars.len(),
+                message: format!("unclosed [{}] tag", kind.name()),
+            });
+        }
+
+        if !text.is_empty() {
+            tokens.push(MarkupToken::Text(text));
+        }
+
+        Ok(MarkupText { tokens: tokens })
+    }
+
+    fn parse_tag(
+        body: &str,
+        pos: usize,
+        opened: &mut Vec<MarkupTagKind>,
+    ) -> Result<MarkupToken, MarkupError> {
+        let error = |message: String| MarkupError {
+            position: pos,
+            message: message,
+        };
+
+        if opened.contains(&MarkupTagKind::Ruby) && body != "/ruby" {
+            return Err(error(format!("tag \"{}\" inside ruby", body)));
+        }
+
+        if let Some(name) = body.strip_prefix('/') {
+            let kind = MarkupTagKind::from_name(name)
+                .ok_or_else(|| error(format!("unknown tag \"{}\"", body)))?;
+            if opened.pop() != Some(kind) {
+                return Err(error(format!("unexpected closing tag \"{}\"", body)));
+            }
+            return Ok(MarkupToken::Close(kind));
+        }
+
+        let mut split = body.splitn(2, '=');
+        let name = split.next().unwrap_or("").trim();
+        let value = split
+            .next()
+            .map(|v| v.trim())
+            .ok_or_else(|| error(format!("tag \"{}\" needs a value", name)))?;
+
+        if name == "wait" {
+            return value
+                .parse::<Clock>()
+                .map(MarkupToken::Pause)
+                .map_err(|_| error(format!("invalid wait \"{}\"", value)));
+        }
+
+        let kind = MarkupTagKind::from_name(name)
+            .ok_or_else(|| error(format!("unknown tag \"{}\"", name)))?;
+        let token = match kind {
+            MarkupTagKind::Color => parse_color(value)
+                .map(|color| MarkupToken::Open(kind, 0.0, color))
+                .ok_or_else(|| error(format!("invalid color \"{}\"", value)))?,
+            MarkupTagKind::Size | MarkupTagKind::Speed => value
+                .parse::<f32>()
+                .ok()
+                .filter(|v| *v > 0.0)
+                .map(|v| MarkupToken::Open(kind, v, 0))
+                .ok_or_else(|| error(format!("invalid {} \"{}\"", name, value)))?,
+            MarkupTagKind::Ruby => {
+                if value.is_empty() {
+                    return Err(error("empty ruby".to_string()));
+                }
+                MarkupToken::RubyOpen(value.to_string())
+            }
+        };
+        opened.push(kind);
+
+        Ok(token)
+    }
+
+    ///
+    /// 変数を置き換え, 装飾ごとに分割した文字列を返す
+    /// lookupで見つからない変数は{name}のまま残す
+    ///
+    pub fn build<F>(&self, default: MarkupStyle, lookup: &F) -> Vec<MarkupRun>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut runs = Vec::new();
+        let mut stack = vec![default];
+        let mut current = MarkupRun::new(default);
+
+        for token in self.tokens.iter() {
+            let next_style = match token {
+                MarkupToken::Text(text) => {
+                    current.text.push_str(text);
+                    continue;
+                }
+                MarkupToken::Variable(name) => {
+                    match lookup(name) {
+                        Some(value) => current.text.push_str(&value),
+                        None => current.text.push_str(&format!("{{{}}}", name)),
+                    }
+                    continue;
+                }
+                MarkupToken::Pause(frames) => {
+                    if !current.text.is_empty() {
+                        let style = current.style;
+                        runs.push(std::mem::replace(&mut current, MarkupRun::new(style)));
+                    }
+                    current.pause += *frames;
+                    continue;
+                }
+                MarkupToken::RubyOpen(ruby) => {
+                    // 読み仮名をつける部分は, 独立したまとまりにする
+                    if !current.text.is_empty() {
+                        let style = current.style;
+                        runs.push(std::mem::replace(&mut current, MarkupRun::new(style)));
+                    }
+                    current.ruby = Some(ruby.to_string());
+                    continue;
+                }
+                MarkupToken::Close(MarkupTagKind::Ruby) => {
+                    let style = current.style;
+                    if current.text.is_empty() {
+                        current.ruby = None;
+                    } else {
+                        runs.push(std::mem::replace(&mut current, MarkupRun::new(style)));
+                    }
+                    continue;
+                }
+                MarkupToken::Open(kind, value, color) => {
+                    let mut style = *stack.last().unwrap();
+                    match kind {
+                        MarkupTagKind::Color => style.color = *color,
+                        MarkupTagKind::Size => style.font_scale = *value,
+                        MarkupTagKind::Speed => style.fpc = *value,
+                        MarkupTagKind::Ruby => (),
+                    }
+                    stack.push(style);
+                    style
+                }
+                MarkupToken::Close(_) => {
+                    if stack.len() > 1 {
+                        stack.pop();
+                    }
+                    *stack.last().unwrap()
+                }
+            };
+
+            if next_style == current.style {
+                continue;
+            }
+
+            if current.text.is_empty() {
+                current.style = next_style;
+            } else {
+                runs.push(std::mem::replace(&mut current, MarkupRun::new(next_style)));
+            }
+        }
+
+        if !current.text.is_empty() || current.pause > 0 || runs.is_empty() {
+            runs.push(current);
+        }
+
+        runs
+    }
+}
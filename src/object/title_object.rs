@@ -24,7 +24,7 @@ use crate::{
     set_table_frame_cell_center,
 };
 use crate::{
-    core::{GameMode, WINDOW_SIZE_X, WINDOW_SIZE_Y},
+    core::{GameMode, SuzuError, WINDOW_SIZE_X, WINDOW_SIZE_Y},
     flush_delay_event, flush_delay_event_and_redraw_check,
     object::util_object::{CheckBox, SeekBar, SelectButton, TextButtonTexture},
     scene::DelayEventList,
@@ -136,7 +136,8 @@ impl TextMenuData {
             Err(_) => panic!("Failed to read: {}", file_path),
         };
 
-        let root = content.parse::<toml::Value>().unwrap();
+        let mut root = content.parse::<toml::Value>().unwrap();
+        ctx.resource.translate(file_path, &mut root);
         let entry_data_set = root["each_entry_data"].as_array().unwrap();
         let mut entries_data = Vec::new();
 
@@ -390,13 +391,17 @@ type DynamicTitleSoundPlayer = MovableWrap<TitleSoundPlayer>;
 pub enum GameConfigElement {
     BGMVolume,
     SEVolume,
+    VoiceVolume,
+    AutoAdvanceWait,
 }
 
 struct TemporaryConfigData {
     bgm_volume: f32,
     se_volume: f32,
+    voice_volume: f32,
     pause_when_inactive: bool,
     fullscreen_mode: bool,
+    auto_advance_wait: Clock,
 }
 
 impl TemporaryConfigData {
@@ -404,12 +409,97 @@ impl TemporaryConfigData {
         TemporaryConfigData {
             bgm_volume: ctx.config.get_bgm_volume(),
             se_volume: ctx.config.get_se_volume(),
+            voice_volume: ctx.config.get_voice_volume(),
             pause_when_inactive: ctx.config.is_pause_when_inactive(),
 	    fullscreen_mode: ctx.config.is_fullscreen_mode_configed(),
+            auto_advance_wait: ctx.config.get_auto_advance_wait(),
         }
     }
 }
 
+///
+/// オート送りの待ち時間のシークバーは0.1秒単位で扱う
+///
+fn auto_advance_wait_to_seek_value(wait: Clock) -> f32 {
+    wait as f32 / 6.0
+}
+
+fn seek_value_to_auto_advance_wait(value: f32) -> Clock {
+    (value.round() as Clock).max(1) * 6
+}
+
+fn auto_advance_wait_text(value: f32) -> String {
+    format!("{:.1}秒", value.round().max(1.0) / 10.0)
+}
+
+fn mod_list_header_text<'a>(ctx: &SuzuContext<'a>) -> String {
+    let conflicts = ctx.resource.get_mods().conflicts().len();
+    if conflicts == 0 {
+        "MOD (読み込み順)".to_string()
+    } else {
+        format!("MOD (読み込み順, 競合 {}件)", conflicts)
+    }
+}
+
+///
+/// 設定画面に表示するMODの行数. 適用ボタンの下端までに収まる数にする
+///
+const MOD_LIST_MAX_LINES: usize = 4;
+
+///
+/// MODの一行に表示する文字数. これより長い名前は省略する
+///
+const MOD_LIST_LINE_CHARS: usize = 20;
+
+fn truncate_mod_list_line(line: String) -> String {
+    if line.chars().count() <= MOD_LIST_LINE_CHARS {
+        line
+    } else {
+        let mut truncated = line
+            .chars()
+            .take(MOD_LIST_LINE_CHARS - 1)
+            .collect::<String>();
+        truncated.push('…');
+        truncated
+    }
+}
+
+///
+/// 読み込んだMODを, 読み込み順に番号を付けて一行ずつ並べる
+/// 表示しきれない分は, 最後の行にまとめて件数を表示する
+///
+fn mod_list_lines<'a>(ctx: &SuzuContext<'a>) -> Vec<String> {
+    let mods = ctx.resource.get_mods().mods();
+    if mods.is_empty() {
+        return vec!["なし".to_string()];
+    }
+
+    let shown = if mods.len() > MOD_LIST_MAX_LINES {
+        MOD_LIST_MAX_LINES - 1
+    } else {
+        mods.len()
+    };
+
+    let mut lines = mods
+        .iter()
+        .take(shown)
+        .enumerate()
+        .map(|(index, loaded)| {
+            truncate_mod_list_line(if loaded.version().is_empty() {
+                format!("{}. {}", index + 1, loaded.name())
+            } else {
+                format!("{}. {} {}", index + 1, loaded.name(), loaded.version())
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if shown < mods.len() {
+        lines.push(format!("ほか {}件", mods.len() - shown));
+    }
+
+    lines
+}
+
 pub struct ConfigPanel {
     canvas: sub_screen::SubScreen,
     background: DarkEffectPanel,
@@ -418,6 +508,8 @@ pub struct ConfigPanel {
     header_text: UniText,
     bgm_volume_bar: SeekBar,
     se_volume_bar: SeekBar,
+    voice_volume_bar: SeekBar,
+    auto_advance_wait_bar: SeekBar,
     checkbox: CheckBox,
     fullscreen_checkbox: CheckBox,
     apply_button: SelectButton,
@@ -462,10 +554,15 @@ impl ConfigPanel {
             ggraphics::Color::from_rgba_u32(0xbbbbbbff),
         );
 
-        for (s, p) in vec![("BGM音量", 180.0), ("SE音量", 280.0)] {
+        for (s, p) in vec![
+            ("BGM音量", numeric::Point2f::new(200.0, 180.0)),
+            ("SE音量", numeric::Point2f::new(200.0, 280.0)),
+            ("ボイス音量", numeric::Point2f::new(750.0, 280.0)),
+            ("オート送り待ち時間", numeric::Point2f::new(750.0, 180.0)),
+        ] {
             let text = UniText::new(
                 s.to_string(),
-                numeric::Point2f::new(200.0, p),
+                p,
                 numeric::Vector2f::new(1.0, 1.0),
                 0.0,
                 0,
@@ -501,6 +598,32 @@ impl ConfigPanel {
             ),
         );
 
+        sb_dynamic_text.insert(
+            GameConfigElement::VoiceVolume,
+            UniText::new(
+                format!("{}%", (ctx.config.get_voice_volume() * 100.0).round()),
+                numeric::Point2f::new(1050.0, 280.0),
+                numeric::Vector2f::new(1.0, 1.0),
+                0.0,
+                0,
+                hrzn_text_font_info.clone(),
+            ),
+        );
+
+        sb_dynamic_text.insert(
+            GameConfigElement::AutoAdvanceWait,
+            UniText::new(
+                auto_advance_wait_text(auto_advance_wait_to_seek_value(
+                    ctx.config.get_auto_advance_wait(),
+                )),
+                numeric::Point2f::new(1050.0, 180.0),
+                numeric::Vector2f::new(1.0, 1.0),
+                0.0,
+                0,
+                hrzn_text_font_info.clone(),
+            ),
+        );
+
         let text_texture = Box::new(TextButtonTexture::new(
             ctx,
             numeric::Point2f::new(0.0, 0.0),
@@ -581,6 +704,23 @@ impl ConfigPanel {
             0,
         );
 
+        let mod_text_font_info = FontInformation::new(
+            ctx.resource.get_font(FontID::Cinema),
+            numeric::Vector2f::new(22.0, 22.0),
+            ggraphics::Color::from_rgba_u32(0xbbbbbbff),
+        );
+        let mod_list = std::iter::once(mod_list_header_text(ctx)).chain(mod_list_lines(ctx));
+        for (index, s) in mod_list.enumerate() {
+            hrzn_text_list.push(UniText::new(
+                s,
+                numeric::Point2f::new(200.0, 510.0 + 28.0 * index as f32),
+                numeric::Vector2f::new(1.0, 1.0),
+                0.0,
+                0,
+                mod_text_font_info.clone(),
+            ));
+        }
+
         ConfigPanel {
             header_text: header_text,
             sb_dynamic_text: sb_dynamic_text,
@@ -610,6 +750,24 @@ impl ConfigPanel {
                 ctx.config.get_se_volume() * 100.0,
                 0,
             ),
+            voice_volume_bar: SeekBar::new(
+                ctx,
+                numeric::Rect::new(750.0, 310.0, 450.0, 40.0),
+                10.0,
+                100.0,
+                0.0,
+                ctx.config.get_voice_volume() * 100.0,
+                0,
+            ),
+            auto_advance_wait_bar: SeekBar::new(
+                ctx,
+                numeric::Rect::new(750.0, 210.0, 450.0, 40.0),
+                10.0,
+                50.0,
+                0.0,
+                auto_advance_wait_to_seek_value(ctx.config.get_auto_advance_wait()),
+                0,
+            ),
             apply_button: apply_button,
             cancel_button: cancel_button,
             original_config_data: TemporaryConfigData::new(ctx),
@@ -621,6 +779,7 @@ impl ConfigPanel {
     fn update_seek_bar_value(&mut self) {
         let bgm_volume = self.bgm_volume_bar.get_current_value() as i32;
         let se_volume = self.se_volume_bar.get_current_value() as i32;
+        let voice_volume = self.voice_volume_bar.get_current_value() as i32;
 
         self.sb_dynamic_text
             .get_mut(&GameConfigElement::BGMVolume)
@@ -630,21 +789,40 @@ impl ConfigPanel {
             .get_mut(&GameConfigElement::SEVolume)
             .unwrap()
             .replace_text(format!("{}%", se_volume));
+        self.sb_dynamic_text
+            .get_mut(&GameConfigElement::VoiceVolume)
+            .unwrap()
+            .replace_text(format!("{}%", voice_volume));
+        self.sb_dynamic_text
+            .get_mut(&GameConfigElement::AutoAdvanceWait)
+            .unwrap()
+            .replace_text(auto_advance_wait_text(
+                self.auto_advance_wait_bar.get_current_value(),
+            ));
     }
 
     fn recover_original_config<'a>(&mut self, ctx: &mut SuzuContext<'a>) {
         let original_bgm = self.original_config_data.bgm_volume * 100.0;
         let original_se = self.original_config_data.se_volume * 100.0;
+        let original_voice = self.original_config_data.voice_volume * 100.0;
         let original_pause = self.original_config_data.pause_when_inactive;
 	let original_fullscreen_config = self.original_config_data.fullscreen_mode;
+        let original_auto_advance_wait = self.original_config_data.auto_advance_wait;
 
         ctx.change_bgm_volume(original_bgm);
         ctx.change_se_volume(original_se);
+        ctx.change_voice_volume(original_voice);
         ctx.config.set_pause_when_inactive(original_pause);
 	ctx.config.set_fullscreen_mode_config(original_fullscreen_config);
+        ctx.config.set_auto_advance_wait(original_auto_advance_wait);
 
         self.bgm_volume_bar.set_value(ctx, original_bgm);
         self.se_volume_bar.set_value(ctx, original_se);
+        self.voice_volume_bar.set_value(ctx, original_voice);
+        self.auto_advance_wait_bar.set_value(
+            ctx,
+            auto_advance_wait_to_seek_value(original_auto_advance_wait),
+        );
         self.checkbox.try_check(original_pause);
 	self.fullscreen_checkbox.try_check(original_fullscreen_config);
 
@@ -678,6 +856,8 @@ impl ConfigPanel {
 
                 self.bgm_volume_bar.start_dragging_check(ctx, rpoint);
                 self.se_volume_bar.start_dragging_check(ctx, rpoint);
+                self.voice_volume_bar.start_dragging_check(ctx, rpoint);
+                self.auto_advance_wait_bar.start_dragging_check(ctx, rpoint);
             }
             _ => (),
         }
@@ -711,6 +891,8 @@ impl ConfigPanel {
     ) -> Option<TitleContentsEvent> {
         self.bgm_volume_bar.release_handler();
         self.se_volume_bar.release_handler();
+        self.voice_volume_bar.release_handler();
+        self.auto_advance_wait_bar.release_handler();
 
         let rpoint = self.canvas.relative_point(point);
         self.checkbox.click_handler(rpoint);
@@ -719,9 +901,13 @@ impl ConfigPanel {
         if self.apply_button.contains(ctx.context, rpoint) {
             ctx.change_bgm_volume(self.bgm_volume_bar.get_current_value());
             ctx.change_se_volume(self.se_volume_bar.get_current_value());
+            ctx.change_voice_volume(self.voice_volume_bar.get_current_value());
             ctx.config
                 .set_pause_when_inactive(self.checkbox.checked_now());
 	    ctx.config.set_fullscreen_mode_config(self.fullscreen_checkbox.checked_now());
+            ctx.config.set_auto_advance_wait(seek_value_to_auto_advance_wait(
+                self.auto_advance_wait_bar.get_current_value(),
+            ));
             ctx.config.save_config();
 	    
             return Some(TitleContentsEvent::NextContents("init-menu".to_string()));
@@ -746,11 +932,14 @@ impl ConfigPanel {
 
         self.bgm_volume_bar.dragging_handler(ctx, rpoint);
         self.se_volume_bar.dragging_handler(ctx, rpoint);
+        self.voice_volume_bar.dragging_handler(ctx, rpoint);
+        self.auto_advance_wait_bar.dragging_handler(ctx, rpoint);
 
         self.update_seek_bar_value();
 
         ctx.change_bgm_volume(self.bgm_volume_bar.get_current_value());
         ctx.change_se_volume(self.se_volume_bar.get_current_value());
+        ctx.change_voice_volume(self.voice_volume_bar.get_current_value());
     }
 }
 
@@ -764,6 +953,8 @@ impl DrawableComponent for ConfigPanel {
             self.header_text.draw(ctx)?;
             self.bgm_volume_bar.draw(ctx)?;
             self.se_volume_bar.draw(ctx)?;
+            self.voice_volume_bar.draw(ctx)?;
+            self.auto_advance_wait_bar.draw(ctx)?;
 
             for text in self.hrzn_text_list.iter_mut() {
                 text.draw(ctx)?;
@@ -966,28 +1157,36 @@ impl UpdatePanel {
         None
     }
 
-    pub fn check_update<'a>(&mut self, _ctx: &mut SuzuContext<'a>, _t: Clock) -> Result<String, ()> {
-        let resp = match reqwest::blocking::get("https://boxed-sumire.fun/suzu/update.toml") {
-            Ok(resp) => match resp.text() {
-                Ok(text) => text,
-                Err(_) => return Err(()),
-            },
-            Err(_) => return Err(()),
+    ///
+    /// 更新の有無を確認する
+    /// 新しいバージョンがあればそのバージョン文字列を, 最新版であればNoneを返す
+    ///
+    pub fn check_update<'a>(
+        &mut self,
+        _ctx: &mut SuzuContext<'a>,
+        _t: Clock,
+    ) -> Result<Option<String>, SuzuError> {
+        let url = "https://boxed-sumire.fun/suzu/update.toml";
+        let network_error = |e: reqwest::Error| SuzuError::Network {
+            url: url.to_string(),
+            detail: e.to_string(),
         };
 
-        let root = match resp.parse::<toml::Value>() {
-            Ok(root) => root,
-            Err(_) => return Err(()),
-        };
+        let resp = reqwest::blocking::get(url)
+            .map_err(network_error)?
+            .text()
+            .map_err(network_error)?;
 
-        if let Some(latest_version) = root["latest"].as_str() {
-            if crate::core::Version::from_str(latest_version) > crate::core::Version::this() {
-                Ok(latest_version.to_string())
-            } else {
-                Err(())
+        let root = crate::core::error::parse_toml_str(url, &resp)?;
+        let latest_version = crate::core::error::TomlNode::root(url, &root).get("latest")?;
+        let latest_version_str = latest_version.as_str()?;
+
+        match crate::core::Version::try_from_str(latest_version_str) {
+            Some(version) if version > crate::core::Version::this() => {
+                Ok(Some(latest_version_str.to_string()))
             }
-        } else {
-            Err(())
+            Some(_) => Ok(None),
+            None => Err(latest_version.invalid_value(latest_version_str)),
         }
     }
 
@@ -1021,7 +1220,7 @@ impl UpdatePanel {
         self.event_list.add_event(
             Box::new(
                 move |slf: &mut UpdatePanel, ctx, t| match slf.check_update(ctx, t) {
-                    Ok(version) => {
+                    Ok(Some(version)) => {
                         slf.latest_version = Some(crate::core::Version::from_str(&version));
                         slf.replace_main_message(
                             ctx,
@@ -1032,12 +1231,16 @@ impl UpdatePanel {
                             ),
                         );
                     }
-                    Err(()) => {
+                    Ok(None) => {
                         slf.replace_main_message(
                             ctx,
                             &format!("最新版です v{}", crate::core::VERSION),
                         );
                     }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        slf.replace_main_message(ctx, "更新の確認に失敗しました");
+                    }
                 },
             ),
             t + 2,
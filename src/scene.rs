@@ -68,6 +68,22 @@ impl FromStr for SceneID {
     }
 }
 
+impl SceneID {
+    ///
+    /// シーンで使うテクスチャのグループ名. game_data.tomlのtexture_groupsに対応する
+    ///
+    pub fn texture_group(&self) -> Option<&'static str> {
+        match self {
+            Self::Title => Some("title"),
+            Self::Scenario => Some("scenario"),
+            Self::SuzunaShop | Self::MainDesk | Self::DayResult | Self::Copying => Some("shop"),
+            Self::Save => Some("save"),
+            Self::End => Some("end"),
+            Self::Null => None,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum DrawRequest {
     InitDraw,
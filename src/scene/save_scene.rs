@@ -27,8 +27,27 @@ impl SaveScene {
     pub fn new<'a>(ctx: &mut SuzuContext<'a>) -> Self {
         let save_data_list = (1..=4)
             .map(|slot_index| match SavableData::new_load(slot_index) {
-                Ok(savable_data) => Some(savable_data),
-                Err(_) => None,
+                Ok((savable_data, report)) => {
+                    if report.migrated() {
+                        eprintln!(
+                            "save{}: v{} -> v{}: {:?}",
+                            slot_index,
+                            report
+                                .saved_version
+                                .as_ref()
+                                .map_or("(legacy)".to_string(), |version| version.to_string()),
+                            crate::core::VERSION,
+                            report.applied_migrations
+                        );
+                    }
+                    Some(savable_data)
+                }
+                Err(e) => {
+                    if !e.is_not_found() {
+                        eprintln!("{}", e);
+                    }
+                    None
+                }
             })
             .collect();
 
@@ -140,10 +159,13 @@ impl SaveScene {
 
     fn load_and_scene_swap<'a>(&mut self, ctx: &mut SuzuContext<'a>, slot: u8, t: Clock) {
         match SavableData::new_load(slot) {
-            Ok(data) => {
+            Ok((data, _)) => {
                 ctx.savable_data.replace(data);
             }
-            Err(_) => return,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
         }
 
         self.scene_transition_effect = Some(effect_object::ScreenTileEffect::new(
@@ -8,7 +8,6 @@ use torifune::{core::Clock, sound::SoundPlayFlags};
 use crate::core::{GameMode, GeneralScenarioID, MouseInformation, ScenarioSceneSaveData, SoundID, SuzuContext, TileBatchTextureID};
 
 use crate::add_delay_event;
-use crate::core::game_system;
 use crate::core::game_system::*;
 use crate::flush_delay_event;
 use crate::flush_delay_event_and_redraw_check;
@@ -176,7 +175,7 @@ impl ScenarioScene {
             Box::new(|slf: &mut Self, ctx, _| {
                 slf.scene_transition_type = SceneTransition::SwapTransition;
                 slf.scene_transition = SceneID::Title;
-                ctx.resource.stop_bgm(ctx.context, SoundID::ScenarioBGM);
+                ctx.resource.stop_all_bgm(ctx.context);
             }),
             t + 60,
         );
@@ -217,11 +216,34 @@ impl ScenarioScene {
     }
 
     fn non_paused_key_down_event(&mut self, ctx: &mut SuzuContext, vkey: tdev::VirtualKey) {
+        if self.scenario_event.is_backlog_opened() {
+            match vkey {
+                tdev::VirtualKey::Action1 | tdev::VirtualKey::Action2 => {
+                    self.scenario_event.close_backlog(ctx);
+                }
+                _ => (),
+            }
+            return;
+        }
+
         match vkey {
             tdev::VirtualKey::Action1 => {
+                self.cancel_skip_mode(ctx);
                 self.scenario_event
                     .key_down_action1(ctx, None, self.get_current_clock());
             }
+            tdev::VirtualKey::Action3 => {
+                self.scenario_event
+                    .toggle_advance_mode(ctx, ScenarioAdvanceMode::Auto);
+            }
+            tdev::VirtualKey::Action5 => {
+                self.scenario_event
+                    .toggle_advance_mode(ctx, ScenarioAdvanceMode::Skip);
+            }
+            tdev::VirtualKey::Action2 => {
+                let t = self.get_current_clock();
+                self.scenario_event.open_backlog(ctx, t);
+            }
             tdev::VirtualKey::Action4 => {
                 let t = self.get_current_clock();
                 self.enter_pause_screen(t);
@@ -230,6 +252,16 @@ impl ScenarioScene {
         }
     }
 
+    ///
+    /// プレイヤーが自分でテキストを送った場合は, スキップモードを解除する
+    ///
+    fn cancel_skip_mode(&mut self, ctx: &mut SuzuContext) {
+        if self.scenario_event.get_advance_mode() == ScenarioAdvanceMode::Skip {
+            self.scenario_event
+                .set_advance_mode(ctx, ScenarioAdvanceMode::Manual);
+        }
+    }
+
     fn schedule_check<'a>(&mut self, ctx: &mut SuzuContext<'a>) {
         if !self.scenario_ctx.wait_opecode_running {
             if let Some(opecode) = self.scenario_event.get_scenario_waiting_opecode() {
@@ -293,23 +325,10 @@ impl ScenarioScene {
                     "NextDay" => {
                         self.status_screen.show_main_page(ctx);
 
-                        let diff = if ctx
-                            .take_save_data_mut()
-                            .suzunaan_status
-                            .get_current_reputation()
-                            < game_system::TAKING_REST_REPUTATION_COST as f32
-                        {
-                            ctx.take_save_data_mut()
-                                .suzunaan_status
-                                .get_current_reputation()
-                        } else {
-                            game_system::TAKING_REST_REPUTATION_COST as f32
-                        };
-
-                        self.status_screen.change_suzunaan_reputation(ctx, -diff);
-                        ctx.take_save_data_mut()
-                            .suzunaan_status
-                            .add_reputation(-diff);
+                        let diff = ctx.simulator().plan_forced_rest();
+
+                        self.status_screen.change_suzunaan_reputation(ctx, diff);
+                        ctx.simulator().apply_reputation_diff(diff);
 
                         add_delay_event!(
                             self.event_list,
@@ -343,7 +362,7 @@ impl ScenarioScene {
                             |slf, ctx, _| {
                                 slf.scene_transition = SceneID::End;
                                 slf.scene_transition_type = SceneTransition::SwapTransition;
-                                ctx.resource.stop_bgm(ctx.context, SoundID::ScenarioBGM);
+                                ctx.resource.stop_all_bgm(ctx.context);
                                 //ctx.go_next_day();
                             },
                             self.get_current_clock() + 1
@@ -371,44 +390,47 @@ impl ScenarioScene {
     }
 
     fn start_shop_work_schedule<'a>(&mut self, ctx: &mut SuzuContext<'a>) {
-	let next_hp = ctx.take_save_data().suzunaan_status.kosuzu_hp - 20.0;
-        if next_hp < 0.0 {
-            let t = self.get_current_clock();
-            self.scenario_event
-                .replace_scenario(ctx, &mut self.scenario_ctx, "/scenario/no_enough_hp.toml", t);
-            return;
-        }
-	
+        let effect = match ctx.simulator().plan_schedule(DayWorkType::ShopWork) {
+            Ok(effect) => effect,
+            Err(_) => {
+                let t = self.get_current_clock();
+                self.scenario_event.replace_scenario(
+                    ctx,
+                    &mut self.scenario_ctx,
+                    "/scenario/no_enough_hp.toml",
+                    t,
+                );
+                return;
+            }
+        };
+
         self.scenario_ctx.builtin_command_inexec = true;
         self.status_screen.show_main_page(ctx);
-        self.status_screen.change_kosuzu_hp(ctx, -20.0);
-        ctx.take_save_data_mut().suzunaan_status.kosuzu_hp -= 20.0;
+        self.status_screen.change_kosuzu_hp(ctx, effect.hp_diff);
+        ctx.simulator().apply_hp_diff(effect.hp_diff);
 
         add_delay_event!(
             self.event_list,
-            |slf, ctx, _| {
-                let reputation_diff = ctx.current_total_ad_reputation_gain();
+            move |slf, ctx, _| {
                 slf.status_screen
-                    .change_suzunaan_reputation(ctx, reputation_diff as f32);
-                ctx.take_save_data_mut()
-                    .suzunaan_status
-                    .add_reputation(reputation_diff as f32);
+                    .change_suzunaan_reputation(ctx, effect.reputation_diff);
+                ctx.simulator()
+                    .apply_reputation_diff(effect.reputation_diff);
             },
             self.get_current_clock() + 100
         );
 
-        let money_diff = ctx.current_total_ad_agency_money_gain() - ctx.current_total_ad_cost();
         self.status_screen
-            .change_main_page_money(ctx, money_diff, self.get_current_clock());
-        ctx.take_save_data_mut().task_result.total_money += money_diff;
+            .change_main_page_money(ctx, effect.money_diff, self.get_current_clock());
+        ctx.simulator().apply_money_diff(effect.money_diff);
 
         add_delay_event!(
             self.event_list,
             |slf, ctx, _| {
                 slf.scene_transition = SceneID::SuzunaShop;
                 slf.scene_transition_type = SceneTransition::SwapTransition;
-                ctx.take_save_data_mut().award_data.shop_work_count += 1;
-                ctx.resource.stop_bgm(ctx.context, SoundID::ScenarioBGM);
+                ctx.simulator().finish_schedule(DayWorkType::ShopWork);
+                ctx.resource.stop_all_bgm(ctx.context);
             },
             self.get_current_clock() + 300
         );
@@ -428,56 +450,49 @@ impl ScenarioScene {
         );
     }
 
-    fn start_going_out_schedule<'a>(&mut self, ctx: &mut SuzuContext<'a>) {
+    fn start_going_out_schedule<'a>(&mut self, ctx: &mut SuzuContext<'a>, dest: GoingOutEvent) {
         self.status_screen.show_main_page(ctx);
-        let money_diff =
-            ctx.current_total_ad_agency_money_gain() - ctx.current_total_ad_cost() - 400;
-
-        if ctx.take_save_data().task_result.total_money + money_diff < 0 {
-            let path = ctx
-                .resource
-                .get_general_scenario_path(&GeneralScenarioID::NoEnoughMoney)
-                .unwrap();
-            let t = self.get_current_clock();
-            self.scenario_event
-                .replace_scenario(ctx, &mut self.scenario_ctx, &path, t);
-            return;
-        }
+
+        let effect = match ctx.simulator().plan_schedule(DayWorkType::GoingOut(dest)) {
+            Ok(effect) => effect,
+            Err(_) => {
+                let path = ctx
+                    .resource
+                    .get_general_scenario_path(&GeneralScenarioID::NoEnoughMoney)
+                    .unwrap();
+                let t = self.get_current_clock();
+                self.scenario_event
+                    .replace_scenario(ctx, &mut self.scenario_ctx, &path, t);
+                return;
+            }
+        };
 
         self.status_screen
-            .change_main_page_money(ctx, money_diff, self.get_current_clock());
-        ctx.take_save_data_mut()
-            .task_result
-            .add_total_money(money_diff);
+            .change_main_page_money(ctx, effect.money_diff, self.get_current_clock());
+        ctx.simulator().apply_money_diff(effect.money_diff);
 
         self.scenario_ctx.builtin_command_inexec = true;
         self.status_screen.show_main_page(ctx);
-        self.status_screen.change_kosuzu_hp(ctx, 40.0);
-        ctx.take_save_data_mut().suzunaan_status.kosuzu_hp += 40.0;
-        if ctx.take_save_data().suzunaan_status.kosuzu_hp > 100.0 {
-            ctx.take_save_data_mut().suzunaan_status.kosuzu_hp = 100.0;
-        }
+        self.status_screen.change_kosuzu_hp(ctx, effect.hp_diff);
+        ctx.simulator().apply_hp_diff(effect.hp_diff);
 
         add_delay_event!(
             self.event_list,
-            |slf, ctx, _| {
-                let reputation_diff = ctx.current_total_ad_reputation_gain() as f32;
+            move |slf, ctx, _| {
                 slf.status_screen
-                    .change_suzunaan_reputation(ctx, reputation_diff);
-                ctx.take_save_data_mut()
-                    .suzunaan_status
-                    .add_reputation(reputation_diff);
+                    .change_suzunaan_reputation(ctx, effect.reputation_diff);
+                ctx.simulator()
+                    .apply_reputation_diff(effect.reputation_diff);
             },
             self.get_current_clock() + 100
         );
 
         add_delay_event!(
             self.event_list,
-            |slf, ctx, _| {
+            move |slf, ctx, _| {
                 slf.scene_transition = SceneID::Scenario;
                 slf.scene_transition_type = SceneTransition::SwapTransition;
-                ctx.take_save_data_mut().award_data.going_out_count += 1;
-                ctx.go_next_day();
+                ctx.simulator().finish_schedule(DayWorkType::GoingOut(dest));
             },
             self.get_current_clock() + 200
         );
@@ -498,44 +513,27 @@ impl ScenarioScene {
     }
 
     fn start_taking_rest_schedule<'a>(&mut self, ctx: &mut SuzuContext<'a>) {
+        let effect = ctx
+            .simulator()
+            .plan_schedule(DayWorkType::TakingRest)
+            .expect("taking rest is always available");
+
         self.scenario_ctx.builtin_command_inexec = true;
         self.status_screen.show_main_page(ctx);
-        self.status_screen.change_kosuzu_hp(ctx, 20.0);
-        ctx.take_save_data_mut().suzunaan_status.kosuzu_hp += 20.0;
-        if ctx.take_save_data().suzunaan_status.kosuzu_hp > 100.0 {
-            ctx.take_save_data_mut().suzunaan_status.kosuzu_hp = 100.0;
-        }
+        self.status_screen.change_kosuzu_hp(ctx, effect.hp_diff);
+        ctx.simulator().apply_hp_diff(effect.hp_diff);
 
-        let money_diff = ctx.current_total_ad_agency_money_gain() - ctx.current_total_ad_cost();
         self.status_screen
-            .change_main_page_money(ctx, money_diff, self.get_current_clock());
-        ctx.take_save_data_mut().task_result.total_money += money_diff;
+            .change_main_page_money(ctx, effect.money_diff, self.get_current_clock());
+        ctx.simulator().apply_money_diff(effect.money_diff);
 
         add_delay_event!(
             self.event_list,
-            |slf, ctx, _| {
-                let reputation_diff = ctx.current_total_ad_reputation_gain() as f32
-                    - game_system::TAKING_REST_REPUTATION_COST as f32;
-
-                let reputation_diff = if ctx
-                    .take_save_data()
-                    .suzunaan_status
-                    .get_current_reputation()
-                    + reputation_diff
-                    < 0.0
-                {
-                    ctx.take_save_data()
-                        .suzunaan_status
-                        .get_current_reputation()
-                } else {
-                    reputation_diff
-                };
-
+            move |slf, ctx, _| {
                 slf.status_screen
-                    .change_suzunaan_reputation(ctx, reputation_diff);
-                ctx.take_save_data_mut()
-                    .suzunaan_status
-                    .add_reputation(reputation_diff);
+                    .change_suzunaan_reputation(ctx, effect.reputation_diff);
+                ctx.simulator()
+                    .apply_reputation_diff(effect.reputation_diff);
             },
             self.get_current_clock() + 100
         );
@@ -545,8 +543,7 @@ impl ScenarioScene {
             |slf, ctx, _| {
                 slf.scene_transition = SceneID::Scenario;
                 slf.scene_transition_type = SceneTransition::SwapTransition;
-                ctx.take_save_data_mut().award_data.taking_rest_count += 1;
-                ctx.go_next_day();
+                ctx.simulator().finish_schedule(DayWorkType::TakingRest);
             },
             self.get_current_clock() + 300
         );
@@ -574,8 +571,8 @@ impl ScenarioScene {
             DayWorkType::TakingRest => {
                 self.start_taking_rest_schedule(ctx);
             }
-            DayWorkType::GoingOut(_) => {
-                self.start_going_out_schedule(ctx);
+            DayWorkType::GoingOut(dest) => {
+                self.start_going_out_schedule(ctx, dest);
             }
         }
     }
@@ -683,7 +680,10 @@ impl SceneManager for ScenarioScene {
                 MouseButton::Left => {
                     let _t = self.get_current_clock();
 
-                    if self.scenario_event.contains_scenario_text_box(point) {
+                    if self.scenario_event.is_backlog_opened() {
+                        self.scenario_event.close_backlog(ctx);
+                    } else if self.scenario_event.contains_scenario_text_box(point) {
+                        self.cancel_skip_mode(ctx);
                         self.scenario_event.key_down_action1(
                             ctx,
                             Some(point),
@@ -696,6 +696,19 @@ impl SceneManager for ScenarioScene {
         }
     }
 
+    fn mouse_wheel_event<'a>(
+        &mut self,
+        ctx: &mut SuzuContext<'a>,
+        _point: numeric::Point2f,
+        _x: f32,
+        y: f32,
+    ) {
+        if !self.now_paused() {
+            let t = self.get_current_clock();
+            self.scenario_event.mouse_wheel_handler(ctx, y, t);
+        }
+    }
+
     fn pre_process<'a>(&mut self, ctx: &mut SuzuContext<'a>) {
         let t = self.get_current_clock();
 
@@ -704,8 +717,10 @@ impl SceneManager for ScenarioScene {
         if self.now_paused() {
         } else {
             // 再描画要求はupdate_textメソッドの中で行われている
+            self.scenario_event.update_staging(ctx, t);
             self.scenario_event
                 .update_text(ctx, Some(&mut self.scenario_ctx));
+            self.scenario_event.update_advance_mode(ctx, t);
 
             if self.scenario_event.get_status() == ScenarioEventStatus::StartSchedule
                 && !self.scenario_ctx.builtin_command_inexec
@@ -755,6 +770,11 @@ impl SceneManager for ScenarioScene {
                 self.scene_transition_type = scene_transition;
             }
 
+            // 既読情報を保存する
+            if let Err(e) = ctx.permanent_save_data.save() {
+                eprintln!("{}", e);
+            }
+
 	    match self.scene_transition {
 		SceneID::Save => {
 		    ctx.take_save_data_mut().scenario_save_data
@@ -20,7 +20,7 @@ use super::*;
 use crate::core::map_parser as mp;
 use crate::core::{
     BookInformation, FontID, MouseInformation, ResultReport, SavableData, SuzuContext,
-    TileBatchTextureID,
+    SuzuError, TileBatchTextureID,
 };
 use crate::flush_delay_event;
 use crate::flush_delay_event_and_redraw_check;
@@ -227,10 +227,10 @@ impl MapData {
         ctx: &mut SuzuContext<'a>,
         map_id: u32,
         camera: Rc<RefCell<numeric::Rect>>,
-    ) -> Self {
+    ) -> Result<Self, SuzuError> {
         let map_constract_data = ctx.resource.get_map_data(map_id).unwrap();
 
-        MapData {
+        Ok(MapData {
             tile_map: mp::StageObjectMap::new(
                 ctx.context,
                 &map_constract_data.map_file_path,
@@ -238,9 +238,9 @@ impl MapData {
                 numeric::Rect::new(0.0, 0.0, 1366.0, 768.0),
                 numeric::Vector2f::new(3.0, 3.0),
             ),
-            event_map: MapEventList::from_file(ctx, &map_constract_data.event_map_file_path),
+            event_map: MapEventList::from_file(ctx, &map_constract_data.event_map_file_path)?,
             scenario_event: None,
-        }
+        })
     }
 
     pub fn get_tile_size(&self) -> numeric::Vector2f {
@@ -252,6 +252,7 @@ impl MapData {
         trigger: EventTrigger,
         point: numeric::Point2f,
         _t: Clock,
+        save_data: &SavableData,
     ) -> Option<&MapEventElement> {
         let tile_size = self.get_tile_size();
         self.event_map.check_event(
@@ -260,6 +261,7 @@ impl MapData {
                 (point.x as f32 / tile_size.x) as i32,
                 (point.y as f32 / tile_size.y) as i32,
             ),
+            save_data,
         )
     }
 }
@@ -706,7 +708,7 @@ impl ShopScene {
         map_id: u32,
         new_books: Vec<BookInformation>,
         task_tutorial: TaskTutorialContext,
-    ) -> ShopScene {
+    ) -> Result<ShopScene, SuzuError> {
         let begining_save_data = ctx.take_save_data().clone();
 
         let camera = Rc::new(RefCell::new(numeric::Rect::new(0.0, 0.0, 1366.0, 768.0)));
@@ -722,12 +724,12 @@ impl ShopScene {
 
         let character_group = CharacterGroup::new();
 
-        let mut map = MapData::new(ctx, map_id, camera.clone());
+        let mut map = MapData::new(ctx, map_id, camera.clone())?;
         map.tile_map.build_collision_map();
 
         let shop_time = ShopClock::new(8, 0);
         let drawble_shop_clock =
-            DrawableShopClock::from_toml(ctx, "/other_config/shop_clock.toml", shop_time.clone());
+            DrawableShopClock::from_toml(ctx, SHOP_CLOCK_CONFIG_PATH, shop_time.clone())?;
 
         let mut result_report = ResultReport::new();
         for new_book in new_books.iter() {
@@ -792,7 +794,7 @@ impl ShopScene {
             Some(SoundPlayFlags::new(10000, 1.0, true, ctx.config.get_bgm_volume())),
         );
 
-        ShopScene {
+        Ok(ShopScene {
             mouse_info: MouseInformation::new(),
             player: player,
             character_group: character_group,
@@ -860,7 +862,7 @@ impl ShopScene {
             },
             task_tutorial_context: task_tutorial,
 	    shop_closing_lock: false,
-        }
+        })
     }
 
     ///
@@ -1343,9 +1345,12 @@ impl ShopScene {
         map_position: numeric::Point2f,
     ) -> Option<EventTrigger> {
         let t = self.get_current_clock();
-        let target_event =
-            self.map
-                .check_event_panel(trigger, map_position, self.get_current_clock());
+        let target_event = self.map.check_event_panel(
+            trigger,
+            map_position,
+            self.get_current_clock(),
+            ctx.take_save_data(),
+        );
 
         if let Some(event_element) = target_event {
             match event_element {
@@ -1720,7 +1725,7 @@ impl ShopScene {
                     slf.transition_status = SceneTransition::SwapTransition;
                     slf.transition_scene = SceneID::DayResult;
 
-                    ctx.resource.stop_bgm(ctx.context, SoundID::ShopBGM);
+                    ctx.resource.stop_all_bgm(ctx.context);
                 }),
                 t + 120,
             );
@@ -1817,7 +1822,7 @@ impl ShopScene {
             Box::new(|slf: &mut Self, ctx, _| {
                 slf.transition_status = SceneTransition::SwapTransition;
                 slf.transition_scene = SceneID::Title;
-		ctx.resource.stop_bgm(ctx.context, SoundID::ShopBGM);
+		ctx.resource.stop_all_bgm(ctx.context);
             }),
             t + 60,
         );
@@ -2341,6 +2346,7 @@ impl SceneManager for ShopScene {
         flush_delay_event_and_redraw_check!(self, self.event_list, ctx, t, {});
 
         if let Some(scenario_event) = self.map.scenario_event.as_mut() {
+            scenario_event.update_staging(ctx, t);
             scenario_event.update_text(ctx, None);
             self.try_finish_scenario_event(ctx, t);
         }
@@ -6,7 +6,7 @@ use torifune::core::*;
 use torifune::device::VirtualKey;
 use torifune::numeric;
 
-use crate::core::{GensoDate, SuzuContext};
+use crate::core::{GensoDate, SuzuContext, SuzuError};
 use crate::scene::*;
 
 use suzuna_sub_scene::*;
@@ -17,11 +17,11 @@ pub struct SuzunaScene {
 }
 
 impl SuzunaScene {
-    pub fn new<'a>(ctx: &mut SuzuContext<'a>, suzuna_map_id: u32) -> Self {
-        SuzunaScene {
+    pub fn new<'a>(ctx: &mut SuzuContext<'a>, suzuna_map_id: u32) -> Result<Self, SuzuError> {
+        Ok(SuzunaScene {
             clock: 0,
-            sub_scene: SuzunaSubScene::new(ctx, suzuna_map_id),
-        }
+            sub_scene: SuzunaSubScene::new(ctx, suzuna_map_id)?,
+        })
     }
 
     fn transition_shop_scene_to_others<'a>(
@@ -63,7 +63,7 @@ pub struct SuzunaSubScene {
 }
 
 impl SuzunaSubScene {
-    pub fn new<'a>(ctx: &mut SuzuContext<'a>, map_id: u32) -> Self {
+    pub fn new<'a>(ctx: &mut SuzuContext<'a>, map_id: u32) -> Result<Self, SuzuError> {
         let date = ctx.take_save_data().date.clone();
 
 	let todays_new_books = match ctx.take_save_data().game_mode {
@@ -80,19 +80,19 @@ impl SuzunaSubScene {
             TaskTutorialContext::new_done()
         };
 
-        SuzunaSubScene {
+        Ok(SuzunaSubScene {
             shop_scene: Some(Box::new(ShopScene::new(
                 ctx,
                 map_id,
                 todays_new_books.get_new_books(),
                 task_tutorial.clone(),
-            ))),
+            )?)),
             desk_work_scene: None,
             day_result_scene: None,
             scene_status: SuzunaSceneStatus::Shop,
             date: date,
             tutorial_context: task_tutorial,
-        }
+        })
     }
 
     pub fn get_shop_scene_mut(&mut self) -> Option<&mut Box<ShopScene>> {
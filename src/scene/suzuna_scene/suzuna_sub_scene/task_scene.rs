@@ -79,9 +79,10 @@ impl TaskScene {
         );
 
         if let Some(customer_request) = customer_request.as_ref() {
+            ctx.simulator().begin_customer_request(customer_request);
+
             match customer_request {
                 CustomerRequest::Borrowing(_) => {
-                    ctx.take_save_data_mut().award_data.borrowing_count += 1;
                     if !tutorial_context.borrowing_request {
                         event_list.add_event(
                             Box::new(move |slf: &mut TaskScene, ctx, t| {
@@ -97,7 +98,6 @@ impl TaskScene {
                     }
                 }
                 CustomerRequest::Returning(_) => {
-                    ctx.take_save_data_mut().award_data.returning_count += 1;
                     if !tutorial_context.returning_request {
                         event_list.add_event(
                             Box::new(move |slf: &mut TaskScene, ctx, t| {
@@ -174,24 +174,7 @@ impl TaskScene {
     }
 
     fn check_done_today_work<'a>(&mut self, ctx: &mut SuzuContext<'a>, request: CustomerRequest) {
-        let task_result = &mut ctx.take_save_data_mut().task_result;
-        match request {
-            CustomerRequest::Borrowing(request_information) => {
-                // 貸出本を記録
-                task_result.done_works += 1;
-                task_result.total_money += request_information.calc_fee();
-                task_result
-                    .borrowing_books
-                    .extend(request_information.borrowing);
-            }
-            CustomerRequest::Returning(request_information) => {
-                // 貸出本を記録
-                task_result.done_works += 1;
-                task_result
-                    .not_shelved_books
-                    .extend(request_information.returning);
-            }
-        }
+        ctx.simulator().complete_customer_request(request);
     }
 
     pub fn get_task_status(&self) -> TaskSceneStatus {
@@ -0,0 +1,18 @@
+extern crate suzu;
+
+use suzu::core::embedded::{embedded_resource, embedded_resource_paths};
+use suzu::core::error::parse_toml_str;
+
+#[test]
+fn embedded_resources_are_valid_toml() {
+    for path in embedded_resource_paths() {
+        let content = embedded_resource(path).unwrap();
+        parse_toml_str(path, content).unwrap();
+    }
+}
+
+#[test]
+fn unknown_path_is_not_embedded() {
+    assert!(embedded_resource("/scenario/day_7_23.toml").is_some());
+    assert!(embedded_resource("/scenario/unknown.toml").is_none());
+}
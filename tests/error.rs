@@ -0,0 +1,23 @@
+extern crate suzu;
+
+use suzu::core::error::{parse_toml_str, TomlNode};
+
+#[test]
+fn missing_key_reports_file_and_position() {
+    let root = parse_toml_str(
+        "day_7_25.toml",
+        "[[scenario-group]]\nid = 1\nnext-id = 2\n[[scenario-group]]\nid = 2\n",
+    )
+    .unwrap();
+    let groups = TomlNode::root("day_7_25.toml", &root)
+        .get("scenario-group")
+        .unwrap()
+        .as_array()
+        .unwrap();
+
+    let e = groups[1].get("next-id").err().unwrap();
+    assert_eq!(
+        e.to_string(),
+        "day_7_25.toml: scenario-group[1] missing next-id"
+    );
+}
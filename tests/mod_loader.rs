@@ -0,0 +1,154 @@
+extern crate suzu;
+
+use std::path::Path;
+
+use suzu::core::error::{parse_toml_str, TomlNode};
+use suzu::core::mod_loader::{LoadedMod, ModManifest, ModRegistry};
+use suzu::core::{BookInformation, GensoDate, ScenarioTable};
+
+fn load_mod(dir: &str, source: &str) -> LoadedMod {
+    let path = format!("{}/mod.toml", dir);
+    LoadedMod::new(
+        ModManifest::from_toml_str(&path, source).unwrap(),
+        &path,
+        Path::new(dir),
+    )
+}
+
+#[test]
+fn mods_are_sorted_by_load_order_and_later_mods_win() {
+    let registry_mods = vec![
+        load_mod(
+            "mods/b",
+            r#"name = "Late"
+load_order = 10
+customers_name = ["マリ", "アヤ"]
+[[books_information]]
+name = "女大学"
+pages = 64
+size = "小判本"
+billing_number = 3
+base_price = 80
+[[scenario-table]]
+path = "/scenario/late.suzu"
+date = { season = 1, month = 1, day = 2 }
+"#,
+        ),
+        load_mod(
+            "mods/a",
+            r#"name = "Early"
+[[scenario-table]]
+path = "/scenario/early.suzu"
+date = { season = 1, month = 1, day = 3 }
+"#,
+        ),
+    ];
+    let mut mods = ModRegistry::new(registry_mods);
+    assert_eq!(
+        mods.mods().iter().map(|m| m.name()).collect::<Vec<_>>(),
+        vec!["Early", "Late"]
+    );
+
+    let mut books = vec![BookInformation::new(
+        "女大学".to_string(),
+        128,
+        "大判本".to_string(),
+        3,
+        100,
+    )];
+    let mut customers = vec!["マリ".to_string()];
+    mods.merge_books_and_customers(&mut books, &mut customers);
+    assert_eq!(books.len(), 1);
+    assert_eq!(books[0].get_pages(), 64);
+    assert_eq!(customers, vec!["マリ".to_string(), "アヤ".to_string()]);
+
+    let base = parse_toml_str(
+        "scenario_table.toml",
+        r#"[[scenario-table]]
+path = "/scenario/base.toml"
+date = { season = 1, month = 1, day = 2 }
+[[general-scenario-table]]
+type = "NoEnoughMoney"
+path = "/scenario/no_money.toml"
+"#,
+    )
+    .unwrap();
+    let mut table =
+        ScenarioTable::from_toml_node(&TomlNode::root("scenario_table.toml", &base)).unwrap();
+    mods.merge_scenario_table(&mut table);
+
+    assert_eq!(
+        table.get_day_scenario_path(&GensoDate::new(1, 1, 2)),
+        Some("/scenario/late.suzu".to_string())
+    );
+    assert_eq!(
+        table.get_day_scenario_path(&GensoDate::new(1, 1, 3)),
+        Some("/scenario/early.suzu".to_string())
+    );
+
+    let kinds = mods
+        .conflicts()
+        .iter()
+        .map(|conflict| (conflict.kind, conflict.previous.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![("books_information", "base"), ("scenario-table", "base")]
+    );
+}
+
+#[test]
+fn event_panels_are_appended_to_the_target_map() {
+    let mut mods = ModRegistry::new(vec![load_mod(
+        "mods/a",
+        r#"name = "Panels"
+[[event-panel]]
+map = "/event_map1.toml"
+type = "text"
+text = "new"
+position = { x = 1, y = 2 }
+[[event-panel]]
+map = "/other.toml"
+type = "text"
+text = "elsewhere"
+position = { x = 5, y = 5 }
+"#,
+    )]);
+
+    let mut root = parse_toml_str(
+        "/event_map1.toml",
+        r#"[[event-panel]]
+type = "text"
+text = "base"
+position = { x = 1, y = 2 }
+"#,
+    )
+    .unwrap();
+
+    assert!(mods.has_event_panels_for("/event_map1.toml"));
+    mods.check_event_panel_conflicts("/event_map1.toml", &root);
+    assert_eq!(mods.conflicts().len(), 1);
+
+    mods.append_event_panels("/event_map1.toml", &mut root);
+    let panels = root["event-panel"].as_array().unwrap();
+    assert_eq!(panels.len(), 2);
+    assert_eq!(panels[1]["text"].as_str(), Some("new"));
+}
+
+#[test]
+fn manifest_errors_report_the_key_path() {
+    let error = ModManifest::from_toml_str(
+        "mods/a/mod.toml",
+        r#"name = "Broken"
+[[scenario-table]]
+path = "/scenario/broken.suzu"
+date = { season = 1, month = 13.5, day = 2 }
+"#,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "mods/a/mod.toml: scenario-table[0].date.month invalid type: floating point `13.5`, expected u8"
+    );
+}
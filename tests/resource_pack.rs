@@ -0,0 +1,143 @@
+extern crate suzu;
+extern crate toml;
+
+use suzu::core::error::parse_toml_str;
+use suzu::core::resource_pack::{self, merge_toml_patch, ResourcePack};
+
+#[test]
+fn pack_round_trip_and_override_order() {
+    let dir = std::env::temp_dir().join(format!("suzu-pack-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let base = dir.join("00-base.suzupack");
+    let over = dir.join("10-mod.suzupack");
+    ResourcePack::write(
+        &base,
+        &[
+            ("/a.txt".to_string(), b"base a".to_vec()),
+            ("/b.txt".to_string(), b"base b".to_vec()),
+        ],
+        false,
+    )
+    .unwrap();
+    ResourcePack::write(&over, &[("/a.txt".to_string(), b"mod a".to_vec())], false).unwrap();
+
+    let pack = ResourcePack::open(&base).unwrap();
+    assert!(pack.contains("/b.txt"));
+    assert_eq!(pack.read("/b.txt").unwrap().unwrap(), b"base b".to_vec());
+    assert!(pack.read("/c.txt").is_none());
+
+    assert!(resource_pack::mount_directory(&dir).is_empty());
+    assert_eq!(
+        resource_pack::read_mounted("/a.txt").unwrap().unwrap(),
+        b"mod a".to_vec()
+    );
+    assert_eq!(
+        resource_pack::read_mounted("/b.txt").unwrap().unwrap(),
+        b"base b".to_vec()
+    );
+
+    let entry = resource_pack::locate_mounted("/a.txt").unwrap();
+    let bytes = std::thread::spawn(move || entry.read().unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(bytes, b"mod a".to_vec());
+
+    // マウントは他のスレッドからも見える
+    let bytes = std::thread::spawn(|| resource_pack::read_mounted("/b.txt").unwrap().unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(bytes, b"base b".to_vec());
+    resource_pack::unmount_all();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn pack_with_oversized_index_is_rejected() {
+    let path =
+        std::env::temp_dir().join(format!("suzu-broken-pack-{}.suzupack", std::process::id()));
+    let mut bytes = b"SUZUPACK".to_vec();
+    bytes.extend_from_slice(&1u32.to_le_bytes());
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&u64::MAX.to_le_bytes());
+    bytes.extend_from_slice(b"[]");
+    std::fs::write(&path, &bytes).unwrap();
+
+    let error = ResourcePack::open(&path).err().unwrap();
+    assert!(error.to_string().contains("index size exceeds file"));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn patch_appends_books_and_replaces_elements_by_id() {
+    let mut base = parse_toml_str(
+        "base.toml",
+        r#"first-scenario-id = 1
+[[books_information]]
+name = "A"
+[[scenario-group]]
+id = 1
+type = "wait"
+[[scenario-group]]
+id = 2
+type = "wait"
+"#,
+    )
+    .unwrap();
+    let patch = parse_toml_str(
+        "base.patch.toml",
+        r#"first-scenario-id = 2
+[[books_information]]
+name = "B"
+[[scenario-group]]
+id = 2
+type = "scenario"
+"#,
+    )
+    .unwrap();
+
+    merge_toml_patch(&mut base, patch);
+
+    assert_eq!(base["first-scenario-id"].as_integer(), Some(2));
+    assert_eq!(base["books_information"].as_array().unwrap().len(), 2);
+    let group = base["scenario-group"].as_array().unwrap();
+    assert_eq!(group.len(), 2);
+    assert_eq!(group[1]["type"].as_str(), Some("scenario"));
+}
+
+#[test]
+fn patch_replaces_books_by_name_and_appends_marked_arrays() {
+    let mut base = parse_toml_str(
+        "game_data.toml",
+        r#"customers_name = ["マリ"]
+[[books_information]]
+name = "女大学"
+pages = 128
+[[books_information]]
+name = "竹取物語"
+pages = 64
+"#,
+    )
+    .unwrap();
+    let patch = parse_toml_str(
+        "game_data.patch.toml",
+        r#""customers_name+" = ["アヤ"]
+[[books_information]]
+name = "女大学"
+pages = 96
+"#,
+    )
+    .unwrap();
+
+    merge_toml_patch(&mut base, patch);
+
+    let books = base["books_information"].as_array().unwrap();
+    assert_eq!(books.len(), 2);
+    assert_eq!(books[0]["pages"].as_integer(), Some(96));
+    assert_eq!(
+        base["customers_name"].as_array().unwrap(),
+        &vec![toml::Value::from("マリ"), toml::Value::from("アヤ")]
+    );
+}
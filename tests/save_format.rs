@@ -0,0 +1,120 @@
+extern crate serde_json;
+extern crate suzu;
+
+use suzu::core::save_format::{load_save_data, wrap_save_data, SAVE_MIGRATIONS};
+use suzu::core::simulation::EconomyData;
+use suzu::core::story::StoryVariables;
+use suzu::core::{GameMode, SavableData, Version};
+
+const DAILY_CUSTOMER_DIST: &str = include_str!("daily_customer_dist.toml");
+
+fn new_savable_data() -> SavableData {
+    let game_data = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/resources/game_data.toml"
+    ))
+    .unwrap();
+    let data = EconomyData::from_toml_str(&game_data, DAILY_CUSTOMER_DIST).unwrap();
+    SavableData::new(&data, GameMode::story())
+}
+
+#[test]
+fn current_save_loads_without_migration() {
+    let content = wrap_save_data(&new_savable_data()).unwrap();
+    let (_, report) = load_save_data("save1", &content).unwrap();
+
+    assert!(report.saved_version == Some(Version::this()));
+    assert!(!report.migrated());
+}
+
+#[test]
+fn versions_are_read_major_first() {
+    assert_eq!(Version::from_str("1.0.2").to_string(), "1.0.2");
+    assert!(Version::from_str("1.0.2") < Version::from_str("1.1.0"));
+    assert!(Version::try_from_str("1.0").is_none());
+}
+
+#[test]
+fn migration_targets_end_at_the_current_version() {
+    let targets = SAVE_MIGRATIONS
+        .iter()
+        .map(|migration| Version::from_str(migration.target))
+        .collect::<Vec<_>>();
+
+    assert!(targets.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(targets.iter().all(|target| *target <= Version::this()));
+    assert!(targets.last() == Some(&Version::this()));
+}
+
+#[test]
+fn complete_legacy_save_is_not_reported_as_migrated() {
+    let legacy = serde_json::to_value(&new_savable_data()).unwrap();
+    let (_, report) = load_save_data("save1", &legacy.to_string()).unwrap();
+
+    assert!(report.saved_version.is_none());
+    assert!(!report.migrated());
+}
+
+#[test]
+fn corrupt_envelope_is_an_error() {
+    let content = r#"{"version": "1.0.2", "payload": {}}"#;
+    assert!(load_save_data("save1", content).is_err());
+}
+
+#[test]
+fn legacy_save_is_migrated() {
+    let mut legacy = serde_json::to_value(&new_savable_data()).unwrap();
+    {
+        let map = legacy.as_object_mut().unwrap();
+        map.remove("run_tutorial");
+        map.remove("scenario_save_data");
+        map.remove("story_variables");
+        map.get_mut("award_data")
+            .unwrap()
+            .as_object_mut()
+            .unwrap()
+            .remove("going_out_count");
+    }
+
+    let (savable_data, report) = load_save_data("save1", &legacy.to_string()).unwrap();
+
+    assert_eq!(report.applied_migrations.len(), SAVE_MIGRATIONS.len());
+    assert!(savable_data.run_tutorial);
+    assert_eq!(savable_data.award_data.going_out_count, 0);
+    assert_eq!(savable_data.story_variables, StoryVariables::new());
+}
+
+#[test]
+fn envelope_without_story_variables_is_migrated() {
+    let mut data = serde_json::to_value(&new_savable_data()).unwrap();
+    data.as_object_mut().unwrap().remove("story_variables");
+    let content = serde_json::json!({ "version": "1.0.2", "data": data }).to_string();
+
+    let (savable_data, report) = load_save_data("save1", &content).unwrap();
+
+    assert!(report.saved_version == Some(Version::from_str("1.0.2")));
+    assert_eq!(
+        report.applied_migrations,
+        vec![
+            "シナリオのフラグと変数を追加",
+            "選択肢の記録とテキスト変数を追加"
+        ]
+    );
+    assert_eq!(savable_data.story_variables, StoryVariables::new());
+}
+
+#[test]
+fn story_variables_without_choices_are_migrated() {
+    let mut data = serde_json::to_value(&new_savable_data()).unwrap();
+    data["story_variables"] =
+        serde_json::json!({ "flags": { "met_marisa": true }, "variables": {} });
+    let content = serde_json::json!({ "version": "1.0.2", "data": data }).to_string();
+
+    let (savable_data, report) = load_save_data("save1", &content).unwrap();
+
+    assert_eq!(
+        report.applied_migrations,
+        vec!["選択肢の記録とテキスト変数を追加"]
+    );
+    assert!(savable_data.story_variables.get_flag("met_marisa"));
+}
@@ -1,6 +1,22 @@
 extern crate suzu;
 
+use suzu::core::error::{parse_toml_str, TomlNode};
 use suzu::core::GensoDate;
+use suzu::object::scenario::{ChoiceCountdown, ScenarioElement, ScenarioElementPool};
+
+const TIMED_CHOICE: &str = concat!(
+    include_str!("scenario_header.toml"),
+    r#"[[scenario-group]]
+type = "choice"
+header_text = "どうする?"
+id = 1
+time-limit = 3
+timeout-jump-id = 100
+   [[scenario-group.choice-pattern]]
+   pattern = "店番"
+   jump-id = 101
+"#
+);
 
 #[test]
 fn day_diff_works() {
@@ -8,3 +24,30 @@ fn day_diff_works() {
     assert_eq!(GensoDate::new(112, 7, 23).diff_day(&GensoDate::new(112, 8, 1)), 9);
     assert_eq!(GensoDate::new(112, 8, 1).diff_day(&GensoDate::new(112, 7, 23)), -9);
 }
+
+#[test]
+fn timed_choice_jumps_to_timeout_id_when_countdown_runs_out() {
+    let root = parse_toml_str("choice.toml", TIMED_CHOICE).unwrap();
+    let pool = ScenarioElementPool::from_toml(
+        &TomlNode::root("choice.toml", &root),
+        ggez::graphics::Font::default(),
+    )
+    .ok()
+    .unwrap();
+
+    let choice = match pool.seq_access(pool.find_index_of_specified_scenario_id(1)) {
+        Some(ScenarioElement::ChoiceSwitch(choice)) => choice,
+        _ => panic!("element 1 is not a choice"),
+    };
+
+    let (limit, timeout_id) = choice.get_time_limit().unwrap();
+    let mut countdown = ChoiceCountdown::new(limit);
+    assert!(!countdown.tick());
+    assert!(!countdown.tick());
+    assert!(countdown.tick());
+    assert_eq!(countdown.remain(), 0);
+
+    let timeout_index = choice.get_choice_patterns().len();
+    assert_eq!(choice.get_jump_id(timeout_index), Some(timeout_id));
+    assert_eq!(timeout_id, 100);
+}
@@ -0,0 +1,50 @@
+extern crate suzu;
+
+use suzu::core::error::{parse_toml_str, TomlNode};
+use suzu::object::scenario::ScenarioElementPool;
+use suzu::object::scenario_graph::ScenarioGraph;
+
+const SCENARIO: &str = concat!(
+    include_str!("scenario_header.toml"),
+    r#"[[scenario-group]]
+type = "choice"
+header_text = "どうする?"
+id = 1
+time-limit = 300
+timeout-jump-id = 100
+   [[scenario-group.choice-pattern]]
+   pattern = "店番"
+   jump-id = 2
+   [[scenario-group.choice-pattern]]
+   pattern = "保存"
+   jump-id = 102
+
+[[scenario-group]]
+type = "switch"
+id = 2
+opecode = "cleared"
+yes = 101
+no = 100
+"#
+);
+
+#[test]
+fn choice_and_switch_labels_are_on_edges() {
+    let root = parse_toml_str("graph.toml", SCENARIO).unwrap();
+    let pool = ScenarioElementPool::from_toml(
+        &TomlNode::root("graph.toml", &root),
+        ggez::graphics::Font::default(),
+    )
+    .ok()
+    .unwrap();
+
+    let dot = ScenarioGraph::from_pool(&pool, Some(1)).to_dot();
+    assert!(dot.contains("n1 -> n2 [label=\"店番\"];"));
+    assert!(dot.contains("n1 -> n102 [label=\"保存\"];"));
+    assert!(dot.contains("n2 -> n101 [label=\"cleared\"];"));
+    assert!(dot.contains("n2 -> n100 [label=\"!cleared\"];"));
+    assert!(dot.contains("n1 -> n100 [label=\"(時間切れ)\"];"));
+
+    let mermaid = ScenarioGraph::from_pool(&pool, Some(1)).to_mermaid();
+    assert!(mermaid.contains("n1 -->|\"店番\"| n2"));
+}
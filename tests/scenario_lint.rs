@@ -0,0 +1,106 @@
+extern crate suzu;
+
+use suzu::object::scenario_lint::{lint_scenario_corpus, lint_scenario_source, LintSeverity};
+
+const BROKEN_SCENARIO: &str = concat!(
+    include_str!("scenario_header.toml"),
+    r#"[[scenario-group]]
+type = "wait"
+id = 1
+next-id = 2
+opecode = "ShowNothing"
+
+[[scenario-group]]
+type = "switch"
+id = 2
+opecode = "cleared"
+yes = 100
+no = 99
+
+[[scenario-group]]
+type = "wait"
+id = 2
+next-id = 100
+opecode = "ShowMain"
+background = "NoSuchTexture"
+"#
+);
+
+#[test]
+fn lint_reports_broken_scenario() {
+    let diagnostics = lint_scenario_source("broken.toml", BROKEN_SCENARIO);
+    let messages = diagnostics
+        .iter()
+        .filter(|d| d.severity == LintSeverity::Error)
+        .map(|d| d.message.clone())
+        .collect::<Vec<_>>();
+
+    assert!(messages.contains(
+        &"broken.toml: scenario-group[0].opecode has unknown value \"ShowNothing\"".to_string()
+    ));
+    assert!(messages.contains(
+        &"broken.toml: scenario-group[2].background has unknown value \"NoSuchTexture\""
+            .to_string()
+    ));
+    assert!(messages
+        .contains(&"broken.toml: scenario-group[1].no points to undefined id 99".to_string()));
+    assert!(messages
+        .iter()
+        .any(|m| m.contains("scenario-group[2] duplicates id 2")));
+
+    // dreamとsaveへは飛んでいないが, シーン切り替えは到達不能として報告しない
+    assert!(!diagnostics
+        .iter()
+        .any(|d| d.message.contains("scene-transition")));
+}
+
+fn text_src_scenario(text_src_path: &str) -> String {
+    format!(
+        r#"{}[[scenario-group]]
+type = "scenario"
+id = 1
+next-id = 100
+   [scenario-group.default-text-attribute]
+   fpc = 2.0
+   font_scale = 32.0
+   color = 0x000000ff
+   [[scenario-group.text]]
+   text_src_path = "{}"
+"#,
+        include_str!("scenario_header.toml"),
+        text_src_path
+    )
+}
+
+#[test]
+fn corpus_lint_reads_text_src_path_from_the_resource_directory() {
+    let resource_dir = std::env::temp_dir().join(format!("suzu-lint-test-{}", std::process::id()));
+    let scenario_dir = resource_dir.join("scenario");
+    std::fs::create_dir_all(&scenario_dir).unwrap();
+    std::fs::write(resource_dir.join("scenario_table.toml"), "").unwrap();
+    std::fs::write(scenario_dir.join("found.txt"), "いらっしゃいませ。").unwrap();
+    std::fs::write(
+        scenario_dir.join("found.toml"),
+        text_src_scenario("/scenario/found.txt"),
+    )
+    .unwrap();
+    std::fs::write(
+        scenario_dir.join("missing.toml"),
+        text_src_scenario("/scenario/missing.txt"),
+    )
+    .unwrap();
+
+    let errors = lint_scenario_corpus(&resource_dir)
+        .into_iter()
+        .filter(|d| d.severity == LintSeverity::Error)
+        .map(|d| d.message)
+        .collect::<Vec<_>>();
+
+    assert!(!errors.iter().any(|m| m.starts_with("/scenario/found.toml")));
+    assert!(errors.contains(
+        &"/scenario/missing.toml: scenario-group[0].text[0].text_src_path /scenario/missing.txt: failed to load asset (not found in resources)"
+            .to_string()
+    ));
+
+    std::fs::remove_dir_all(&resource_dir).unwrap();
+}
@@ -0,0 +1,123 @@
+extern crate suzu;
+
+use suzu::core::error::TomlNode;
+use suzu::object::scenario::ScenarioElementPool;
+use suzu::object::scenario_graph::ScenarioGraph;
+use suzu::object::scenario_script::{compile_scenario_script, parse_scenario_source};
+
+const SCRIPT: &str = "# 一日目
+*start
+[bg SightBackground1]
+[tachie right=KosuzuTachie1]
+@小鈴
+さてと、
+昨日の続きでも読もうかな。
+
+? どうする?
+> 店番 -> shop
+> 寝る -> dream
+
+*shop
+@
+いらっしゃいませ。
+-> scenario
+";
+
+#[test]
+fn script_compiles_to_scenario_groups() {
+    let root = compile_scenario_script("day.suzu", SCRIPT).unwrap();
+
+    assert_eq!(root["first-scenario-id"].as_integer(), Some(100));
+    assert_eq!(root["scene-transition"]["dream"].as_integer(), Some(2));
+
+    let group = root["scenario-group"].as_array().unwrap();
+    assert_eq!(group.len(), 3);
+
+    assert_eq!(group[0]["type"].as_str(), Some("scenario"));
+    assert_eq!(group[0]["next-id"].as_integer(), Some(101));
+    assert_eq!(group[0]["speaker"].as_str(), Some("小鈴"));
+    assert_eq!(group[0]["background"].as_str(), Some("SightBackground1"));
+    assert_eq!(
+        group[0]["tachie-data"]["right"].as_str(),
+        Some("KosuzuTachie1")
+    );
+    assert_eq!(
+        group[0]["text"][0]["text"].as_str(),
+        Some("さてと、\n昨日の続きでも読もうかな。")
+    );
+
+    assert_eq!(group[1]["type"].as_str(), Some("choice"));
+    assert_eq!(group[1]["header_text"].as_str(), Some("どうする?"));
+    assert_eq!(
+        group[1]["choice-pattern"][0]["jump-id"].as_integer(),
+        Some(102)
+    );
+    assert_eq!(
+        group[1]["choice-pattern"][1]["jump-id"].as_integer(),
+        Some(2)
+    );
+
+    assert!(group[2].get("speaker").is_none());
+    assert_eq!(group[2]["next-id"].as_integer(), Some(1));
+}
+
+const EQUIVALENT_TOML: &str = r#"first-scenario-id = 100
+
+[scene-transition]
+scenario = 1
+dream = 2
+save = 4
+
+[[scenario-group]]
+type = "scenario"
+id = 100
+next-id = 101
+speaker = "小鈴"
+background = "SightBackground1"
+tachie-data = { right = "KosuzuTachie1" }
+default-text-attribute = { fpc = 2.0, font_scale = 32.0, color = 255 }
+text = [ { text = "さてと、\n昨日の続きでも読もうかな。" } ]
+
+[[scenario-group]]
+type = "choice"
+id = 101
+header_text = "どうする?"
+background = "SightBackground1"
+tachie-data = { right = "KosuzuTachie1" }
+choice-pattern = [
+    { pattern = "店番", jump-id = 102 },
+    { pattern = "寝る", jump-id = 2 },
+]
+
+[[scenario-group]]
+type = "scenario"
+id = 102
+next-id = 1
+background = "SightBackground1"
+tachie-data = { right = "KosuzuTachie1" }
+default-text-attribute = { fpc = 2.0, font_scale = 32.0, color = 255 }
+text = [ { text = "いらっしゃいませ。" } ]
+"#;
+
+#[test]
+fn script_compiles_to_same_scenario_as_handwritten_toml() {
+    let script = parse_scenario_source("day.suzu", SCRIPT).unwrap();
+    let toml = parse_scenario_source("day.toml", EQUIVALENT_TOML).unwrap();
+    assert_eq!(script, toml);
+
+    let pool = ScenarioElementPool::from_toml(
+        &TomlNode::root("day.suzu", &script),
+        ggez::graphics::Font::default(),
+    )
+    .unwrap();
+
+    let dot = ScenarioGraph::from_pool(&pool, Some(100))
+        .export(suzu::object::scenario_graph::ScenarioGraphFormat::Dot);
+    assert!(dot.contains("n101 -> n102 [label=\"店番\"]"));
+}
+
+#[test]
+fn undefined_label_reports_line() {
+    let error = compile_scenario_script("bad.suzu", "こんにちは\n-> nowhere\n").unwrap_err();
+    assert_eq!(error.to_string(), "bad.suzu:2: undefined label *nowhere");
+}
@@ -0,0 +1,98 @@
+extern crate suzu;
+
+use suzu::core::book_management::NewBookScheduleFile;
+use suzu::core::error::{parse_toml_str, TomlNode};
+use suzu::core::schema;
+use suzu::core::SuzuError;
+use suzu::object::map_object::MapEventList;
+use suzu::object::shop_object::ConfigVector;
+
+fn validate_str(
+    path: &str,
+    content: &str,
+    validate: fn(&TomlNode, &mut Vec<SuzuError>),
+) -> Vec<SuzuError> {
+    let root = parse_toml_str(path, content).unwrap();
+    let mut problems = Vec::new();
+    validate(&TomlNode::root(path, &root), &mut problems);
+    problems
+}
+
+fn messages(problems: &[SuzuError]) -> Vec<String> {
+    problems.iter().map(|problem| problem.to_string()).collect()
+}
+
+#[test]
+fn every_problem_is_reported_with_its_key_path() {
+    let problems = validate_str(
+        "/other_config/new_book_schedule.toml",
+        r#"[[new-book-schedule]]
+date = { season = 112, month = "7", day = 23 }
+[[new-book-schedule.books_information]]
+name = "アンの手記"
+pages = 128
+size = "中判本"
+billing_number = 7
+base_price = 100
+num = 2
+
+[[new-book-schedule]]
+date = { season = 112, month = 7, day = 24 }
+[[new-book-schedule.books_information]]
+name = "アンの手記"
+pages = 128
+size = "中判本"
+billing_number = 7
+num = 2
+"#,
+        NewBookScheduleFile::validate,
+    );
+
+    assert_eq!(
+        messages(&problems),
+        vec![
+            "/other_config/new_book_schedule.toml: new-book-schedule[0].date.month invalid type: string \"7\", expected u8",
+            "/other_config/new_book_schedule.toml: new-book-schedule[1].books_information[0] missing field `base_price`",
+        ]
+    );
+}
+
+#[test]
+fn event_panels_check_fields_of_their_type() {
+    let problems = validate_str(
+        "events.toml",
+        r#"event-panel = [
+    { type = "text", trigger = "action", text = "hello", position = { x = 1, y = 2 } },
+    { type = "book-shelf", trigger = "action", position = { x = 1, y = 3 }, shelf-info = { begin-number = 1.5, end-number = 2 } },
+    { type = "door", position = { x = 1, y = 4 } },
+    { type = "text", trigger = "action", text = "hello", position = { x = 1, y = 5 }, condition = "cleared &&" },
+]
+"#,
+        MapEventList::validate,
+    );
+
+    let messages = messages(&problems);
+    assert_eq!(messages.len(), 3);
+    assert_eq!(
+        messages[0],
+        "events.toml: event-panel[1].shelf-info.begin-number invalid type: floating point `1.5`, expected u16"
+    );
+    assert_eq!(
+        messages[1],
+        "events.toml: event-panel[2].type has unknown value \"door\""
+    );
+    assert!(messages[2].starts_with("events.toml: event-panel[3].condition has unknown value"));
+
+    let error = SuzuError::Validation { problems: problems };
+    assert!(error
+        .to_string()
+        .starts_with("3 problems found in config files\n  "));
+}
+
+#[test]
+fn integer_literals_are_read_as_floats() {
+    let root = parse_toml_str("shop_clock.toml", "x = 1\ny = 2.5\n").unwrap();
+    let vector: ConfigVector = schema::deserialize("shop_clock.toml", root).unwrap();
+
+    assert_eq!((vector.x, vector.y), (1.0, 2.5));
+}
@@ -0,0 +1,71 @@
+extern crate suzu;
+
+use suzu::core::game_system::{DayWorkType, GoingOutEvent};
+use suzu::core::simulation::{EconomyData, ScheduleRejection, SuzunaSimulator};
+use suzu::core::{GameMode, ResultReport, SavableData};
+
+const DAILY_CUSTOMER_DIST: &str = include_str!("daily_customer_dist.toml");
+
+fn load_economy_data() -> EconomyData {
+    let game_data = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/resources/game_data.toml"
+    ))
+    .unwrap();
+    EconomyData::from_toml_str(&game_data, DAILY_CUSTOMER_DIST).unwrap()
+}
+
+#[test]
+fn simulate_week_without_window() {
+    let data = load_economy_data();
+    let mut save_data = SavableData::new(&data, GameMode::story());
+    let first_day = save_data.date;
+
+    let logs = SuzunaSimulator::new(&data, &mut save_data).simulate_week(
+        [
+            DayWorkType::ShopWork,
+            DayWorkType::TakingRest,
+            DayWorkType::GoingOut(GoingOutEvent::Dangoya),
+            DayWorkType::ShopWork,
+            DayWorkType::ShopWork,
+            DayWorkType::ShopWork,
+            DayWorkType::TakingRest,
+        ],
+        |_, _| (Vec::new(), ResultReport::new()),
+    );
+
+    assert_eq!(logs.len(), 7);
+    assert!(logs.iter().all(|log| log.result.is_ok()));
+    assert_eq!(first_day.diff_day(&save_data.date), 7);
+    assert_eq!(save_data.award_data.shop_work_count, 4);
+    assert_eq!(save_data.award_data.going_out_count, 1);
+    assert_eq!(save_data.task_result.total_money, 600);
+}
+
+#[test]
+fn shop_work_is_rejected_without_hp() {
+    let data = load_economy_data();
+    let mut save_data = SavableData::new(&data, GameMode::story());
+    save_data.suzunaan_status.kosuzu_hp = 10.0;
+
+    let simulator = SuzunaSimulator::new(&data, &mut save_data);
+    assert_eq!(
+        simulator.plan_schedule(DayWorkType::ShopWork),
+        Err(ScheduleRejection::NoEnoughHp)
+    );
+}
+
+#[test]
+fn taking_rest_does_not_drop_reputation_below_zero() {
+    let data = load_economy_data();
+    let mut save_data = SavableData::new(&data, GameMode::story());
+    for ad_type in save_data.ad_status.clone().keys() {
+        save_data.change_ad_status(*ad_type, false);
+    }
+    let current = save_data.suzunaan_status.get_current_reputation();
+    save_data.suzunaan_status.add_reputation(1.0 - current);
+
+    let simulator = SuzunaSimulator::new(&data, &mut save_data);
+    let effect = simulator.plan_schedule(DayWorkType::TakingRest).unwrap();
+    assert_eq!(effect.reputation_diff, -1.0);
+}
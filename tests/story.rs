@@ -0,0 +1,47 @@
+extern crate suzu;
+
+use suzu::core::story::{StoryExpr, StoryValue, StoryVariables};
+
+#[test]
+fn condition_reads_flags_and_variables() {
+    let mut variables = StoryVariables::new();
+    variables.set_flag("met_akyu", true);
+    variables.set_variable("akyu_trust", 55);
+    variables.add_variable("akyu_trust", 10);
+
+    let lookup = |name: &str| variables.lookup(name);
+
+    let expr = StoryExpr::parse("akyu_trust >= 60 && met_akyu").unwrap();
+    assert!(expr.eval_bool(&lookup));
+
+    let expr = StoryExpr::parse("!(met_akyu || unknown_flag) || akyu_trust - 5 < 60").unwrap();
+    assert!(!expr.eval_bool(&lookup));
+
+    assert_eq!(
+        StoryExpr::parse("1 + 2 - 4").unwrap().eval(&lookup),
+        StoryValue::Int(-1)
+    );
+}
+
+#[test]
+fn syntax_error_reports_column() {
+    let err = StoryExpr::parse("akyu_trust >= && met_akyu").unwrap_err();
+    assert_eq!(err.to_string(), "expected value at column 15");
+}
+
+#[test]
+fn recorded_choice_is_queryable_by_file_stem() {
+    let mut variables = StoryVariables::new();
+    variables.record_choice("/scenario/day_7_25.toml", 5, 1, "断る");
+
+    let record = variables.get_choice("/scenario/day_7_25.toml", 5).unwrap();
+    assert_eq!(record.pattern, "断る");
+
+    let lookup = |name: &str| variables.lookup(name);
+    assert!(StoryExpr::parse("choice.day_7_25.5 == 2")
+        .unwrap()
+        .eval_bool(&lookup));
+    assert!(!StoryExpr::parse("choice.day_7_26.5")
+        .unwrap()
+        .eval_bool(&lookup));
+}
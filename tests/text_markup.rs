@@ -0,0 +1,84 @@
+extern crate suzu;
+
+use suzu::object::text_markup::{MarkupStyle, MarkupText};
+
+const DEFAULT: MarkupStyle = MarkupStyle {
+    fpc: 1.0,
+    font_scale: 28.0,
+    color: 0x000000ff,
+};
+
+#[test]
+fn markup_splits_runs_by_style_and_pause() {
+    let markup =
+        MarkupText::parse("所持金は{money}円. [color=ff0000]大事[/color]な[wait=30][[本]]")
+            .unwrap();
+    let runs = markup.build(DEFAULT, &|name: &str| {
+        if name == "money" {
+            Some("1200".to_string())
+        } else {
+            None
+        }
+    });
+
+    let texts = runs.iter().map(|run| run.text.as_str()).collect::<Vec<_>>();
+    assert_eq!(texts, vec!["所持金は1200円. ", "大事", "な", "[本]"]);
+    assert_eq!(runs[1].style.color, 0xff0000ff);
+    assert_eq!(runs[2].style, DEFAULT);
+    assert_eq!(runs[3].pause, 30);
+}
+
+#[test]
+fn unknown_variable_is_kept_and_nested_tags_restore_style() {
+    let markup = MarkupText::parse("[size=40]{customer}[speed=0.5]さん[/speed][/size]").unwrap();
+    let runs = markup.build(DEFAULT, &|_: &str| None);
+
+    assert_eq!(runs[0].text, "{customer}");
+    assert_eq!(runs[0].style.font_scale, 40.0);
+    assert_eq!(runs[1].style.fpc, 0.5);
+    assert_eq!(runs[1].style.font_scale, 40.0);
+}
+
+#[test]
+fn markup_errors_report_column() {
+    let err = MarkupText::parse("ここは[colr=ff0000]誤り").unwrap_err();
+    assert_eq!(err.to_string(), "unknown tag \"colr\" at column 4");
+
+    let err = MarkupText::parse("[size=30]閉じ忘れ[/color]").unwrap_err();
+    assert_eq!(err.position, 13);
+}
+
+#[test]
+fn unclosed_tags_are_errors() {
+    let err = MarkupText::parse("[color=ff0000]abc").unwrap_err();
+    assert_eq!(err.to_string(), "unclosed [color] tag at column 18");
+
+    let err = MarkupText::parse("[ruby=x]abc").unwrap_err();
+    assert_eq!(err.to_string(), "unclosed [ruby] tag at column 12");
+}
+
+#[test]
+fn ruby_becomes_its_own_run() {
+    let markup =
+        MarkupText::parse("[color=ff0000][ruby=ようかい]妖怪[/ruby]が出る[/color]").unwrap();
+    let runs = markup.build(DEFAULT, &|_: &str| None);
+
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].text, "妖怪");
+    assert_eq!(runs[0].ruby.as_deref(), Some("ようかい"));
+    assert_eq!(runs[0].style.color, 0xff0000ff);
+    assert_eq!(runs[1].text, "が出る");
+    assert_eq!(runs[1].ruby, None);
+
+    assert!(MarkupText::parse("[ruby=よみ]改\n行[/ruby]").is_err());
+    assert!(MarkupText::parse("[ruby=よみ][wait=10]字[/ruby]").is_err());
+}
+
+#[test]
+fn doubled_brackets_and_braces_are_escaped() {
+    let runs = MarkupText::parse("{{money}} [[本]]")
+        .unwrap()
+        .build(DEFAULT, &|_: &str| Some("1200".to_string()));
+
+    assert_eq!(runs[0].text, "{money} [本]");
+}
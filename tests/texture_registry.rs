@@ -0,0 +1,94 @@
+extern crate suzu;
+
+use std::str::FromStr;
+
+use suzu::core::error::parse_toml_str;
+use suzu::core::TextureID;
+
+#[test]
+fn builtin_names_resolve_to_constants() {
+    assert_eq!(
+        TextureID::from_str("KosuzuTachie1"),
+        Ok(TextureID::KosuzuTachie1)
+    );
+    assert_eq!(TextureID::KosuzuTachie1.name(), "KosuzuTachie1");
+    assert!(TextureID::from_str("NotDeclared").is_err());
+}
+
+#[test]
+fn registered_names_get_stable_handles() {
+    let id = TextureID::register("ModArtwork1");
+    assert_eq!(TextureID::register("ModArtwork1"), id);
+    assert_eq!(TextureID::from_str("ModArtwork1"), Ok(id));
+    assert_ne!(id, TextureID::Ghost1);
+}
+
+#[test]
+fn registered_names_are_shared_between_threads() {
+    let id = TextureID::register("ModArtwork2");
+    let found = std::thread::spawn(|| {
+        (
+            TextureID::from_str("ModArtwork2"),
+            TextureID::register("ModArtwork2"),
+        )
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(found, (Ok(id), id));
+    assert_eq!(id.name(), "ModArtwork2");
+}
+
+#[test]
+fn random_textures_are_chosen_from_declared_ones() {
+    let declared = TextureID::declare("ModArtwork3");
+    TextureID::register("OnlyReferenced");
+
+    for _ in 0..100 {
+        assert_ne!(
+            TextureID::select_random(),
+            TextureID::lookup("OnlyReferenced").unwrap()
+        );
+    }
+    assert_eq!(TextureID::lookup("ModArtwork3"), Some(declared));
+}
+
+#[test]
+fn game_data_declares_every_builtin_texture() {
+    let path = "./resources/game_data.toml";
+    let root = parse_toml_str(path, &std::fs::read_to_string(path).unwrap()).unwrap();
+    let textures = root["textures"].as_table().unwrap();
+
+    for id in TextureID::builtin_ids() {
+        assert!(
+            textures.contains_key(&id.name()),
+            "{} is not declared",
+            id.name()
+        );
+    }
+}
+
+#[test]
+fn texture_groups_refer_to_declared_textures() {
+    let path = "./resources/game_data.toml";
+    let root = parse_toml_str(path, &std::fs::read_to_string(path).unwrap()).unwrap();
+    let textures = root["textures"].as_table().unwrap();
+
+    for (group, names) in root["texture_groups"].as_table().unwrap() {
+        for name in names.as_array().unwrap() {
+            let name = name.as_str().unwrap();
+            assert!(textures.contains_key(name), "{}: {} is not declared", group, name);
+        }
+    }
+}
+
+#[test]
+fn tachie_speakers_refer_to_declared_textures() {
+    let path = "./resources/game_data.toml";
+    let root = parse_toml_str(path, &std::fs::read_to_string(path).unwrap()).unwrap();
+    let textures = root["textures"].as_table().unwrap();
+
+    for name in root["tachie_speakers"].as_table().unwrap().keys() {
+        assert!(textures.contains_key(name), "{} is not declared", name);
+    }
+}
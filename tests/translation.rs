@@ -0,0 +1,99 @@
+extern crate suzu;
+
+use suzu::core::error::parse_toml_str;
+use suzu::core::translation::TranslationCatalog;
+use suzu::core::util::set_resource_directory;
+
+const SCENARIO: &str = concat!(
+    include_str!("scenario_header.toml"),
+    r#"[[scenario-group]]
+type = "scenario"
+id = 1
+next-id = 2
+   [scenario-group.default-text-attribute]
+   fpc = 2.0
+   font_scale = 32.0
+   color = 0x000000ff
+   [[scenario-group.text]]
+   text = "いらっしゃいませ。"
+
+[[scenario-group]]
+type = "choice"
+header_text = "どうする?"
+id = 2
+   [[scenario-group.choice-pattern]]
+   pattern = "店番"
+   jump-id = 100
+"#
+);
+
+#[test]
+fn extract_and_inject_scenario_strings() {
+    let path = "/scenario/test.toml";
+    let mut root = parse_toml_str(path, SCENARIO).unwrap();
+
+    let mut catalog = TranslationCatalog::new("en");
+    assert_eq!(catalog.extract(path, &root).unwrap(), 3);
+
+    let entries = catalog.files.get_mut(path).unwrap();
+    assert_eq!(entries["1.text.0"].source, "いらっしゃいませ。");
+    assert_eq!(entries["2.header_text"].source, "どうする?");
+    entries.get_mut("1.text.0").unwrap().translation = "Welcome.".to_string();
+    entries.get_mut("2.pattern.0").unwrap().translation = "Mind the shop".to_string();
+
+    let catalog = TranslationCatalog::from_json(path, &catalog.to_json()).unwrap();
+    assert_eq!(catalog.inject(path, &mut root), 2);
+
+    let group = root["scenario-group"].as_array().unwrap();
+    assert_eq!(group[0]["text"][0]["text"].as_str(), Some("Welcome."));
+    assert_eq!(group[1]["header_text"].as_str(), Some("どうする?"));
+    assert_eq!(
+        group[1]["choice-pattern"][0]["pattern"].as_str(),
+        Some("Mind the shop")
+    );
+}
+
+#[test]
+fn changed_source_drops_translation() {
+    let path = "/title_contents/menu.toml";
+    let mut catalog = TranslationCatalog::new("en");
+
+    let root = parse_toml_str(path, "[[each_entry_data]]\ntext = \"開始\"\n").unwrap();
+    catalog.extract(path, &root).unwrap();
+    catalog
+        .files
+        .get_mut(path)
+        .unwrap()
+        .get_mut("entry.0")
+        .unwrap()
+        .translation = "Start".to_string();
+    assert_eq!(catalog.lookup(path, "entry.0"), Some("Start"));
+
+    let root = parse_toml_str(path, "[[each_entry_data]]\ntext = \"始める\"\n").unwrap();
+    catalog.extract(path, &root).unwrap();
+    assert_eq!(catalog.lookup(path, "entry.0"), None);
+}
+
+#[test]
+fn text_src_path_is_read_from_the_resource_directory() {
+    let resource_dir = std::env::temp_dir().join(format!("suzu-i18n-test-{}", std::process::id()));
+    std::fs::create_dir_all(resource_dir.join("scenario")).unwrap();
+    std::fs::write(resource_dir.join("scenario/1.txt"), "本日は晴天なり。").unwrap();
+    set_resource_directory(resource_dir.clone());
+
+    let path = "/scenario/test.toml";
+    let root = parse_toml_str(
+        path,
+        &SCENARIO.replace(
+            "text = \"いらっしゃいませ。\"",
+            "text_src_path = \"/scenario/1.txt\"",
+        ),
+    )
+    .unwrap();
+
+    let mut catalog = TranslationCatalog::new("en");
+    catalog.extract(path, &root).unwrap();
+    assert_eq!(catalog.files[path]["1.text.0"].source, "本日は晴天なり。");
+
+    std::fs::remove_dir_all(&resource_dir).unwrap();
+}